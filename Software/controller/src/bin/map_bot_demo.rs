@@ -6,9 +6,11 @@ use crossterm::terminal::{
 use device_query::keymap::Keycode::Comma;
 use glib::bitflags::_core::sync::atomic::AtomicBool;
 use sm4_controller::canopen_backend::{CANOpenBackend, ENCODER_RESOLUTION};
+use sm4_controller::differential_drive::DifferentialDriveBackend;
 use sm4_controller::draw;
 use sm4_controller::tui::{SystemEvent, SystemEvents};
-use sm4_shared::prelude::Position;
+use sm4_shared::prelude::{DifferentialDrive, Position};
+use std::f32::consts::PI;
 use std::io::Write;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -16,6 +18,11 @@ use std::time::Duration;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
+/// Radius of the wheels of the robot in meters.
+const WHEEL_RADIUS: f32 = 0.04;
+/// Distance between the wheels of the robot in meters.
+const TRACK_WIDTH: f32 = 0.2;
+
 enum Command {
     Forward,
     Rotate,
@@ -24,9 +31,12 @@ enum Command {
 }
 
 fn main() -> anyhow::Result<()> {
-    let backend = CANOpenBackend::new("can0", 0x01, 50000);
-    backend.set_axis1_enabled(true);
-    backend.set_axis2_enabled(true);
+    let mut drive = DifferentialDrive::new(WHEEL_RADIUS, TRACK_WIDTH);
+    // the left motor is mounted in the opposite direction
+    drive.set_left_inverted(true);
+    let robot = DifferentialDriveBackend::new(CANOpenBackend::new("can0", 0x01, 50000), drive);
+    robot.set_enabled(true);
+    robot.update_odometry();
 
     let mut running = Arc::new(AtomicBool::new(true));
 
@@ -35,7 +45,10 @@ fn main() -> anyhow::Result<()> {
         move || running.store(false, Ordering::SeqCst)
     });
 
+    // speed of the wheels in revolutions per second
     let speed = 0.5;
+    let linear = speed * 2.0 * PI * WHEEL_RADIUS;
+    let angular = linear / TRACK_WIDTH;
 
     let sequence = [
         Command::Forward,
@@ -66,17 +79,22 @@ fn main() -> anyhow::Result<()> {
 
     while running.load(Ordering::SeqCst) {
         for seq in sequence.iter() {
-            let (s1, s2) = match *seq {
-                Command::Forward => (speed, speed),
-                Command::Rotate => (0.5 * speed, -0.5 * speed),
-                Command::Backward => (-speed, -speed),
-                Command::Rotate2 => (-0.5 * speed, 0.5 * speed),
-            };
-
-            backend.set_axis1_target_velocity(-s1);
-            backend.set_axis2_target_velocity(s2);
+            match *seq {
+                Command::Forward => robot.set_velocity(linear, 0.0),
+                Command::Rotate => robot.set_velocity(0.0, -angular),
+                Command::Backward => robot.set_velocity(-linear, 0.0),
+                Command::Rotate2 => robot.set_velocity(0.0, angular),
+            }
 
             std::thread::sleep(Duration::from_millis(1000));
+
+            let pose = robot.update_odometry();
+            println!(
+                "x: {:.3} m, y: {:.3} m, heading: {:.1} deg",
+                pose.x,
+                pose.y,
+                pose.heading.to_degrees()
+            );
         }
     }
 
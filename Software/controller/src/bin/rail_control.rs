@@ -15,6 +15,7 @@ use sm4_controller::tui::SystemEvent;
 use sm4_controller::tui::SystemEvents;
 use sm4_shared::prelude::Axis;
 use sm4_shared::prelude::Position;
+use sm4_shared::prelude::UnitScaling;
 use sm4_shared::OnError;
 use std::io::Write;
 use std::sync::Arc;
@@ -104,12 +105,23 @@ impl I2CBackend {
         self.state.lock().target_position
     }
 
+    /// Moves the target position by the `distance` in millimeters, within the length of the rail.
+    fn move_by(&self, distance: f32) {
+        let target = RAIL.position_to_units(&self.get_target_position()) + distance;
+        self.set_target_position(RAIL.position_from_units(target.max(0.0).min(RAIL_LENGTH)));
+    }
+
     fn get_state(&self) -> AxisState {
         *self.state.lock()
     }
 }
 
-const MAX_POSITION: Position<ENCODER_RESOLUTION> = Position::<ENCODER_RESOLUTION>::new(10, 0);
+/// The rail is driven by a lead screw with the pitch of 8 mm.
+const RAIL: UnitScaling = UnitScaling::new(8.0, 1, 1, false);
+/// The length of the rail in millimeters.
+const RAIL_LENGTH: f32 = 80.0;
+/// The distance travelled by a single key press in millimeters.
+const STEP: f32 = 1.25;
 
 fn draw<B: Backend>(state: &AxisState, frame: &mut Frame<B>) {
     let chunks = Layout::default()
@@ -138,8 +150,9 @@ fn draw<B: Backend>(state: &AxisState, frame: &mut Frame<B>) {
         )
         .gauge_style(Style::default().fg(Color::Yellow))
         .percent(
-            (state.actual_position.get_increments() as f32 / MAX_POSITION.get_increments() as f32
-                * 100.0) as u16,
+            (RAIL.position_to_units(&state.actual_position) / RAIL_LENGTH * 100.0)
+                .max(0.0)
+                .min(100.0) as u16,
         );
     frame.render_widget(gauge, chunks[1]);
     draw_axis_block(state, frame, chunks[3]);
@@ -200,18 +213,11 @@ fn main() -> anyhow::Result<()> {
         terminal.draw(|frame| {
             draw(&backend.get_state(), frame);
         })?;
-        let position_increment = Position::<ENCODER_RESOLUTION>::new(0, 500);
         match system_events.recv() {
             SystemEvent::Input(key) => match key.code {
                 KeyCode::Char('q') => running = false,
-                KeyCode::Left => {
-                    backend
-                        .set_target_position(backend.get_target_position() - &position_increment);
-                }
-                KeyCode::Right => {
-                    backend
-                        .set_target_position(backend.get_target_position() + &position_increment);
-                }
+                KeyCode::Left => backend.move_by(-STEP),
+                KeyCode::Right => backend.move_by(STEP),
                 _ => {}
             },
             SystemEvent::Tick => {}
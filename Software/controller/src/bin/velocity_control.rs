@@ -31,7 +31,7 @@ fn main() -> anyhow::Result<()> {
             draw(&backend.get_state(), frame);
         })?;
         const INCREMENT: f32 = 0.05;
-        let position_increment = Position::<ENCODER_RESOLUTION>::new(0, 100);
+        let position_increment = Position::<ENCODER_RESOLUTION>::new(0, ENCODER_RESOLUTION / 32);
         match system_events.recv() {
             SystemEvent::Input(key) => match key.code {
                 KeyCode::Char('o') => {
@@ -45,6 +45,10 @@ fn main() -> anyhow::Result<()> {
                             .set_axis1_target_position(
                                 state.axis1.target_position + &position_increment,
                             ),
+                        sm4_shared::prelude::AxisMode::Homing
+                        | sm4_shared::prelude::AxisMode::Interpolated
+                        | sm4_shared::prelude::AxisMode::Gearing
+                        | sm4_shared::prelude::AxisMode::AutoTune => {}
                     }
                 }
                 KeyCode::Char('p') => {
@@ -58,6 +62,10 @@ fn main() -> anyhow::Result<()> {
                             .set_axis2_target_position(
                                 state.axis2.target_position + &position_increment,
                             ),
+                        sm4_shared::prelude::AxisMode::Homing
+                        | sm4_shared::prelude::AxisMode::Interpolated
+                        | sm4_shared::prelude::AxisMode::Gearing
+                        | sm4_shared::prelude::AxisMode::AutoTune => {}
                     }
                 }
                 KeyCode::Char('k') => {
@@ -72,6 +80,10 @@ fn main() -> anyhow::Result<()> {
                                 state.axis1.target_position - &position_increment,
                             );
                         }
+                        sm4_shared::prelude::AxisMode::Homing
+                        | sm4_shared::prelude::AxisMode::Interpolated
+                        | sm4_shared::prelude::AxisMode::Gearing
+                        | sm4_shared::prelude::AxisMode::AutoTune => {}
                     }
                 }
                 KeyCode::Char('l') => {
@@ -85,6 +97,10 @@ fn main() -> anyhow::Result<()> {
                             .set_axis2_target_position(
                                 state.axis2.target_position - &position_increment,
                             ),
+                        sm4_shared::prelude::AxisMode::Homing
+                        | sm4_shared::prelude::AxisMode::Interpolated
+                        | sm4_shared::prelude::AxisMode::Gearing
+                        | sm4_shared::prelude::AxisMode::AutoTune => {}
                     }
                 }
                 KeyCode::Char('n') => backend.toggle_axis1_mode(),
@@ -93,6 +109,10 @@ fn main() -> anyhow::Result<()> {
                     backend.set_axis1_enabled(!backend.axis1_enabled());
                     backend.set_axis2_enabled(!backend.axis2_enabled());
                 }
+                KeyCode::Char('r') => {
+                    backend.reset_axis1_fault();
+                    backend.reset_axis2_fault();
+                }
                 KeyCode::Char('q') => running = false,
                 _ => {}
             },
@@ -1,7 +1,7 @@
 use parking_lot::Mutex;
 use sm4_shared::prelude::{
-    AxisMode, Position, RxPDO1, RxPDO2, RxPDO3, RxPDO4, SerializePDO, TxPDO1, TxPDO2, TxPDO3,
-    TxPDO4,
+    AxisMode, ErrorCode, Position, RxPDO1, RxPDO2, RxPDO3, RxPDO4, SerializePDO, TxPDO1, TxPDO2,
+    TxPDO3, TxPDO4, MAX_MICROSTEPS,
 };
 use socketcan::canopen::{
     CANOpen, CANOpenNodeCommand, CANOpenNodeMessage, NMTCommand, NMTState, PDO,
@@ -10,7 +10,8 @@ use socketcan::CANFrame;
 use std::convert::TryFrom;
 use std::sync::Arc;
 
-pub const ENCODER_RESOLUTION: u32 = 16 * 200;
+/// The resolution of the positions exchanged with the driver, independent of the microstepping of the axes.
+pub const ENCODER_RESOLUTION: u32 = MAX_MICROSTEPS as u32 * 200;
 
 #[derive(Copy, Clone)]
 pub struct AxisState {
@@ -20,6 +21,10 @@ pub struct AxisState {
     pub target_velocity: f32,
     pub actual_position: Position<ENCODER_RESOLUTION>,
     pub target_position: Position<ENCODER_RESOLUTION>,
+    pub following_error: bool,
+    pub error_code: ErrorCode,
+    /// The fault reset requested by the user, sent with the next RxPDO1.
+    pub fault_reset: bool,
 }
 
 impl Default for AxisState {
@@ -31,6 +36,9 @@ impl Default for AxisState {
             target_velocity: 0.0,
             actual_position: Position::zero(),
             target_position: Position::zero(),
+            following_error: false,
+            error_code: ErrorCode::None,
+            fault_reset: false,
         }
     }
 }
@@ -40,6 +48,24 @@ impl AxisState {
         match self.mode {
             AxisMode::Velocity => "Velocity",
             AxisMode::Position => "Position",
+            AxisMode::Homing => "Homing",
+            AxisMode::Interpolated => "Interpolated",
+            AxisMode::Gearing => "Gearing",
+            AxisMode::AutoTune => "Auto-tune",
+        }
+    }
+
+    pub fn error_code(&self) -> &'static str {
+        match self.error_code {
+            ErrorCode::None => "None",
+            ErrorCode::DriverError => "Driver error",
+            ErrorCode::FollowingError => "Following error",
+            ErrorCode::OverTemperature => "Over-temperature",
+            ErrorCode::Undervoltage => "Undervoltage",
+            ErrorCode::LimitHit => "Limit hit",
+            ErrorCode::CommunicationLoss => "Communication loss",
+            ErrorCode::StepLoss => "Step loss",
+            ErrorCode::Stall => "Stall",
         }
     }
 }
@@ -98,7 +124,7 @@ impl CANOpenBackend {
                 if let Ok(Some(frame)) = receiver.recv().map(Option::<CANOpenNodeMessage>::from) {
                     match frame {
                         CANOpenNodeMessage::SyncReceived => {
-                            let state = state.lock();
+                            let mut state = state.lock();
                             sender
                                 .send(CANFrame::from(CANOpenNodeCommand::SendPDO(
                                     id,
@@ -108,12 +134,17 @@ impl CANOpenBackend {
                                         axis2_mode: state.axis2.mode,
                                         axis1_enabled: state.axis1.enabled,
                                         axis2_enabled: state.axis2.enabled,
+                                        axis1_fault_reset: state.axis1.fault_reset,
+                                        axis2_fault_reset: state.axis2.fault_reset,
                                     }
                                     .to_raw()
                                     .unwrap(),
                                     RxPDO1::len(),
                                 )))
                                 .unwrap();
+                            // the reset is requested only once
+                            state.axis1.fault_reset = false;
+                            state.axis2.fault_reset = false;
 
                             sender
                                 .send(CANFrame::from(CANOpenNodeCommand::SendPDO(
@@ -163,6 +194,10 @@ impl CANOpenBackend {
                                     let mut state = state.lock();
                                     state.voltage = pdo.battery_voltage as f32 / 1000.0;
                                     state.temperature = pdo.temperature as f32 / 10.0;
+                                    state.axis1.following_error = pdo.axis1_following_error;
+                                    state.axis2.following_error = pdo.axis2_following_error;
+                                    state.axis1.error_code = pdo.axis1_error_code;
+                                    state.axis2.error_code = pdo.axis2_error_code;
                                 }
                                 Err(_) => {
                                     println!("received malformed TxPDO1");
@@ -248,7 +283,11 @@ impl CANOpenBackend {
         let mut state = self.state.lock();
         state.axis1.mode = match state.axis1.mode {
             AxisMode::Velocity => AxisMode::Position,
-            AxisMode::Position => AxisMode::Velocity,
+            AxisMode::Position => AxisMode::Homing,
+            AxisMode::Homing => AxisMode::Interpolated,
+            AxisMode::Interpolated => AxisMode::Gearing,
+            AxisMode::Gearing => AxisMode::AutoTune,
+            AxisMode::AutoTune => AxisMode::Velocity,
         };
     }
 
@@ -256,10 +295,24 @@ impl CANOpenBackend {
         let mut state = self.state.lock();
         state.axis2.mode = match state.axis2.mode {
             AxisMode::Velocity => AxisMode::Position,
-            AxisMode::Position => AxisMode::Velocity,
+            AxisMode::Position => AxisMode::Homing,
+            AxisMode::Homing => AxisMode::Interpolated,
+            AxisMode::Interpolated => AxisMode::Gearing,
+            AxisMode::Gearing => AxisMode::AutoTune,
+            AxisMode::AutoTune => AxisMode::Velocity,
         };
     }
 
+    /// Resets the latched fault of the first axis.
+    pub fn reset_axis1_fault(&self) {
+        self.state.lock().axis1.fault_reset = true;
+    }
+
+    /// Resets the latched fault of the second axis.
+    pub fn reset_axis2_fault(&self) {
+        self.state.lock().axis2.fault_reset = true;
+    }
+
     pub fn set_axis1_target_position(&self, position: Position<ENCODER_RESOLUTION>) {
         self.state.lock().axis1.target_position = position;
     }
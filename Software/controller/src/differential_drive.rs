@@ -0,0 +1,50 @@
+use crate::canopen_backend::{CANOpenBackend, ENCODER_RESOLUTION};
+use parking_lot::Mutex;
+use sm4_shared::prelude::{DifferentialDrive, Pose};
+
+/// Differential drive robot with the left wheel driven by the first axis and the right wheel by the second axis.
+pub struct DifferentialDriveBackend {
+    backend: CANOpenBackend,
+    drive: Mutex<DifferentialDrive<ENCODER_RESOLUTION>>,
+}
+
+impl DifferentialDriveBackend {
+    pub fn new(backend: CANOpenBackend, drive: DifferentialDrive<ENCODER_RESOLUTION>) -> Self {
+        Self {
+            backend,
+            drive: Mutex::new(drive),
+        }
+    }
+
+    pub fn backend(&self) -> &CANOpenBackend {
+        &self.backend
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.backend.set_axis1_enabled(enabled);
+        self.backend.set_axis2_enabled(enabled);
+    }
+
+    /// Sets the forward velocity and the angular velocity (in radians per second) of the robot.
+    pub fn set_velocity(&self, linear: f32, angular: f32) {
+        let (left, right) = self.drive.lock().wheel_velocities(linear, angular);
+        self.backend.set_axis1_target_velocity(left.get_rps());
+        self.backend.set_axis2_target_velocity(right.get_rps());
+    }
+
+    /// Integrates the odometry from the last positions reported by the driver and returns the pose of the robot.
+    pub fn update_odometry(&self) -> Pose {
+        let state = self.backend.get_state();
+        self.drive
+            .lock()
+            .update_odometry(state.axis1.actual_position, state.axis2.actual_position)
+    }
+
+    pub fn pose(&self) -> Pose {
+        self.drive.lock().pose()
+    }
+
+    pub fn reset_odometry(&self, pose: Pose) {
+        self.drive.lock().reset_odometry(pose);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod canopen_backend;
+pub mod differential_drive;
 pub mod gui;
 pub mod tui;
 
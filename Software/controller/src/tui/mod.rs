@@ -75,6 +75,8 @@ fn draw_axis_block<B: Backend>(state: &AxisState, frame: &mut Frame<B>, target:
             state.actual_position.get_revolutions(),
             state.actual_position.get_angle(),
         )),
+        Spans::from(format!("following error: {}", state.following_error)),
+        Spans::from(format!("fault: {}", state.error_code())),
     ];
     let block = Block::default().borders(Borders::ALL).title(Span::styled(
         "Axis 1",
@@ -0,0 +1,153 @@
+use core::convert::TryFrom;
+
+/// A data frame with a standard identifier, as it is received from or transmitted to the CAN bus.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CANFrame {
+    id: u16,
+    data: [u8; 8],
+    len: usize,
+}
+
+impl CANFrame {
+    /// Creates a new frame, returns `None` when the `data` do not fit into a frame.
+    pub fn new(id: u16, data: &[u8]) -> Option<Self> {
+        if data.len() > 8 {
+            return None;
+        }
+        let mut raw = [0u8; 8];
+        raw[..data.len()].copy_from_slice(data);
+        Some(Self {
+            id,
+            data: raw,
+            len: data.len(),
+        })
+    }
+
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Returns the CANOpen message the frame carries, the device ID is ignored.
+    pub fn message(&self) -> Option<CANOpenMessage> {
+        CANOpenMessage::try_from(self.id & 0xff80).ok()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CANOpenMessage {
+    NMTNodeControl,
+    GlobalFailsafeCommand,
+    Sync,
+    Emergency,
+    TimeStamp,
+    TxPDO1,
+    RxPDO1,
+    TxPDO2,
+    RxPDO2,
+    TxPDO3,
+    RxPDO3,
+    TxPDO4,
+    RxPDO4,
+    TxSDO,
+    RxSDO,
+    NMTNodeMonitoring,
+}
+impl TryFrom<u16> for CANOpenMessage {
+    type Error = ();
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value & 0xff80 {
+            0x000 => Ok(Self::NMTNodeControl),
+            0x001 => Ok(Self::GlobalFailsafeCommand),
+            0x080 => Ok(Self::Sync),
+            0x081 => Ok(Self::Emergency),
+            0x100 => Ok(Self::TimeStamp),
+            0x180 => Ok(Self::TxPDO1),
+            0x200 => Ok(Self::RxPDO1),
+            0x280 => Ok(Self::TxPDO2),
+            0x300 => Ok(Self::RxPDO2),
+            0x380 => Ok(Self::TxPDO3),
+            0x400 => Ok(Self::RxPDO3),
+            0x480 => Ok(Self::TxPDO4),
+            0x500 => Ok(Self::RxPDO4),
+            0x580 => Ok(Self::TxSDO),
+            0x600 => Ok(Self::RxSDO),
+            0x700 => Ok(Self::NMTNodeMonitoring),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<&CANOpenMessage> for u16 {
+    fn from(message: &CANOpenMessage) -> Self {
+        match message {
+            CANOpenMessage::NMTNodeControl => 0x000,
+            CANOpenMessage::GlobalFailsafeCommand => 0x001,
+            CANOpenMessage::Sync => 0x080,
+            CANOpenMessage::Emergency => 0x081,
+            CANOpenMessage::TimeStamp => 0x100,
+            CANOpenMessage::TxPDO1 => 0x180,
+            CANOpenMessage::RxPDO1 => 0x200,
+            CANOpenMessage::TxPDO2 => 0x280,
+            CANOpenMessage::RxPDO2 => 0x300,
+            CANOpenMessage::TxPDO3 => 0x380,
+            CANOpenMessage::RxPDO3 => 0x400,
+            CANOpenMessage::TxPDO4 => 0x480,
+            CANOpenMessage::RxPDO4 => 0x500,
+            CANOpenMessage::TxSDO => 0x580,
+            CANOpenMessage::RxSDO => 0x600,
+            CANOpenMessage::NMTNodeMonitoring => 0x700,
+        }
+    }
+}
+
+impl CANOpenMessage {
+    /// Returns the raw ID of the message sent by or to the device with the `device_id`.
+    pub fn message_id_with_device(&self, device_id: u8) -> u16 {
+        match self {
+            CANOpenMessage::NMTNodeControl
+            | CANOpenMessage::GlobalFailsafeCommand
+            | CANOpenMessage::Sync
+            | CANOpenMessage::TimeStamp => u16::from(self),
+            CANOpenMessage::Emergency
+            | CANOpenMessage::TxPDO1
+            | CANOpenMessage::RxPDO1
+            | CANOpenMessage::TxPDO2
+            | CANOpenMessage::RxPDO2
+            | CANOpenMessage::TxPDO3
+            | CANOpenMessage::RxPDO3
+            | CANOpenMessage::TxPDO4
+            | CANOpenMessage::RxPDO4
+            | CANOpenMessage::TxSDO
+            | CANOpenMessage::RxSDO
+            | CANOpenMessage::NMTNodeMonitoring => u16::from(self) | device_id as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame() {
+        let frame = CANFrame::new(0x201, &[1, 2, 3]).unwrap();
+        assert_eq!(frame.data(), &[1, 2, 3]);
+        assert_eq!(frame.message(), Some(CANOpenMessage::RxPDO1));
+        assert_eq!(
+            CANFrame::new(0x080, &[]).unwrap().message(),
+            Some(CANOpenMessage::Sync)
+        );
+        assert!(CANFrame::new(0x201, &[0; 9]).is_none());
+    }
+
+    #[test]
+    fn message_id() {
+        assert_eq!(CANOpenMessage::TxSDO.message_id_with_device(0x01), 0x581);
+        assert_eq!(CANOpenMessage::Sync.message_id_with_device(0x01), 0x080);
+    }
+}
@@ -0,0 +1,28 @@
+//! The portable core of the SM4 firmware.
+//!
+//! The core contains the protocol handling and the state of the driver, that is the CANOpen NMT, PDO and SDO
+//! handling, the USB and I2C register access and the failsafe. It is independent of the microcontroller,
+//! the peripherals are accessed through the traits in the `peripherals` module, which are implemented
+//! by the board layer in the firmware and by mocks in the tests, so the core can be tested on the host.
+//!
+//! The `defmt` feature enables logging through `defmt`, without it the log messages are discarded.
+
+#![cfg_attr(not(test), no_std)]
+
+#[macro_use]
+mod log;
+mod can;
+mod node;
+mod peripherals;
+mod protocol;
+mod state;
+
+pub mod prelude {
+    pub use crate::can::{CANFrame, CANOpenMessage};
+    pub use crate::node::Node;
+    pub use crate::peripherals::{
+        CANPort, I2CPort, I2CTransfer, StatusIndicator, TransmitError, USBLink,
+    };
+    pub use crate::protocol::*;
+    pub use crate::state::DriverState;
+}
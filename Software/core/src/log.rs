@@ -0,0 +1,26 @@
+//! Logging macros forwarding the messages to `defmt` when the `defmt` feature is enabled.
+//! Without `defmt`, the arguments are only borrowed, so the expansion is never an empty block.
+
+macro_rules! error {
+    ($message:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        defmt::error!($message $(, $arg)*);
+        #[cfg(not(feature = "defmt"))]
+        {
+            let _ = $message;
+            $(let _ = &$arg;)*
+        }
+    }};
+}
+
+macro_rules! warn {
+    ($message:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        defmt::warn!($message $(, $arg)*);
+        #[cfg(not(feature = "defmt"))]
+        {
+            let _ = $message;
+            $(let _ = &$arg;)*
+        }
+    }};
+}
@@ -0,0 +1,653 @@
+use crate::can::{CANFrame, CANOpenMessage};
+use crate::peripherals::{CANPort, I2CPort, I2CTransfer, StatusIndicator, USBLink};
+use crate::protocol::*;
+use crate::state::DriverState;
+use core::convert::TryFrom;
+use sm4_shared::prelude::*;
+
+/// The communication of the driver, handles the messages received by the peripherals
+/// and keeps the state of the driver.
+pub struct Node<OD, const R: u32> {
+    id: u8,
+    state: DriverState<OD, R>,
+}
+
+impl<OD, const R: u32> Node<OD, R>
+where
+    OD: ObjectDictionary<R>,
+{
+    /// Creates the node with the CANOpen `id`, the node boots up to the pre-operational state.
+    pub fn new(id: u8, object_dictionary: OD) -> Self {
+        let mut state = DriverState::new(object_dictionary);
+        state.go_to_preoperational_if_needed();
+        Self { id, state }
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn state(&self) -> &DriverState<OD, R> {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut DriverState<OD, R> {
+        &mut self.state
+    }
+
+    /// Decrements the failsafe counter and raises the fault when the speed commands stopped coming.
+    /// The counter is kept refreshed while an enabled axis moves on its own, e.g. homes or follows
+    /// the PVT points, as the master does not send the commands during these moves.
+    pub fn failsafe_tick(&mut self) {
+        if self.is_moving_autonomously() {
+            self.state.invalidate_last_received_speed_command_counter();
+            return;
+        }
+        let expired = self.state.decrement_last_received_speed_command_counter();
+        if expired && self.state.nmt_state() == NMTState::Operational {
+            error!("Communication with the master lost.");
+            raise_fault(self.state.object_dictionary(), ErrorCode::CommunicationLoss);
+        }
+    }
+
+    /// Returns true when an enabled axis is in a mode that is not driven by the target from the master.
+    fn is_moving_autonomously(&mut self) -> bool {
+        let dictionary = self.state.object_dictionary();
+        [Axis::Axis1, Axis::Axis2].iter().any(|&axis| {
+            let axis = dictionary.axis(axis);
+            axis.enabled() && !matches!(axis.mode(), AxisMode::Velocity | AxisMode::Position)
+        })
+    }
+
+    pub fn heartbeat<C, S>(&mut self, can: &mut C, leds: &mut S)
+    where
+        C: CANPort,
+        S: StatusIndicator,
+    {
+        leds.heartbeat();
+        send(
+            can,
+            self.id,
+            CANOpenMessage::NMTNodeMonitoring,
+            &[u8::from(self.state.nmt_state())],
+        )
+        .on_error(|_| leds.signalize_can_error());
+    }
+
+    pub fn process_usb<U: USBLink>(&mut self, usb: &mut U) {
+        match usb.receive() {
+            Some(USBMessage::Request(index, subindex)) => {
+                let (data, len) =
+                    read_object_dictionary(index, subindex, self.state.object_dictionary());
+                usb.send(USBMessage::Transfer(index, subindex, len as u8, data));
+            }
+            Some(USBMessage::Transfer(index, subindex, length, data)) => {
+                // the rejected write is already logged, the USB protocol has no response to report it
+                let _ = update_object_dictionary(
+                    index,
+                    subindex,
+                    &data[..length as usize],
+                    self.state.object_dictionary(),
+                );
+            }
+            None => {}
+        }
+    }
+
+    pub fn process_can<C, S>(&mut self, can: &mut C, leds: &mut S)
+    where
+        C: CANPort,
+        S: StatusIndicator,
+    {
+        if let Some(frame) = can.receive() {
+            if let Some(message) = frame.message() {
+                self.process_can_frame(message, &frame, can, leds);
+            }
+        }
+    }
+
+    fn process_can_frame<C, S>(
+        &mut self,
+        message: CANOpenMessage,
+        frame: &CANFrame,
+        can: &mut C,
+        leds: &mut S,
+    ) where
+        C: CANPort,
+        S: StatusIndicator,
+    {
+        match message {
+            CANOpenMessage::NMTNodeControl => {
+                nmt_received(self.id, frame, &mut self.state);
+            }
+            CANOpenMessage::GlobalFailsafeCommand => {}
+            CANOpenMessage::Sync => {
+                leds.signalize_sync();
+                sync(can, self.id, &mut self.state, leds);
+            }
+            CANOpenMessage::Emergency => {}
+            CANOpenMessage::TimeStamp => {}
+            CANOpenMessage::RxPDO1 => rx_pdo1(frame, &mut self.state),
+            CANOpenMessage::RxPDO2 => rx_pdo2(frame, &mut self.state),
+            CANOpenMessage::RxPDO3 => rx_pdo3(frame, &mut self.state),
+            CANOpenMessage::RxPDO4 => rx_pdo4(frame, &mut self.state),
+            CANOpenMessage::RxSDO => {
+                if let Some(response) = sdo_received(frame, &mut self.state) {
+                    send(can, self.id, CANOpenMessage::TxSDO, &response)
+                        .on_error(|_| error!("Failed to send TxSDO."));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn process_i2c_event<I: I2CPort>(&mut self, i2c: &mut I) {
+        match i2c.event() {
+            None => {}
+            Some(I2CTransfer::DataRequested(register)) => {
+                if let Ok(register) = I2CRegister::try_from(register) {
+                    if !register.readable() {
+                        error!("Register not readable");
+                        // TOOD nack
+                    }
+                    self.i2c_data_requested(register, i2c);
+                }
+            }
+            Some(I2CTransfer::DataReceived(register)) => {
+                if let Ok(register) = I2CRegister::try_from(register) {
+                    if !register.writeable() {
+                        // TOOD nack
+                    }
+                    self.i2c_data_received(register, i2c.received_data());
+                }
+            }
+        }
+    }
+
+    fn i2c_data_requested<I: I2CPort>(&mut self, register: I2CRegister, i2c: &mut I) {
+        let dictionary = self.state.object_dictionary();
+        match register {
+            I2CRegister::AxisSettings => {
+                i2c.set_transmit_buffer(&axis_settings(dictionary));
+            }
+            I2CRegister::Axis1Velocity => i2c.set_transmit_buffer(
+                &dictionary
+                    .axis(Axis::Axis1)
+                    .actual_velocity()
+                    .get_rps()
+                    .to_le_bytes(),
+            ),
+            I2CRegister::Axis2Velocity => i2c.set_transmit_buffer(
+                &dictionary
+                    .axis(Axis::Axis2)
+                    .actual_velocity()
+                    .get_rps()
+                    .to_le_bytes(),
+            ),
+            I2CRegister::BothAxesVelocity => {
+                // nacked before
+            }
+            I2CRegister::Axis1Position => {
+                i2c.set_transmit_buffer(&position(&dictionary.axis(Axis::Axis1).actual_position()));
+            }
+            I2CRegister::Axis2Position => {
+                i2c.set_transmit_buffer(&position(&dictionary.axis(Axis::Axis2).actual_position()));
+            }
+            I2CRegister::BothAxesPosition => {
+                i2c.set_transmit_buffer(&both_axes_position(dictionary));
+            }
+        }
+    }
+
+    fn i2c_data_received(&mut self, register: I2CRegister, data: &[u8]) {
+        let dictionary = self.state.object_dictionary();
+        match register {
+            I2CRegister::AxisSettings => {
+                set_axis_settings(data, dictionary);
+                self.state.go_to_operational();
+            }
+            I2CRegister::Axis1Velocity => {
+                dictionary
+                    .axis_mut(Axis::Axis1)
+                    .set_target_velocity(parse_velocity(data));
+                self.state.invalidate_last_received_speed_command_counter();
+            }
+            I2CRegister::Axis2Velocity => {
+                dictionary
+                    .axis_mut(Axis::Axis2)
+                    .set_target_velocity(parse_velocity(data));
+                self.state.invalidate_last_received_speed_command_counter();
+            }
+            I2CRegister::BothAxesVelocity => {
+                let (axis1_velocity, axis2_velocity) = parse_both_axes_velocities(data);
+                dictionary
+                    .axis_mut(Axis::Axis1)
+                    .set_target_velocity(axis1_velocity);
+                dictionary
+                    .axis_mut(Axis::Axis2)
+                    .set_target_velocity(axis2_velocity);
+                self.state.invalidate_last_received_speed_command_counter();
+            }
+            I2CRegister::Axis1Position => {
+                dictionary
+                    .axis_mut(Axis::Axis1)
+                    .set_target_position(parse_position(data));
+                self.state.invalidate_last_received_speed_command_counter();
+            }
+            I2CRegister::Axis2Position => {
+                dictionary
+                    .axis_mut(Axis::Axis2)
+                    .set_target_position(parse_position(data));
+                self.state.invalidate_last_received_speed_command_counter();
+            }
+            I2CRegister::BothAxesPosition => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peripherals::TransmitError;
+    use core::cell::RefCell;
+    use spin::Mutex;
+    use std::collections::{HashMap, VecDeque};
+
+    const RESOLUTION: u32 = 4096;
+    const ID: u8 = 0x01;
+
+    #[derive(Default)]
+    struct MockStorage {
+        values: HashMap<u16, [u8; 4]>,
+    }
+
+    impl ObjectDictionaryStorage for MockStorage {
+        fn save_f32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: f32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn save_bool<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: bool) {
+            self.values.insert(key.raw(), [value as u8, 0, 0, 0]);
+        }
+        fn save_u8<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: u8) {
+            self.values.insert(key.raw(), [value, 0, 0, 0]);
+        }
+        fn save_i32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: i32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn load_f32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<f32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| f32::from_le_bytes(*raw))
+        }
+        fn load_bool<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<bool> {
+            self.values.get(&key.raw()).map(|raw| raw[0] != 0)
+        }
+        fn load_u8<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<u8> {
+            self.values.get(&key.raw()).map(|raw| raw[0])
+        }
+        fn load_i32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<i32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| i32::from_le_bytes(*raw))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockCAN {
+        received: VecDeque<CANFrame>,
+        transmitted: Vec<CANFrame>,
+    }
+
+    impl MockCAN {
+        fn receive_frame(&mut self, id: u16, data: &[u8]) {
+            self.received.push_back(CANFrame::new(id, data).unwrap());
+        }
+    }
+
+    impl CANPort for MockCAN {
+        fn receive(&mut self) -> Option<CANFrame> {
+            self.received.pop_front()
+        }
+        fn transmit(&mut self, frame: &CANFrame) -> Result<(), TransmitError> {
+            self.transmitted.push(*frame);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockLEDs {
+        syncs: u32,
+        heartbeats: u32,
+    }
+
+    impl StatusIndicator for MockLEDs {
+        fn signalize_sync(&mut self) {
+            self.syncs += 1;
+        }
+        fn heartbeat(&mut self) {
+            self.heartbeats += 1;
+        }
+        fn signalize_can_error(&mut self) {}
+    }
+
+    #[derive(Default)]
+    struct MockUSB {
+        received: Option<USBMessage>,
+        sent: Vec<USBMessage>,
+    }
+
+    impl USBLink for MockUSB {
+        fn receive(&mut self) -> Option<USBMessage> {
+            self.received.take()
+        }
+        fn send(&mut self, message: USBMessage) {
+            self.sent.push(message);
+        }
+    }
+
+    #[derive(Default)]
+    struct MockI2C {
+        transfer: Option<I2CTransfer>,
+        received: Vec<u8>,
+        transmitted: Vec<u8>,
+    }
+
+    impl I2CPort for MockI2C {
+        fn event(&mut self) -> Option<I2CTransfer> {
+            self.transfer.take()
+        }
+        fn received_data(&mut self) -> &[u8] {
+            &self.received
+        }
+        fn set_transmit_buffer(&mut self, data: &[u8]) {
+            self.transmitted = data.to_vec();
+        }
+    }
+
+    type TestNode = Node<PersistentStoreObjectDictionary<MockStorage, RESOLUTION>, RESOLUTION>;
+
+    fn node() -> TestNode {
+        let storage = Box::leak(Box::new(Mutex::new(RefCell::new(MockStorage::default()))));
+        Node::new(ID, PersistentStoreObjectDictionary::new(storage))
+    }
+
+    /// Delivers all the received frames to the `node`.
+    fn process(node: &mut TestNode, can: &mut MockCAN, leds: &mut MockLEDs) {
+        while !can.received.is_empty() {
+            node.process_can(can, leds);
+        }
+    }
+
+    #[test]
+    fn nmt() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        assert!(node.state().nmt_state() == NMTState::PreOperational);
+
+        // the command for another node is ignored
+        can.receive_frame(0x000, &[0x01, 0x02]);
+        process(&mut node, &mut can, &mut leds);
+        assert!(node.state().nmt_state() == NMTState::PreOperational);
+
+        can.receive_frame(0x000, &[0x01, ID]);
+        process(&mut node, &mut can, &mut leds);
+        assert!(node.state().nmt_state() == NMTState::Operational);
+
+        can.receive_frame(0x000, &[0x02, ID]);
+        process(&mut node, &mut can, &mut leds);
+        assert!(node.state().nmt_state() == NMTState::Stopped);
+
+        node.heartbeat(&mut can, &mut leds);
+        assert_eq!(can.transmitted, [CANFrame::new(0x701, &[0x04]).unwrap()]);
+        assert_eq!(leds.heartbeats, 1);
+    }
+
+    #[test]
+    fn nmt_reset() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+
+        for reset in [0x81, 0x82] {
+            can.receive_frame(0x000, &[0x01, ID]);
+            process(&mut node, &mut can, &mut leds);
+            assert!(node.state().nmt_state() == NMTState::Operational);
+
+            can.receive_frame(0x000, &[reset, ID]);
+            process(&mut node, &mut can, &mut leds);
+            assert!(node.state().nmt_state() == NMTState::PreOperational);
+            assert!(node.state().is_movement_blocked());
+        }
+    }
+
+    #[test]
+    fn pdo() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        can.receive_frame(0x000, &[0x01, ID]);
+        // velocity mode, both axes enabled
+        can.receive_frame(0x201, &[0x00, 0x03]);
+        let mut velocities = [0u8; 8];
+        velocities[..4].copy_from_slice(&1.5f32.to_le_bytes());
+        velocities[4..].copy_from_slice(&(-2.0f32).to_le_bytes());
+        can.receive_frame(0x301, &velocities);
+        process(&mut node, &mut can, &mut leds);
+
+        assert!(!node.state().is_movement_blocked());
+        let dictionary = node.state_mut().object_dictionary();
+        assert!(dictionary.axis(Axis::Axis1).enabled());
+        assert!(dictionary.axis(Axis::Axis2).enabled());
+        assert_eq!(
+            dictionary.axis(Axis::Axis1).target_velocity().get_rps(),
+            1.5
+        );
+        assert_eq!(
+            dictionary.axis(Axis::Axis2).target_velocity().get_rps(),
+            -2.0
+        );
+
+        can.receive_frame(0x080, &[]);
+        process(&mut node, &mut can, &mut leds);
+        assert_eq!(leds.syncs, 1);
+        let ids: Vec<u16> = can.transmitted.iter().map(|frame| frame.id()).collect();
+        assert_eq!(ids, [0x181, 0x281, 0x381, 0x481]);
+    }
+
+    #[test]
+    fn failsafe() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        can.receive_frame(0x000, &[0x01, ID]);
+        can.receive_frame(0x301, &[0; 8]);
+        process(&mut node, &mut can, &mut leds);
+
+        for _ in 0..9 {
+            node.failsafe_tick();
+        }
+        assert!(!node.state().is_movement_blocked());
+        node.failsafe_tick();
+        assert!(node.state().is_movement_blocked());
+        let dictionary = node.state_mut().object_dictionary();
+        assert_eq!(
+            dictionary.axis(Axis::Axis1).fault(),
+            ErrorCode::CommunicationLoss
+        );
+        assert_eq!(
+            dictionary.axis(Axis::Axis2).fault(),
+            ErrorCode::CommunicationLoss
+        );
+    }
+
+    #[test]
+    fn failsafe_homing() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        can.receive_frame(0x000, &[0x01, ID]);
+        // expedited download of the homing mode and enabling of the first axis
+        can.receive_frame(0x601, &[0x2f, 0x00, 0x21, 0x01, 0x04, 0, 0, 0]);
+        can.receive_frame(0x601, &[0x2f, 0x00, 0x21, 0x02, 0x01, 0, 0, 0]);
+        process(&mut node, &mut can, &mut leds);
+
+        // the homing takes 2 s without any PDO
+        for _ in 0..20 {
+            node.failsafe_tick();
+            assert!(!node.state().is_movement_blocked());
+        }
+        let dictionary = node.state_mut().object_dictionary();
+        assert_eq!(dictionary.axis(Axis::Axis1).fault(), ErrorCode::None);
+        assert_eq!(dictionary.axis(Axis::Axis2).fault(), ErrorCode::None);
+
+        // the failsafe applies again once the axis leaves the homing
+        dictionary
+            .axis_mut(Axis::Axis1)
+            .set_mode(AxisMode::Velocity);
+        for _ in 0..10 {
+            node.failsafe_tick();
+        }
+        assert!(node.state().is_movement_blocked());
+    }
+
+    #[test]
+    fn sdo() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        let acceleration = 12.5f32.to_le_bytes();
+
+        // expedited download of the acceleration of the second axis
+        let mut download = [0x23, 0x00, 0x22, 0x09, 0, 0, 0, 0];
+        download[4..].copy_from_slice(&acceleration);
+        can.receive_frame(0x601, &download);
+        // expedited upload of the same object
+        can.receive_frame(0x601, &[0x43, 0x00, 0x22, 0x09, 0, 0, 0, 0]);
+        // segmented transfers are not supported
+        can.receive_frame(0x601, &[0x21, 0x00, 0x22, 0x09, 0, 0, 0, 0]);
+        process(&mut node, &mut can, &mut leds);
+
+        let mut upload = [0x40, 0x00, 0x22, 0x09, 0, 0, 0, 0];
+        upload[4..].copy_from_slice(&acceleration);
+        assert_eq!(
+            can.transmitted,
+            [
+                CANFrame::new(0x581, &[0x60, 0x00, 0x22, 0x09, 0, 0, 0, 0]).unwrap(),
+                CANFrame::new(0x581, &upload).unwrap(),
+            ]
+        );
+        assert_eq!(
+            node.state_mut()
+                .object_dictionary()
+                .axis(Axis::Axis2)
+                .acceleration(),
+            12.5
+        );
+    }
+
+    #[test]
+    fn pvt_push() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        can.receive_frame(0x000, &[0x01, ID]);
+        // expedited download of the time of the PVT point, which pushes it to the queue
+        let mut download = [0x23, 0x00, 0x21, 0x2c, 0, 0, 0, 0];
+        download[4..].copy_from_slice(&0.1f32.to_le_bytes());
+        for _ in 0..=PVT_QUEUE_CAPACITY {
+            can.receive_frame(0x601, &download);
+        }
+        process(&mut node, &mut can, &mut leds);
+
+        // the pushed points refresh the failsafe
+        assert!(!node.state().is_movement_blocked());
+        assert_eq!(
+            node.state_mut()
+                .object_dictionary()
+                .axis(Axis::Axis1)
+                .pvt_queue()
+                .len(),
+            PVT_QUEUE_CAPACITY
+        );
+        assert_eq!(
+            can.transmitted[PVT_QUEUE_CAPACITY - 1],
+            CANFrame::new(0x581, &[0x60, 0x00, 0x21, 0x2c, 0, 0, 0, 0]).unwrap()
+        );
+        // the push to the full queue is aborted with the out of memory code
+        assert_eq!(
+            can.transmitted[PVT_QUEUE_CAPACITY],
+            CANFrame::new(0x581, &[0x80, 0x00, 0x21, 0x2c, 0x05, 0x00, 0x04, 0x05]).unwrap()
+        );
+    }
+
+    #[test]
+    fn restricted_microsteps() {
+        let mut node = node();
+        let mut can = MockCAN::default();
+        let mut leds = MockLEDs::default();
+        node.state_mut()
+            .object_dictionary()
+            .axis_mut(Axis::Axis1)
+            .restrict_microsteps(&[4, 16]);
+
+        // expedited download of 8 microsteps to both axes
+        can.receive_frame(0x601, &[0x2b, 0x00, 0x21, 0x51, 8, 0, 0, 0]);
+        can.receive_frame(0x601, &[0x2b, 0x00, 0x22, 0x51, 8, 0, 0, 0]);
+        process(&mut node, &mut can, &mut leds);
+
+        let dictionary = node.state_mut().object_dictionary();
+        assert_eq!(dictionary.axis(Axis::Axis1).microsteps(), 16);
+        assert_eq!(dictionary.axis(Axis::Axis2).microsteps(), 8);
+
+        // expedited download of 4 microsteps to the first axis
+        can.receive_frame(0x601, &[0x2b, 0x00, 0x21, 0x51, 4, 0, 0, 0]);
+        process(&mut node, &mut can, &mut leds);
+
+        let dictionary = node.state_mut().object_dictionary();
+        assert_eq!(dictionary.axis(Axis::Axis1).microsteps(), 4);
+    }
+
+    #[test]
+    fn usb() {
+        let mut node = node();
+        let mut usb = MockUSB {
+            received: Some(USBMessage::Transfer(0x2100, 0x09, 4, 7.0f32.to_le_bytes())),
+            ..Default::default()
+        };
+        node.process_usb(&mut usb);
+        usb.received = Some(USBMessage::Request(0x2100, 0x09));
+        node.process_usb(&mut usb);
+        assert!(usb.sent == [USBMessage::Transfer(0x2100, 0x09, 4, 7.0f32.to_le_bytes())]);
+    }
+
+    #[test]
+    fn i2c() {
+        let mut node = node();
+        // position mode and enabled on both axes
+        let mut i2c = MockI2C {
+            transfer: Some(I2CTransfer::DataReceived(0x10)),
+            received: vec![0x11, 0x11],
+            ..Default::default()
+        };
+        node.process_i2c_event(&mut i2c);
+        assert!(node.state().nmt_state() == NMTState::Operational);
+
+        i2c.transfer = Some(I2CTransfer::DataReceived(0x22));
+        i2c.received = 3.0f32.to_le_bytes().to_vec();
+        node.process_i2c_event(&mut i2c);
+        assert!(!node.state().is_movement_blocked());
+        assert_eq!(
+            node.state_mut()
+                .object_dictionary()
+                .axis(Axis::Axis2)
+                .target_velocity()
+                .get_rps(),
+            3.0
+        );
+
+        i2c.transfer = Some(I2CTransfer::DataRequested(0x10));
+        node.process_i2c_event(&mut i2c);
+        assert_eq!(i2c.transmitted, [0x11, 0x11]);
+    }
+}
@@ -0,0 +1,45 @@
+//! The peripherals the core communicates through, implemented by the board layer.
+//! The storage of the object dictionary is abstracted by [sm4_shared::prelude::ObjectDictionaryStorage].
+use crate::can::CANFrame;
+use sm4_shared::prelude::USBMessage;
+
+/// The frame could not be transmitted, e.g. it is malformed or the bus is in the error state.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransmitError;
+
+pub trait CANPort {
+    /// Returns the received frame, `None` when there is no frame or it cannot be read.
+    fn receive(&mut self) -> Option<CANFrame>;
+    fn transmit(&mut self, frame: &CANFrame) -> Result<(), TransmitError>;
+}
+
+pub trait USBLink {
+    /// Returns the received message, `None` when no complete message was received yet.
+    fn receive(&mut self) -> Option<USBMessage>;
+    fn send(&mut self, message: USBMessage);
+}
+
+/// The transfer of the I2C master, which is waiting for the core to handle it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum I2CTransfer {
+    /// The master reads the register.
+    DataRequested(u8),
+    /// The master has written data to the register.
+    DataReceived(u8),
+}
+
+pub trait I2CPort {
+    /// Handles the event of the I2C peripheral and returns the transfer waiting to be handled.
+    fn event(&mut self) -> Option<I2CTransfer>;
+    /// Returns the data received in the [I2CTransfer::DataReceived] transfer.
+    fn received_data(&mut self) -> &[u8];
+    /// Sets the data transmitted in the [I2CTransfer::DataRequested] transfer.
+    fn set_transmit_buffer(&mut self, data: &[u8]);
+}
+
+/// The status LEDs of the driver.
+pub trait StatusIndicator {
+    fn signalize_sync(&mut self);
+    fn heartbeat(&mut self);
+    fn signalize_can_error(&mut self);
+}
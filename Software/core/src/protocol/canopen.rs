@@ -0,0 +1,1092 @@
+use crate::can::{CANFrame, CANOpenMessage};
+use crate::peripherals::{CANPort, StatusIndicator, TransmitError};
+use crate::state::DriverState;
+use core::convert::{TryFrom, TryInto};
+use sm4_shared::prelude::*;
+
+/// Sends the `data` of the `message` from the device with the `id`.
+pub fn send<C: CANPort>(
+    bus: &mut C,
+    id: u8,
+    message: CANOpenMessage,
+    data: &[u8],
+) -> Result<(), TransmitError> {
+    let frame = CANFrame::new(message.message_id_with_device(id), data).ok_or(TransmitError)?;
+    bus.transmit(&frame)
+}
+
+pub fn sync<C, S, OD, const R: u32>(
+    bus: &mut C,
+    id: u8,
+    state: &mut DriverState<OD, R>,
+    leds: &mut S,
+) where
+    C: CANPort,
+    S: StatusIndicator,
+    OD: ObjectDictionary<R>,
+{
+    let pdo = TxPDO1 {
+        battery_voltage: (state.object_dictionary().battery_voltage() * 1000.0) as u16,
+        temperature: (state.object_dictionary().temperature() * 10.0) as u16,
+        axis1_following_error: state
+            .object_dictionary()
+            .axis(Axis::Axis1)
+            .following_error(),
+        axis2_following_error: state
+            .object_dictionary()
+            .axis(Axis::Axis2)
+            .following_error(),
+        axis1_error_code: state.object_dictionary().axis(Axis::Axis1).fault(),
+        axis2_error_code: state.object_dictionary().axis(Axis::Axis2).fault(),
+    };
+    send(
+        bus,
+        id,
+        CANOpenMessage::TxPDO1,
+        &pdo.to_raw().unwrap()[..TxPDO1::len()],
+    )
+    .on_error(|_| leds.signalize_can_error());
+
+    match state.object_dictionary().pdo_units() {
+        PdoUnits::Native => send_native_pdos(bus, id, state, leds),
+        units => send_units_pdos(bus, id, state, leds, units == PdoUnits::FixedPoint),
+    }
+}
+
+fn send_native_pdos<C, S, OD, const R: u32>(
+    bus: &mut C,
+    id: u8,
+    state: &mut DriverState<OD, R>,
+    leds: &mut S,
+) where
+    C: CANPort,
+    S: StatusIndicator,
+    OD: ObjectDictionary<R>,
+{
+    let pdo = TxPDO2 {
+        axis1_velocity: state
+            .object_dictionary()
+            .axis(Axis::Axis1)
+            .actual_velocity()
+            .get_rps(),
+        axis2_velocity: state
+            .object_dictionary()
+            .axis(Axis::Axis2)
+            .actual_velocity()
+            .get_rps(),
+    };
+    send(
+        bus,
+        id,
+        CANOpenMessage::TxPDO2,
+        &pdo.to_raw().unwrap()[..TxPDO2::len()],
+    )
+    .on_error(|_| leds.signalize_can_error());
+
+    let pdo = TxPDO3 {
+        revolutions: state
+            .object_dictionary()
+            .axis(Axis::Axis1)
+            .actual_position()
+            .get_revolutions(),
+        angle: state
+            .object_dictionary()
+            .axis(Axis::Axis1)
+            .actual_position()
+            .get_angle(),
+    };
+    send(
+        bus,
+        id,
+        CANOpenMessage::TxPDO3,
+        &pdo.to_raw().unwrap()[..TxPDO3::len()],
+    )
+    .on_error(|_| leds.signalize_can_error());
+
+    let pdo = TxPDO4 {
+        revolutions: state
+            .object_dictionary()
+            .axis(Axis::Axis2)
+            .actual_position()
+            .get_revolutions(),
+        angle: state
+            .object_dictionary()
+            .axis(Axis::Axis2)
+            .actual_position()
+            .get_angle(),
+    };
+    send(
+        bus,
+        id,
+        CANOpenMessage::TxPDO4,
+        &pdo.to_raw().unwrap()[..TxPDO4::len()],
+    )
+    .on_error(|_| {
+        error!("Failed to send.");
+        leds.signalize_can_error();
+    });
+}
+
+/// Sends the actual velocities and positions of the axes in user units.
+fn send_units_pdos<C, S, OD, const R: u32>(
+    bus: &mut C,
+    id: u8,
+    state: &mut DriverState<OD, R>,
+    leds: &mut S,
+    fixed_point: bool,
+) where
+    C: CANPort,
+    S: StatusIndicator,
+    OD: ObjectDictionary<R>,
+{
+    let axis1 = state.object_dictionary().axis(Axis::Axis1);
+    let scaling1 = axis1.unit_scaling();
+    let velocity1 = scaling1.velocity_to_units(&axis1.actual_velocity());
+    let position1 = scaling1.position_to_units(&axis1.actual_position());
+    let axis2 = state.object_dictionary().axis(Axis::Axis2);
+    let scaling2 = axis2.unit_scaling();
+    let velocity2 = scaling2.velocity_to_units(&axis2.actual_velocity());
+    let position2 = scaling2.position_to_units(&axis2.actual_position());
+
+    let pdos = [
+        (CANOpenMessage::TxPDO2, velocity1, velocity2),
+        (CANOpenMessage::TxPDO3, position1, 0.0),
+        (CANOpenMessage::TxPDO4, position2, 0.0),
+    ];
+    for (message, first, second) in pdos {
+        let pdo = UnitsPDO { first, second };
+        send(
+            bus,
+            id,
+            message,
+            &pdo.to_raw(fixed_point).unwrap()[..UnitsPDO::len()],
+        )
+        .on_error(|_| leds.signalize_can_error());
+    }
+}
+
+pub fn nmt_received<OD, const R: u32>(id: u8, frame: &CANFrame, state: &mut DriverState<OD, R>)
+where
+    OD: ObjectDictionary<R>,
+{
+    if frame.data().len() != 2 {
+        error!("Malformed NMT node control data received.");
+        return;
+    }
+    if frame.data()[1] != id {
+        return;
+    }
+    match NMTRequestedState::try_from(frame.data()[0]) {
+        Ok(nmt_state) => match nmt_state {
+            NMTRequestedState::Operational => {
+                state.go_to_operational();
+            }
+            NMTRequestedState::Stopped => {
+                state.go_to_stopped();
+            }
+            NMTRequestedState::PreOperational => {
+                state.go_to_preoperational();
+            }
+            // the node is not restarted, the reset only returns it to the pre-operational state
+            NMTRequestedState::ResetNode => {
+                warn!("NMT reset node requested, going to pre-operational.");
+                state.go_to_preoperational();
+            }
+            NMTRequestedState::ResetCommunication => {
+                warn!("NMT reset communication requested, going to pre-operational.");
+                state.go_to_preoperational();
+            }
+        },
+        Err(_) => {
+            error!("Invalid NMT requested state received.");
+        }
+    }
+}
+
+pub fn rx_pdo1<OD, const R: u32>(frame: &CANFrame, state: &mut DriverState<OD, R>)
+where
+    OD: ObjectDictionary<R>,
+{
+    if let Ok(pdo) = RxPDO1::try_from(frame.data()) {
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis1)
+            .set_mode(pdo.axis1_mode);
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis2)
+            .set_mode(pdo.axis2_mode);
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis1)
+            .set_enabled(pdo.axis1_enabled);
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis2)
+            .set_enabled(pdo.axis2_enabled);
+        if pdo.axis1_fault_reset {
+            state
+                .object_dictionary()
+                .axis_mut(Axis::Axis1)
+                .reset_fault();
+        }
+        if pdo.axis2_fault_reset {
+            state
+                .object_dictionary()
+                .axis_mut(Axis::Axis2)
+                .reset_fault();
+        }
+    } else {
+        warn!("Malformed RxPDO1 received.");
+    }
+}
+
+pub fn rx_pdo2<OD, const R: u32>(frame: &CANFrame, state: &mut DriverState<OD, R>)
+where
+    OD: ObjectDictionary<R>,
+{
+    let units = state.object_dictionary().pdo_units();
+    if units != PdoUnits::Native {
+        match UnitsPDO::parse(frame.data(), units == PdoUnits::FixedPoint) {
+            Ok(pdo) => {
+                for (axis, velocity) in [(Axis::Axis1, pdo.first), (Axis::Axis2, pdo.second)] {
+                    let dictionary = state.object_dictionary().axis_mut(axis);
+                    let velocity = dictionary.unit_scaling().velocity_from_units(velocity);
+                    dictionary.set_target_velocity(velocity);
+                }
+                state.invalidate_last_received_speed_command_counter();
+            }
+            Err(_) => warn!("Malformed RxPDO2 received."),
+        }
+        return;
+    }
+    if let Ok(pdo) = RxPDO2::try_from(frame.data()) {
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis1)
+            .set_target_velocity(Velocity::new(pdo.axis1_velocity));
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis2)
+            .set_target_velocity(Velocity::new(pdo.axis2_velocity));
+
+        state.invalidate_last_received_speed_command_counter();
+    } else {
+        warn!("Malformed RxPDO2 received.");
+    }
+}
+
+pub fn rx_pdo3<OD, const R: u32>(frame: &CANFrame, state: &mut DriverState<OD, R>)
+where
+    OD: ObjectDictionary<R>,
+{
+    if state.object_dictionary().pdo_units() != PdoUnits::Native {
+        units_target_position(frame, state, Axis::Axis1);
+        return;
+    }
+    if let Ok(pdo) = RxPDO3::try_from(frame.data()) {
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis1)
+            .set_target_position(Position::new(pdo.revolutions, pdo.angle));
+        state.invalidate_last_received_speed_command_counter();
+    } else {
+        warn!("Malformed RxPDO3 received.");
+    }
+}
+
+pub fn rx_pdo4<OD, const R: u32>(frame: &CANFrame, state: &mut DriverState<OD, R>)
+where
+    OD: ObjectDictionary<R>,
+{
+    if state.object_dictionary().pdo_units() != PdoUnits::Native {
+        units_target_position(frame, state, Axis::Axis2);
+        return;
+    }
+    if let Ok(pdo) = RxPDO4::try_from(frame.data()) {
+        state
+            .object_dictionary()
+            .axis_mut(Axis::Axis2)
+            .set_target_position(Position::new(pdo.revolutions, pdo.angle));
+        state.invalidate_last_received_speed_command_counter();
+    } else {
+        warn!("Malformed RxPDO4 received.");
+    }
+}
+
+/// Sets the target position of the `axis` received in user units.
+fn units_target_position<OD, const R: u32>(
+    frame: &CANFrame,
+    state: &mut DriverState<OD, R>,
+    axis: Axis,
+) where
+    OD: ObjectDictionary<R>,
+{
+    let fixed_point = state.object_dictionary().pdo_units() == PdoUnits::FixedPoint;
+    match UnitsPDO::parse(frame.data(), fixed_point) {
+        Ok(pdo) => {
+            let dictionary = state.object_dictionary().axis_mut(axis);
+            let position = dictionary.unit_scaling().position_from_units(pdo.first);
+            dictionary.set_target_position(position);
+            state.invalidate_last_received_speed_command_counter();
+        }
+        Err(_) => warn!("Malformed position PDO received."),
+    }
+}
+
+/// Handles the expedited SDO transfer and returns the response, other transfers are ignored.
+// the bits of the command bytes are grouped by the fields of CiA 301 (scs, x, n, e, s)
+#[allow(clippy::unusual_byte_groupings)]
+pub fn sdo_received<OD, const R: u32>(
+    frame: &CANFrame,
+    state: &mut DriverState<OD, R>,
+) -> Option<[u8; 8]>
+where
+    OD: ObjectDictionary<R>,
+{
+    if frame.data().len() != 8 {
+        return None;
+    }
+    let data = frame.data();
+    let command = data[0];
+    let ccs = (command & 0xe0) >> 5;
+    let length = (4 - ((command & 0x0c) >> 2)) as usize; // 4 - n
+    let expedited = (command & 0x02) > 0; // e
+    let size_in_command = (command & 0x01) > 0; // s
+
+    if !(expedited && size_in_command) {
+        return None; // ignore not expedited frames
+    }
+
+    let index = u16::from_le_bytes(data[1..3].try_into().unwrap());
+    let subindex = data[3];
+    let [index_low, index_high] = index.to_le_bytes();
+
+    if ccs == 1 {
+        // initiate download, data are written to the SM4 controller
+        let data = &data[4..][..length];
+        match update_object_dictionary(index, subindex, data, state.object_dictionary()) {
+            Ok(()) => {
+                if is_pvt_push(index, subindex) {
+                    state.invalidate_last_received_speed_command_counter();
+                }
+                Some([0b011_0_00_00, index_low, index_high, subindex, 0, 0, 0, 0])
+            }
+            Err(error) => {
+                let [code0, code1, code2, code3] = error.abort_code().to_le_bytes();
+                Some([
+                    0b100_0_00_00,
+                    index_low,
+                    index_high,
+                    subindex,
+                    code0,
+                    code1,
+                    code2,
+                    code3,
+                ])
+            }
+        }
+    } else if ccs == 2 {
+        // initiate upload, data are read from the SM4 controller
+        let (data, _) = read_object_dictionary(index, subindex, state.object_dictionary());
+        Some([
+            0b010_0_00_00,
+            index_low,
+            index_high,
+            subindex,
+            data[0],
+            data[1],
+            data[2],
+            data[3],
+        ])
+    } else {
+        None
+    }
+}
+
+/// Returns true when the object at the `index` and `subindex` pushes the PVT point to the queue of an axis.
+fn is_pvt_push(index: u16, subindex: u8) -> bool {
+    matches!(
+        Key::parse(index, subindex),
+        Some(Key::Axis1(AxisKey::PvtTime)) | Some(Key::Axis2(AxisKey::PvtTime))
+    )
+}
+
+/// The reason why a write to the object dictionary was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WriteError {
+    /// The PVT queue of the axis has no space left for the pushed point.
+    PvtQueueFull,
+}
+
+impl WriteError {
+    /// Returns the SDO abort code of CiA 301 the write is aborted with.
+    pub fn abort_code(self) -> u32 {
+        match self {
+            WriteError::PvtQueueFull => 0x0504_0005, // out of memory
+        }
+    }
+}
+
+/// Writes the `data` to the object at the `index` and `subindex`.
+/// The malformed data and the read-only objects are only logged,
+/// the error is returned for the writes the master shall be notified about.
+pub fn update_object_dictionary<const R: u32>(
+    index: u16,
+    subindex: u8,
+    data: &[u8],
+    object_dictionary: &mut dyn ObjectDictionary<R>,
+) -> Result<(), WriteError> {
+    if let Some(key) = Key::parse(index, subindex) {
+        match key {
+            Key::BatteryVoltage => {
+                error!("Battery voltage shall not be changed by the higher level systems.");
+            }
+            Key::Temperature => {
+                error!("Temperature shall not be changed by the higher level systems.");
+            }
+            Key::CoordinatedMotion => object_dictionary.set_coordinated_motion_enabled(data[0] > 0),
+            Key::MaxTemperature => parse_f32(data, |v| object_dictionary.set_max_temperature(v)),
+            Key::MinBatteryVoltage => {
+                parse_f32(data, |v| object_dictionary.set_min_battery_voltage(v))
+            }
+            Key::PdoUnits => match PdoUnits::try_from(data[0]) {
+                Ok(units) => object_dictionary.set_pdo_units(units),
+                Err(_) => error!("Unsupported PDO units."),
+            },
+            Key::Axis1(key) => {
+                return update_axis_dictionary(key, data, object_dictionary.axis_mut(Axis::Axis1));
+            }
+            Key::Axis2(key) => {
+                return update_axis_dictionary(key, data, object_dictionary.axis_mut(Axis::Axis2));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_f32<F: FnOnce(f32)>(data: &[u8], f: F) {
+    let raw: Result<[u8; 4], _> = data.try_into();
+    if let Ok(raw) = raw {
+        f(f32::from_le_bytes(raw))
+    } else {
+        error!("Failed to parse f32 from SDO data.");
+    }
+}
+
+fn update_axis_dictionary<const R: u32>(
+    key: AxisKey,
+    data: &[u8],
+    dictionary: &mut dyn AxisDictionary<R>,
+) -> Result<(), WriteError> {
+    match key {
+        AxisKey::Mode => dictionary.set_mode(AxisMode::from(data[0])),
+        AxisKey::Enabled => dictionary.set_enabled(data[0] > 0),
+        AxisKey::TargetVelocity => {
+            parse_f32(data, |v| dictionary.set_target_velocity(Velocity::new(v)))
+        }
+        AxisKey::ActualVelocity => {
+            error!("Writing to actual velocity is forbidden.")
+        }
+        AxisKey::TargetPositionRevolutions => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            if let Ok(raw) = raw {
+                let position = dictionary.target_position();
+                dictionary.set_target_position(Position::new(
+                    i32::from_le_bytes(raw),
+                    position.get_angle(),
+                ));
+            } else {
+                error!("Failed to parse f32 from SDO data.");
+            }
+        }
+        AxisKey::TargetPositionAngle => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            if let Ok(raw) = raw {
+                let position = dictionary.target_position();
+                dictionary.set_target_position(Position::new(
+                    position.get_revolutions(),
+                    u32::from_le_bytes(raw),
+                ));
+            } else {
+                error!("Failed to parse f32 from SDO data.");
+            }
+        }
+        AxisKey::ActualPositionRevolutions => {
+            error!("Writing to actual velocity is forbidden.")
+        }
+        AxisKey::ActualPositionAngle => {
+            error!("Writing to actual velocity is forbidden.")
+        }
+        AxisKey::Acceleration => parse_f32(data, |v| dictionary.set_acceleration(v)),
+        AxisKey::VelocityFeedbackControlEnabled => {
+            dictionary.set_velocity_feedback_control_enabled(data[0] > 0)
+        }
+        AxisKey::AcceleratingCurrent => parse_f32(data, |v| dictionary.set_accelerating_current(v)),
+        AxisKey::StandStillCurrent => parse_f32(data, |v| dictionary.set_standstill_current(v)),
+        AxisKey::ConstantVelocityCurrent => {
+            parse_f32(data, |v| dictionary.set_constant_velocity_current(v))
+        }
+        AxisKey::VelocityP => parse_f32(data, |v| dictionary.set_velocity_controller_p(v)),
+        AxisKey::VelocityS => parse_f32(data, |v| dictionary.set_velocity_controller_s(v)),
+        AxisKey::VelocityD => parse_f32(data, |v| dictionary.set_velocity_controller_d(v)),
+        AxisKey::VelocityMaxAction => {
+            parse_f32(data, |v| dictionary.set_velocity_controller_max_output(v))
+        }
+        AxisKey::PositionP => parse_f32(data, |v| dictionary.set_position_controller_p(v)),
+        AxisKey::PositionS => parse_f32(data, |v| dictionary.set_position_controller_s(v)),
+        AxisKey::PositionD => parse_f32(data, |v| dictionary.set_position_controller_d(v)),
+        AxisKey::PositionMaxAction => {
+            parse_f32(data, |v| dictionary.set_position_controller_max_output(v))
+        }
+        AxisKey::RampProfile => dictionary.set_ramp_profile(RampProfile::from(data[0])),
+        AxisKey::Jerk => parse_f32(data, |v| dictionary.set_jerk(v)),
+        AxisKey::MaxVelocity => parse_f32(data, |v| dictionary.set_max_velocity(v)),
+        AxisKey::Deceleration => parse_f32(data, |v| dictionary.set_deceleration(v)),
+        AxisKey::VelocityFeedforward => parse_f32(data, |v| dictionary.set_velocity_feedforward(v)),
+        AxisKey::AccelerationFeedforward => {
+            parse_f32(data, |v| dictionary.set_acceleration_feedforward(v))
+        }
+        AxisKey::VelocityIntegralLimit => parse_f32(data, |v| {
+            dictionary.set_velocity_controller_integral_limit(v)
+        }),
+        AxisKey::VelocityDerivativeFilter => parse_f32(data, |v| {
+            dictionary.set_velocity_controller_derivative_filter(v)
+        }),
+        AxisKey::PositionIntegralLimit => parse_f32(data, |v| {
+            dictionary.set_position_controller_integral_limit(v)
+        }),
+        AxisKey::PositionDerivativeFilter => parse_f32(data, |v| {
+            dictionary.set_position_controller_derivative_filter(v)
+        }),
+        AxisKey::HomingMethod => match HomingMethod::try_from(data[0]) {
+            Ok(method) => dictionary.set_homing_method(method),
+            Err(_) => error!("Unsupported homing method."),
+        },
+        AxisKey::HomingSearchVelocity => {
+            parse_f32(data, |v| dictionary.set_homing_search_velocity(v))
+        }
+        AxisKey::HomingLatchVelocity => {
+            parse_f32(data, |v| dictionary.set_homing_latch_velocity(v))
+        }
+        AxisKey::HomingBackoff => parse_f32(data, |v| dictionary.set_homing_backoff(v)),
+        AxisKey::HomingOffset => parse_f32(data, |v| dictionary.set_homing_offset(v)),
+        AxisKey::HomingState => error!("Writing to homing state is forbidden."),
+        AxisKey::PositionLimitsEnabled => dictionary.set_position_limits_enabled(data[0] > 0),
+        AxisKey::MinPosition => parse_f32(data, |v| dictionary.set_min_position(v)),
+        AxisKey::MaxPosition => parse_f32(data, |v| dictionary.set_max_position(v)),
+        AxisKey::LimitTolerance => parse_f32(data, |v| dictionary.set_limit_tolerance(v)),
+        AxisKey::LimitViolation => error!("Writing to limit violation is forbidden."),
+        AxisKey::PvtPosition => parse_f32(data, |v| dictionary.set_pvt_position(v)),
+        AxisKey::PvtVelocity => parse_f32(data, |v| dictionary.set_pvt_velocity(v)),
+        AxisKey::PvtTime => {
+            let mut pushed = Ok(());
+            parse_f32(data, |v| {
+                dictionary.set_pvt_time(v);
+                let point = dictionary.pvt_point();
+                if dictionary.pvt_queue_mut().push(point).is_err() {
+                    error!("The PVT queue is full.");
+                    pushed = Err(WriteError::PvtQueueFull);
+                }
+            });
+            return pushed;
+        }
+        AxisKey::PvtQueueLength => dictionary.pvt_queue_mut().clear(),
+        AxisKey::PvtState => error!("Writing to PVT state is forbidden."),
+        AxisKey::GearNumerator => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            if let Ok(raw) = raw {
+                dictionary.set_gear_numerator(i32::from_le_bytes(raw));
+            } else {
+                error!("Failed to parse i32 from SDO data.");
+            }
+        }
+        AxisKey::GearDenominator => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            match raw.map(i32::from_le_bytes) {
+                Ok(0) => error!("The gear denominator shall not be zero."),
+                Ok(denominator) => dictionary.set_gear_denominator(denominator),
+                Err(_) => error!("Failed to parse i32 from SDO data."),
+            }
+        }
+        AxisKey::GearOffset => parse_f32(data, |v| dictionary.set_gear_offset(v)),
+        AxisKey::GearingState => error!("Writing to gearing state is forbidden."),
+        AxisKey::IdleCurrent => parse_f32(data, |v| dictionary.set_idle_current(v)),
+        AxisKey::IdleDelay => parse_f32(data, |v| dictionary.set_idle_delay(v)),
+        AxisKey::FullCurrentVelocity => {
+            parse_f32(data, |v| dictionary.set_full_current_velocity(v))
+        }
+        AxisKey::CurrentLoadBoost => parse_f32(data, |v| dictionary.set_current_load_boost(v)),
+        AxisKey::CurrentSlewRate => parse_f32(data, |v| dictionary.set_current_slew_rate(v)),
+        AxisKey::PositionErrorWindow => {
+            parse_f32(data, |v| dictionary.set_position_error_window(v))
+        }
+        AxisKey::PositionErrorTime => parse_f32(data, |v| dictionary.set_position_error_time(v)),
+        AxisKey::VelocityErrorWindow => {
+            parse_f32(data, |v| dictionary.set_velocity_error_window(v))
+        }
+        AxisKey::VelocityErrorTime => parse_f32(data, |v| dictionary.set_velocity_error_time(v)),
+        AxisKey::FollowingErrorReaction => match FaultReaction::try_from(data[0]) {
+            Ok(reaction) => dictionary.set_following_error_reaction(reaction),
+            Err(_) => error!("Unsupported fault reaction."),
+        },
+        AxisKey::FollowingError => {
+            if data[0] == 0 {
+                dictionary.set_following_error(false);
+            } else {
+                error!("The following error can only be cleared.");
+            }
+        }
+        AxisKey::ErrorCode => error!("Writing to error code is forbidden."),
+        AxisKey::FaultReset => dictionary.reset_fault(),
+        AxisKey::Backlash => parse_f32(data, |v| dictionary.set_backlash(v)),
+        AxisKey::BacklashVelocity => parse_f32(data, |v| dictionary.set_backlash_velocity(v)),
+        AxisKey::UnitsPerRevolution => parse_f32(data, |v| dictionary.set_units_per_revolution(v)),
+        AxisKey::ScalingNumerator => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            match raw.map(i32::from_le_bytes) {
+                Ok(numerator) => dictionary.set_scaling_numerator(numerator),
+                Err(_) => error!("Failed to parse i32 from SDO data."),
+            }
+        }
+        AxisKey::ScalingDenominator => {
+            let raw: Result<[u8; 4], _> = data.try_into();
+            match raw.map(i32::from_le_bytes) {
+                Ok(0) => error!("The scaling denominator shall not be zero."),
+                Ok(denominator) => dictionary.set_scaling_denominator(denominator),
+                Err(_) => error!("Failed to parse i32 from SDO data."),
+            }
+        }
+        AxisKey::PolarityInverted => dictionary.set_polarity_inverted(data[0] > 0),
+        AxisKey::TargetPositionUnits => parse_f32(data, |v| {
+            let position = dictionary.unit_scaling().position_from_units(v);
+            dictionary.set_target_position(position)
+        }),
+        AxisKey::ActualPositionUnits => {
+            error!("Writing to actual position is forbidden.")
+        }
+        AxisKey::TargetVelocityUnits => parse_f32(data, |v| {
+            let velocity = dictionary.unit_scaling().velocity_from_units(v);
+            dictionary.set_target_velocity(velocity)
+        }),
+        AxisKey::ActualVelocityUnits => {
+            error!("Writing to actual velocity is forbidden.")
+        }
+        AxisKey::VelocityFilter => match VelocityFilter::try_from(data[0]) {
+            Ok(filter) => dictionary.set_velocity_filter(filter),
+            Err(_) => error!("Unsupported velocity filter."),
+        },
+        AxisKey::VelocityFilterWindow => {
+            if (1..=MAX_VELOCITY_WINDOW).contains(&data[0]) {
+                dictionary.set_velocity_filter_window(data[0]);
+            } else {
+                error!("The velocity filter window is out of range.");
+            }
+        }
+        AxisKey::VelocityFilterCutoff => {
+            parse_f32(data, |v| dictionary.set_velocity_filter_cutoff(v))
+        }
+        AxisKey::StepLossThreshold => parse_f32(data, |v| dictionary.set_step_loss_threshold(v)),
+        AxisKey::EncoderRatio => parse_f32(data, |v| dictionary.set_encoder_ratio(v)),
+        AxisKey::StepLossReaction => match StepLossReaction::try_from(data[0]) {
+            Ok(reaction) => dictionary.set_step_loss_reaction(reaction),
+            Err(_) => error!("Unsupported step loss reaction."),
+        },
+        AxisKey::StepLossDeviation => {
+            error!("Writing to step loss deviation is forbidden.")
+        }
+        AxisKey::Microsteps => {
+            if data.len() < 2 {
+                error!("Failed to parse u16 from SDO data.");
+                return Ok(());
+            }
+            let microsteps = u16::from_le_bytes([data[0], data[1]]);
+            if dictionary.supports_microsteps(microsteps) {
+                dictionary.set_microsteps(microsteps);
+            } else {
+                error!("Unsupported microstepping.");
+            }
+        }
+        AxisKey::AutoTuneLoop => match TuningLoop::try_from(data[0]) {
+            Ok(tuning_loop) => dictionary.set_auto_tune_loop(tuning_loop),
+            Err(_) => error!("Unsupported tuning loop."),
+        },
+        AxisKey::AutoTuneRule => match TuningRule::try_from(data[0]) {
+            Ok(rule) => dictionary.set_auto_tune_rule(rule),
+            Err(_) => error!("Unsupported tuning rule."),
+        },
+        AxisKey::AutoTuneAmplitude => parse_f32(data, |v| dictionary.set_auto_tune_amplitude(v)),
+        AxisKey::AutoTuneHysteresis => parse_f32(data, |v| dictionary.set_auto_tune_hysteresis(v)),
+        AxisKey::AutoTuneState => error!("Writing to auto-tune state is forbidden."),
+        AxisKey::UltimateGain
+        | AxisKey::UltimatePeriod
+        | AxisKey::ProposedP
+        | AxisKey::ProposedS
+        | AxisKey::ProposedD => error!("Writing to auto-tune result is forbidden."),
+        AxisKey::AutoTuneCommit => {
+            if !dictionary.commit_auto_tune_result() {
+                error!("The auto-tuning has not finished, there are no gains to commit.");
+            }
+        }
+        AxisKey::StallThreshold => {
+            if data.len() < 2 {
+                error!("Failed to parse u16 from SDO data.");
+                return Ok(());
+            }
+            dictionary.set_stall_threshold(u16::from_le_bytes([data[0], data[1]]));
+        }
+        AxisKey::StallMinVelocity => parse_f32(data, |v| dictionary.set_stall_min_velocity(v)),
+        AxisKey::StallGuardResult => error!("Writing to StallGuard result is forbidden."),
+    }
+    Ok(())
+}
+
+pub fn read_object_dictionary<const R: u32>(
+    index: u16,
+    subindex: u8,
+    dictionary: &dyn ObjectDictionary<R>,
+) -> ([u8; 4], usize) {
+    if let Some(key) = Key::parse(index, subindex) {
+        match key {
+            Key::BatteryVoltage => (dictionary.battery_voltage().to_le_bytes(), 4),
+            Key::Temperature => (dictionary.temperature().to_le_bytes(), 4),
+            Key::CoordinatedMotion => ([dictionary.coordinated_motion_enabled() as u8, 0, 0, 0], 1),
+            Key::MaxTemperature => (dictionary.max_temperature().to_le_bytes(), 4),
+            Key::MinBatteryVoltage => (dictionary.min_battery_voltage().to_le_bytes(), 4),
+            Key::PdoUnits => ([dictionary.pdo_units().into(), 0, 0, 0], 1),
+            Key::Axis1(key) => read_axis_dictionary(key, dictionary.axis(Axis::Axis1)),
+            Key::Axis2(key) => read_axis_dictionary(key, dictionary.axis(Axis::Axis2)),
+        }
+    } else {
+        ([0u8; 4], 4)
+    }
+}
+
+fn read_axis_dictionary<const R: u32>(
+    key: AxisKey,
+    dictionary: &dyn AxisDictionary<R>,
+) -> ([u8; 4], usize) {
+    match key {
+        AxisKey::Mode => ([dictionary.mode().into(), 0, 0, 0], 1),
+        AxisKey::Enabled => ([dictionary.enabled() as u8, 0, 0, 0], 1),
+        AxisKey::TargetVelocity => (dictionary.target_velocity().get_rps().to_le_bytes(), 4),
+        AxisKey::ActualVelocity => (dictionary.actual_velocity().get_rps().to_le_bytes(), 4),
+        AxisKey::TargetPositionRevolutions => (
+            dictionary.target_position().get_revolutions().to_le_bytes(),
+            4,
+        ),
+        AxisKey::TargetPositionAngle => (dictionary.target_position().get_angle().to_le_bytes(), 4),
+        AxisKey::ActualPositionRevolutions => (
+            dictionary.actual_position().get_revolutions().to_le_bytes(),
+            4,
+        ),
+        AxisKey::ActualPositionAngle => (dictionary.actual_position().get_angle().to_le_bytes(), 4),
+        AxisKey::Acceleration => (dictionary.acceleration().to_le_bytes(), 4),
+        AxisKey::VelocityFeedbackControlEnabled => (
+            [
+                dictionary.velocity_feedback_control_enabled() as u8,
+                0,
+                0,
+                0,
+            ],
+            1,
+        ),
+        AxisKey::AcceleratingCurrent => {
+            (dictionary.current().accelerating_current().to_le_bytes(), 4)
+        }
+        AxisKey::StandStillCurrent => (dictionary.current().standstill_current().to_le_bytes(), 4),
+        AxisKey::ConstantVelocityCurrent => (
+            dictionary
+                .current()
+                .constant_velocity_current()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityP => (
+            dictionary
+                .velocity_controller_settings()
+                .proportional()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityS => (
+            dictionary
+                .velocity_controller_settings()
+                .integral()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityD => (
+            dictionary
+                .velocity_controller_settings()
+                .derivative()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityMaxAction => (
+            dictionary
+                .velocity_controller_settings()
+                .max_output_amplitude()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionP => (
+            dictionary
+                .position_controller_settings()
+                .proportional()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionS => (
+            dictionary
+                .position_controller_settings()
+                .integral()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionD => (
+            dictionary
+                .position_controller_settings()
+                .derivative()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionMaxAction => (
+            dictionary
+                .position_controller_settings()
+                .max_output_amplitude()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::RampProfile => ([dictionary.ramp_profile().into(), 0, 0, 0], 1),
+        AxisKey::Jerk => (dictionary.jerk().to_le_bytes(), 4),
+        AxisKey::MaxVelocity => (dictionary.max_velocity().to_le_bytes(), 4),
+        AxisKey::Deceleration => (dictionary.deceleration().to_le_bytes(), 4),
+        AxisKey::VelocityFeedforward => (dictionary.velocity_feedforward().to_le_bytes(), 4),
+        AxisKey::AccelerationFeedforward => {
+            (dictionary.acceleration_feedforward().to_le_bytes(), 4)
+        }
+        AxisKey::VelocityIntegralLimit => (
+            dictionary
+                .velocity_controller_settings()
+                .integral_limit()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityDerivativeFilter => (
+            dictionary
+                .velocity_controller_settings()
+                .derivative_filter()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionIntegralLimit => (
+            dictionary
+                .position_controller_settings()
+                .integral_limit()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionDerivativeFilter => (
+            dictionary
+                .position_controller_settings()
+                .derivative_filter()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::HomingMethod => ([dictionary.homing_settings().method().into(), 0, 0, 0], 1),
+        AxisKey::HomingSearchVelocity => (
+            dictionary.homing_settings().search_velocity().to_le_bytes(),
+            4,
+        ),
+        AxisKey::HomingLatchVelocity => (
+            dictionary.homing_settings().latch_velocity().to_le_bytes(),
+            4,
+        ),
+        AxisKey::HomingBackoff => (dictionary.homing_settings().backoff().to_le_bytes(), 4),
+        AxisKey::HomingOffset => (dictionary.homing_settings().offset().to_le_bytes(), 4),
+        AxisKey::HomingState => ([dictionary.homing_state().into(), 0, 0, 0], 1),
+        AxisKey::PositionLimitsEnabled => {
+            ([dictionary.position_limits().enabled() as u8, 0, 0, 0], 1)
+        }
+        AxisKey::MinPosition => (dictionary.position_limits().min().to_le_bytes(), 4),
+        AxisKey::MaxPosition => (dictionary.position_limits().max().to_le_bytes(), 4),
+        AxisKey::LimitTolerance => (dictionary.position_limits().tolerance().to_le_bytes(), 4),
+        AxisKey::LimitViolation => ([dictionary.limit_violation().into(), 0, 0, 0], 1),
+        AxisKey::PvtPosition => (dictionary.pvt_point().position.to_le_bytes(), 4),
+        AxisKey::PvtVelocity => (dictionary.pvt_point().velocity.to_le_bytes(), 4),
+        AxisKey::PvtTime => (dictionary.pvt_point().time.to_le_bytes(), 4),
+        AxisKey::PvtQueueLength => ([dictionary.pvt_queue().len() as u8, 0, 0, 0], 1),
+        AxisKey::PvtState => ([dictionary.pvt_state().into(), 0, 0, 0], 1),
+        AxisKey::GearNumerator => (dictionary.gearing_settings().numerator().to_le_bytes(), 4),
+        AxisKey::GearDenominator => (dictionary.gearing_settings().denominator().to_le_bytes(), 4),
+        AxisKey::GearOffset => (dictionary.gearing_settings().offset().to_le_bytes(), 4),
+        AxisKey::GearingState => ([dictionary.gearing_state().into(), 0, 0, 0], 1),
+        AxisKey::IdleCurrent => (dictionary.current_policy().idle_current().to_le_bytes(), 4),
+        AxisKey::IdleDelay => (dictionary.current_policy().idle_delay().to_le_bytes(), 4),
+        AxisKey::FullCurrentVelocity => (
+            dictionary
+                .current_policy()
+                .full_current_velocity()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::CurrentLoadBoost => (dictionary.current_policy().load_boost().to_le_bytes(), 4),
+        AxisKey::CurrentSlewRate => (dictionary.current_policy().slew_rate().to_le_bytes(), 4),
+        AxisKey::PositionErrorWindow => (
+            dictionary
+                .following_error_settings()
+                .position_window()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::PositionErrorTime => (
+            dictionary
+                .following_error_settings()
+                .position_time()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityErrorWindow => (
+            dictionary
+                .following_error_settings()
+                .velocity_window()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityErrorTime => (
+            dictionary
+                .following_error_settings()
+                .velocity_time()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::FollowingErrorReaction => (
+            [
+                dictionary.following_error_settings().reaction().into(),
+                0,
+                0,
+                0,
+            ],
+            1,
+        ),
+        AxisKey::FollowingError => ([dictionary.following_error() as u8, 0, 0, 0], 1),
+        AxisKey::ErrorCode => ([dictionary.fault().into(), 0, 0, 0], 1),
+        AxisKey::FaultReset => ([0, 0, 0, 0], 1),
+        AxisKey::Backlash => (dictionary.backlash_settings().distance().to_le_bytes(), 4),
+        AxisKey::BacklashVelocity => (dictionary.backlash_settings().velocity().to_le_bytes(), 4),
+        AxisKey::UnitsPerRevolution => (
+            dictionary
+                .unit_scaling()
+                .units_per_revolution()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::ScalingNumerator => (dictionary.unit_scaling().numerator().to_le_bytes(), 4),
+        AxisKey::ScalingDenominator => (dictionary.unit_scaling().denominator().to_le_bytes(), 4),
+        AxisKey::PolarityInverted => ([dictionary.unit_scaling().inverted() as u8, 0, 0, 0], 1),
+        AxisKey::TargetPositionUnits => (
+            dictionary
+                .unit_scaling()
+                .position_to_units(&dictionary.target_position())
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::ActualPositionUnits => (
+            dictionary
+                .unit_scaling()
+                .position_to_units(&dictionary.actual_position())
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::TargetVelocityUnits => (
+            dictionary
+                .unit_scaling()
+                .velocity_to_units(&dictionary.target_velocity())
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::ActualVelocityUnits => (
+            dictionary
+                .unit_scaling()
+                .velocity_to_units(&dictionary.actual_velocity())
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::VelocityFilter => (
+            [
+                dictionary.velocity_estimator_settings().filter().into(),
+                0,
+                0,
+                0,
+            ],
+            1,
+        ),
+        AxisKey::VelocityFilterWindow => (
+            [dictionary.velocity_estimator_settings().window(), 0, 0, 0],
+            1,
+        ),
+        AxisKey::VelocityFilterCutoff => (
+            dictionary
+                .velocity_estimator_settings()
+                .cutoff()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::StepLossThreshold => {
+            (dictionary.step_loss_settings().threshold().to_le_bytes(), 4)
+        }
+        AxisKey::EncoderRatio => (
+            dictionary
+                .step_loss_settings()
+                .encoder_ratio()
+                .to_le_bytes(),
+            4,
+        ),
+        AxisKey::StepLossReaction => (
+            [dictionary.step_loss_settings().reaction().into(), 0, 0, 0],
+            1,
+        ),
+        AxisKey::StepLossDeviation => (dictionary.step_loss_deviation().to_le_bytes(), 4),
+        AxisKey::Microsteps => {
+            let [low, high] = dictionary.microsteps().to_le_bytes();
+            ([low, high, 0, 0], 2)
+        }
+        AxisKey::AutoTuneLoop => (
+            [
+                dictionary.auto_tune_settings().tuning_loop().into(),
+                0,
+                0,
+                0,
+            ],
+            1,
+        ),
+        AxisKey::AutoTuneRule => ([dictionary.auto_tune_settings().rule().into(), 0, 0, 0], 1),
+        AxisKey::AutoTuneAmplitude => {
+            (dictionary.auto_tune_settings().amplitude().to_le_bytes(), 4)
+        }
+        AxisKey::AutoTuneHysteresis => (
+            dictionary.auto_tune_settings().hysteresis().to_le_bytes(),
+            4,
+        ),
+        AxisKey::AutoTuneState => ([dictionary.auto_tune_state().into(), 0, 0, 0], 1),
+        AxisKey::UltimateGain => (dictionary.auto_tune_result().ultimate_gain.to_le_bytes(), 4),
+        AxisKey::UltimatePeriod => (
+            dictionary.auto_tune_result().ultimate_period.to_le_bytes(),
+            4,
+        ),
+        AxisKey::ProposedP => (dictionary.auto_tune_result().proportional.to_le_bytes(), 4),
+        AxisKey::ProposedS => (dictionary.auto_tune_result().integral.to_le_bytes(), 4),
+        AxisKey::ProposedD => (dictionary.auto_tune_result().derivative.to_le_bytes(), 4),
+        AxisKey::AutoTuneCommit => ([0, 0, 0, 0], 1),
+        AxisKey::StallThreshold => {
+            let [low, high] = dictionary.stall_settings().threshold().to_le_bytes();
+            ([low, high, 0, 0], 2)
+        }
+        AxisKey::StallMinVelocity => (dictionary.stall_settings().min_velocity().to_le_bytes(), 4),
+        AxisKey::StallGuardResult => {
+            let [low, high] = dictionary.stall_guard_result().to_le_bytes();
+            ([low, high, 0, 0], 2)
+        }
+    }
+}
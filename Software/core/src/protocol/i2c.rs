@@ -51,7 +51,7 @@ pub fn axis_settings<const R: u32>(dictionary: &dyn ObjectDictionary<R>) -> [u8;
 
 pub fn set_axis_settings<const R: u32>(raw: &[u8], dictionary: &mut dyn ObjectDictionary<R>) {
     if raw.len() < 2 {
-        defmt::error!("Too few raw data to parse axis settings.");
+        error!("Too few raw data to parse axis settings.");
         return;
     }
 
@@ -72,7 +72,7 @@ pub fn set_axis_settings<const R: u32>(raw: &[u8], dictionary: &mut dyn ObjectDi
 
 pub fn parse_velocity(raw: &[u8]) -> Velocity {
     if raw.len() < 4 {
-        defmt::error!("Too few raw data to parse axis velocity.");
+        error!("Too few raw data to parse axis velocity.");
         return Velocity::zero();
     }
 
@@ -81,7 +81,7 @@ pub fn parse_velocity(raw: &[u8]) -> Velocity {
 
 pub fn parse_both_axes_velocities(raw: &[u8]) -> (Velocity, Velocity) {
     if raw.len() < 8 {
-        defmt::error!("Too few raw data to parse both axes velocity.");
+        error!("Too few raw data to parse both axes velocity.");
         return (Velocity::zero(), Velocity::zero());
     }
     (parse_velocity(&raw[..4]), parse_velocity(&raw[4..]))
@@ -96,7 +96,7 @@ pub fn position<const R: u32>(position: &Position<R>) -> [u8; 8] {
 
 pub fn parse_position<const R: u32>(raw: &[u8]) -> Position<R> {
     if raw.len() < 8 {
-        defmt::error!("Too few raw data to parse axis position.");
+        error!("Too few raw data to parse axis position.");
         return Position::zero();
     }
 
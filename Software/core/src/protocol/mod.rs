@@ -2,8 +2,8 @@ mod canopen;
 mod i2c;
 
 pub use canopen::{
-    nmt_received, read_object_dictionary, rx_pdo1, rx_pdo2, rx_pdo3, rx_pdo4, sync,
-    update_object_dictionary,
+    nmt_received, read_object_dictionary, rx_pdo1, rx_pdo2, rx_pdo3, rx_pdo4, sdo_received, send,
+    sync, update_object_dictionary, WriteError,
 };
 pub use i2c::{
     axis_settings, both_axes_position, parse_both_axes_velocities, parse_position, parse_velocity,
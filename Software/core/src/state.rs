@@ -48,10 +48,13 @@ where
             || self.last_received_speed_command_down_counter == 0
     }
 
-    pub fn decrement_last_received_speed_command_counter(&mut self) {
+    /// Returns true when the counter has just expired - the speed commands stopped coming.
+    pub fn decrement_last_received_speed_command_counter(&mut self) -> bool {
         if self.last_received_speed_command_down_counter != 0 {
             self.last_received_speed_command_down_counter -= 1;
+            return self.last_received_speed_command_down_counter == 0;
         }
+        false
     }
 
     pub fn invalidate_last_received_speed_command_counter(&mut self) {
@@ -12,6 +12,7 @@ use sm4_firmware::prelude::StatusLED;
 use sm4_firmware::staging::config::Config;
 use sm4_firmware::staging::{NoRx, Serial};
 use sm4_firmware::SM4;
+use sm4_shared::prelude::*;
 use stm32f4xx_hal::gpio::gpioa::PA9;
 use stm32f4xx_hal::gpio::{Alternate, AF7};
 use stm32f4xx_hal::prelude::*;
@@ -78,9 +79,17 @@ const APP: () = {
         cx.resources.led.toggle();
         let serial: &mut HDSerial = cx.resources.serial;
 
-        serial
-            .bwrite_all(&[0b1010_0000, 0x01, 0b10101010, 0x01, 0x02, 0x03, 0x04, 0x55])
-            .unwrap();
+        let register = TrinamicRegister::InterfaceCounter;
+        let mut reply = [0u8; DATAGRAM_LENGTH];
+        match TrinamicUart::write(serial, &read_request(0, register))
+            .and_then(|_| TrinamicUart::read(serial, &mut reply))
+        {
+            Ok(_) => match parse_reply::<()>(&reply, register) {
+                Ok(count) => defmt::info!("Interface counter: {=u32}", count),
+                Err(_) => defmt::error!("Malformed reply received."),
+            },
+            Err(_) => defmt::error!("Failed to read the interface counter."),
+        }
 
         cx.schedule.blink(cx.scheduled + BLINK.cycles()).unwrap();
     }
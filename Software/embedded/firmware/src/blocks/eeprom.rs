@@ -70,6 +70,16 @@ impl ObjectDictionaryStorage for Storage {
             .on_error(|_| defmt::error!("Failed to write value to store."))
     }
 
+    fn save_u8<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: u8) {
+        self.write(key.raw(), value as u32)
+            .on_error(|_| defmt::error!("Failed to write value to store."))
+    }
+
+    fn save_i32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: i32) {
+        self.write(key.raw(), value as u32)
+            .on_error(|_| defmt::error!("Failed to write value to store."))
+    }
+
     fn load_f32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<f32> {
         self.read(key.raw())
             .map(|u| unsafe { *(u.to_le_bytes().as_ptr() as *const f32) })
@@ -78,6 +88,14 @@ impl ObjectDictionaryStorage for Storage {
     fn load_bool<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<bool> {
         self.read(key.raw()).map(|u| u > 0)
     }
+
+    fn load_u8<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<u8> {
+        self.read(key.raw()).map(|u| u as u8)
+    }
+
+    fn load_i32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<i32> {
+        self.read(key.raw()).map(|u| u as i32)
+    }
 }
 
 impl Storage {
@@ -37,8 +37,8 @@ impl GPIO {
             mode2: gpioa.pa9.into_floating_input(),
             ref1: gpioa.pa4.into_analog(),
             ref2: gpioa.pa5.into_analog(),
-            en1: gpioa.pa6.into_floating_input(),
-            en2: gpioa.pa7.into_floating_input(),
+            en1: gpioa.pa6.into_push_pull_output(),
+            en2: gpioa.pa7.into_push_pull_output(),
             step1: gpioc.pc6.into_alternate_af3(),
             step2: gpioa.pa8.into_alternate_af1(),
             err1: gpioc.pc5.into_pull_down_input(),
@@ -1,5 +1,6 @@
 use crate::board::definitions::{ErrorLED, StatusLED};
 use blinq::Blinq;
+use sm4_core::prelude::StatusIndicator;
 
 pub struct LEDs {
     status_led: Blinq<blinq::consts::U8, StatusLED>,
@@ -18,18 +19,20 @@ impl LEDs {
         self.status_led.step();
         self.error_led.step();
     }
+}
 
-    pub fn signalize_sync(&mut self) {
+impl StatusIndicator for LEDs {
+    fn signalize_sync(&mut self) {
         self.status_led
             .enqueue(blinq::patterns::blinks::SHORT_ON_OFF);
     }
 
-    pub fn heartbeat(&mut self) {
+    fn heartbeat(&mut self) {
         self.status_led
             .enqueue(blinq::patterns::blinks::SHORT_ON_OFF);
     }
 
-    pub fn signalize_can_error(&mut self) {
+    fn signalize_can_error(&mut self) {
         self.error_led
             .enqueue(blinq::patterns::blinks::MEDIUM_OFF_ON);
     }
@@ -1,6 +1,7 @@
 pub use current_reference::*;
 pub use gpio::GPIO;
 pub use leds::LEDs;
+pub use mode_pin::ModePin;
 pub use monitoring::Monitoring;
 pub use step_counter::StepCounterEncoder;
 pub use step_timer::StepGeneratorTimer;
@@ -13,6 +14,7 @@ mod eeprom;
 mod flash;
 mod gpio;
 mod leds;
+mod mode_pin;
 mod monitoring;
 mod step_counter;
 mod step_timer;
@@ -0,0 +1,47 @@
+use crate::board::definitions::{Mode1, Mode2};
+use sm4_shared::prelude::TristatePin;
+use stm32f4xx_hal::stm32;
+
+/// The MODE pin connected to the CFG1 pin of a driver, which is switched between the push-pull output
+/// and the floating input by the mode register of the port, so the driver can detect it as open.
+pub struct ModePin<P> {
+    _pin: P,
+}
+
+macro_rules! mode_pin {
+    ($pin:ident, $port:ident, $moder:ident, $bs:ident, $br:ident) => {
+        impl ModePin<$pin> {
+            /// Creates the mode pin, which is left floating.
+            pub fn new(pin: $pin) -> Self {
+                Self { _pin: pin }
+            }
+        }
+
+        impl TristatePin for ModePin<$pin> {
+            fn set_low(&mut self) {
+                // NOTE(unsafe) the output is set by an atomic write and the mode register is modified
+                // only by the mode pins after the initialization, which are used from the control loop.
+                let port = unsafe { &(*stm32::$port::ptr()) };
+                port.bsrr.write(|w| w.$br().set_bit());
+                port.moder.modify(|_, w| w.$moder().output());
+            }
+
+            fn set_high(&mut self) {
+                // NOTE(unsafe) the output is set by an atomic write and the mode register is modified
+                // only by the mode pins after the initialization, which are used from the control loop.
+                let port = unsafe { &(*stm32::$port::ptr()) };
+                port.bsrr.write(|w| w.$bs().set_bit());
+                port.moder.modify(|_, w| w.$moder().output());
+            }
+
+            fn set_open(&mut self) {
+                // NOTE(unsafe) the mode register is modified only by the mode pins after the initialization.
+                let port = unsafe { &(*stm32::$port::ptr()) };
+                port.moder.modify(|_, w| w.$moder().input());
+            }
+        }
+    };
+}
+
+mode_pin!(Mode1, GPIOA, moder3, bs3, br3);
+mode_pin!(Mode2, GPIOA, moder9, bs9, br9);
@@ -11,8 +11,12 @@ pub trait Counter {
     fn reset_value(&mut self);
 }
 
+/// Encoder counting the generated steps, the steps are converted to the `RESOLUTION` of the position.
 pub struct StepCounterEncoder<T, const RESOLUTION: u32> {
     timer: T,
+    /// The total number of the counted steps.
+    steps: i32,
+    steps_per_revolution: u32,
     past_position: Position<RESOLUTION>,
     current_position: Position<RESOLUTION>,
     current_velocity: Velocity,
@@ -29,11 +33,12 @@ where
         let value = self.timer.get_value();
         let increment = value - self.past_value;
         self.past_value = value;
-        self.current_position += if self.direction == Direction::Clockwise {
+        self.steps += if self.direction == Direction::Clockwise {
             increment as i32
         } else {
             -(increment as i32)
         };
+        self.current_position = Position::from_increments(self.steps, self.steps_per_revolution);
 
         // self.timer.reset_value();
     }
@@ -53,6 +58,7 @@ where
 
     fn reset_position(&mut self) -> Position<RESOLUTION> {
         let past = self.current_position;
+        self.steps = 0;
         self.current_position = Position::zero();
         self.past_position = Position::zero();
         self.current_velocity = Velocity::zero();
@@ -78,6 +84,17 @@ where
 
         self.direction = direction;
     }
+
+    fn set_step_resolution(&mut self, steps_per_revolution: u32) {
+        if steps_per_revolution == self.steps_per_revolution || steps_per_revolution == 0 {
+            return;
+        }
+        self.update_current_position();
+        // the position stays the same, only the steps are counted in the new resolution
+        self.steps = (self.steps as i64 * steps_per_revolution as i64
+            / self.steps_per_revolution as i64) as i32;
+        self.steps_per_revolution = steps_per_revolution;
+    }
 }
 
 macro_rules! counter {
@@ -112,6 +129,8 @@ macro_rules! counter {
                 Self {
                     timer,
                     direction: Direction::Clockwise,
+                    steps: 0,
+                    steps_per_revolution: RESOLUTION,
                     past_position: Position::zero(),
                     current_position: Position::zero(),
                     current_velocity: Velocity::zero(),
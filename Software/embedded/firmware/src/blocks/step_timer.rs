@@ -8,6 +8,8 @@ pub struct StepGeneratorTimer<T> {
     timer: T,
     clocks: Clocks,
     frequency: u32,
+    /// The frequency that is actually generated in Hz.
+    generated: f32,
 }
 
 macro_rules! generator {
@@ -46,6 +48,7 @@ macro_rules! generator {
                     timer,
                     clocks,
                     frequency: 0,
+                    generated: 0.0,
                 }
             }
         }
@@ -63,6 +66,7 @@ macro_rules! generator {
 
                 let frequency = freq.0;
                 if frequency == 0 {
+                    self.generated = 0.0;
                     return; // leave the timer in a paused state
                 }
 
@@ -73,6 +77,8 @@ macro_rules! generator {
                 self.timer.psc.write(|w| w.psc().bits(psc as u16));
 
                 let arr = ticks / (psc + 1);
+                self.generated =
+                    (self.clocks.pclk2().0 * pclk_mul) as f32 / ((psc + 1) * arr) as f32;
                 self.timer.arr.write(|w| unsafe { w.bits(arr as u32) });
 
                 self.timer.cr1.modify(|_, w| w.urs().set_bit());
@@ -86,6 +92,10 @@ macro_rules! generator {
                 // start counter
                 self.timer.cr1.modify(|_, w| w.cen().set_bit());
             }
+
+            fn step_frequency(&self) -> f32 {
+                self.generated
+            }
         }
     };
 }
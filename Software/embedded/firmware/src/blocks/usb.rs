@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use sm4_core::prelude::USBLink;
 use sm4_shared::{
     prelude::{USBMessage, USBProtocolConsumer},
     OnError,
@@ -124,6 +125,16 @@ impl USBProtocol {
     }
 }
 
+impl USBLink for USBProtocol {
+    fn receive(&mut self) -> Option<USBMessage> {
+        self.process_interrupt()
+    }
+
+    fn send(&mut self, message: USBMessage) {
+        USBProtocol::send(self, message);
+    }
+}
+
 impl USBProtocolConsumer for USBProtocol {
     fn buffer(&self) -> &[u8] {
         &self.buffer
@@ -1,10 +1,11 @@
 pub mod config {
     pub const CAN_ID: u8 = 0x01;
     pub const SENSE_R: f32 = 0.22;
-    pub const MICROSTEPS: u32 = 16;
+    /// The microstepping the drivers start with, until the one of the object dictionary is applied.
+    pub const MICROSTEPS: u16 = 16;
     pub const STEPS_PER_REV: u32 = 200;
-    pub const ENCODER_RESOLUTION: u32 = MICROSTEPS * STEPS_PER_REV;
-    pub const MICROSTEPS_PER_REV: u32 = ENCODER_RESOLUTION;
+    /// The resolution of the positions, which does not depend on the microstepping of the axes.
+    pub const ENCODER_RESOLUTION: u32 = sm4_shared::prelude::MAX_MICROSTEPS as u32 * STEPS_PER_REV;
 }
 
 pub mod definitions {
@@ -22,6 +23,7 @@ pub mod definitions {
     pub type Dir1 = PA1<Output<PushPull>>;
     pub type Dir2 = PB12<Output<PushPull>>;
 
+    /// The MODE pins are connected to the CFG1 pins of the drivers, their CFG2 pins are left open.
     pub type Mode1 = PA3<Input<Floating>>;
     pub type Mode2 = PA9<Input<Floating>>;
 
@@ -30,8 +32,9 @@ pub mod definitions {
     pub type CurrentRef2Pin = PA5<Analog>;
     pub type CurrentRef2Channel = C2;
 
-    pub type En1 = PA6<Input<Floating>>;
-    pub type En2 = PA7<Input<Floating>>;
+    /// The enable inputs of the drivers are active low.
+    pub type En1 = PA6<Output<PushPull>>;
+    pub type En2 = PA7<Output<PushPull>>;
 
     pub type Step1 = PC6<Alternate<AF3>>;
     pub type Step2 = PA8<Alternate<AF1>>;
@@ -58,20 +61,40 @@ pub mod definitions {
         Step1,
         Dir1,
         CurrentDACChannel<CurrentRef1Channel>,
+        En1,
+        Err1,
+        ModePin<Mode1>,
     >;
     type Axis2Driver = TMC2100<
         StepGeneratorTimer<stm32f4xx_hal::pac::TIM1>,
         Step2,
         Dir2,
         CurrentDACChannel<CurrentRef2Channel>,
+        En2,
+        Err2,
+        ModePin<Mode2>,
+    >;
+    // there is no encoder connected on this revision of the board, the generated steps are counted instead
+    type Axis1Encoder = FilteredEncoder<
+        StepCounterEncoder<stm32f4xx_hal::pac::TIM5, { super::config::ENCODER_RESOLUTION }>,
+        { super::config::ENCODER_RESOLUTION },
+    >;
+    type Axis2Encoder = FilteredEncoder<
+        StepCounterEncoder<stm32f4xx_hal::pac::TIM2, { super::config::ENCODER_RESOLUTION }>,
+        { super::config::ENCODER_RESOLUTION },
     >;
-    type Axis1Encoder =
-        StepCounterEncoder<stm32f4xx_hal::pac::TIM5, { super::config::ENCODER_RESOLUTION }>;
-    type Axis2Encoder =
-        StepCounterEncoder<stm32f4xx_hal::pac::TIM2, { super::config::ENCODER_RESOLUTION }>;
 
-    pub type Axis1 =
-        AxisMotionController<Axis1Driver, Axis1Encoder, { super::config::ENCODER_RESOLUTION }>;
-    pub type Axis2 =
-        AxisMotionController<Axis2Driver, Axis2Encoder, { super::config::ENCODER_RESOLUTION }>;
+    // there are no free inputs for home switches on this revision of the board
+    pub type Axis1 = AxisMotionController<
+        Axis1Driver,
+        Axis1Encoder,
+        NoHomeSwitch,
+        { super::config::ENCODER_RESOLUTION },
+    >;
+    pub type Axis2 = AxisMotionController<
+        Axis2Driver,
+        Axis2Encoder,
+        NoHomeSwitch,
+        { super::config::ENCODER_RESOLUTION },
+    >;
 }
@@ -1,3 +1,4 @@
+use sm4_core::prelude::{I2CPort, I2CTransfer};
 use stm32f4xx_hal::i2c::{Instance, PinScl, PinSda};
 use stm32f4xx_hal::stm32::RCC;
 
@@ -10,12 +11,6 @@ pub enum TransferState {
     Transmitting,
 }
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum State {
-    DataRequested(u8),
-    DataReceived(u8),
-}
-
 // direction as specified in the datasheet
 #[derive(Copy, Clone, PartialEq)]
 pub enum Direction {
@@ -51,7 +46,7 @@ pub struct I2CSlave<I2C: Instance, SDA, SCL> {
     buffer_index: usize,
     register: u8,
     transfer_state: TransferState,
-    state: Option<State>,
+    state: Option<I2CTransfer>,
     _sda: SDA,
     _scl: SCL,
 }
@@ -169,7 +164,7 @@ where
             self.clear_stopped();
             // handle reception
             if self.transfer_state == TransferState::Receiving {
-                self.state = Some(State::DataReceived(self.register));
+                self.state = Some(I2CTransfer::DataReceived(self.register));
             } else if self.transfer_state == TransferState::Transmitting {
                 // data was transmitted, nothing else to do
                 self.state = None;
@@ -188,7 +183,7 @@ where
             defmt::error!("Slave is now Transmitter.");
             if self.transfer_state == TransferState::RegisterSet {
                 self.transfer_state = TransferState::Transmitting;
-                self.state = Some(State::DataRequested(self.register));
+                self.state = Some(I2CTransfer::DataRequested(self.register));
             }
             return;
         }
@@ -278,7 +273,26 @@ where
         data
     }
 
-    pub fn get_state(&self) -> Option<State> {
+    pub fn get_state(&self) -> Option<I2CTransfer> {
         self.state
     }
 }
+
+impl<I2C: Instance, SDA, SCL> I2CPort for I2CSlave<I2C, SDA, SCL>
+where
+    SDA: PinSda<I2C>,
+    SCL: PinScl<I2C>,
+{
+    fn event(&mut self) -> Option<I2CTransfer> {
+        self.event_interrupt();
+        self.get_state()
+    }
+
+    fn received_data(&mut self) -> &[u8] {
+        self.get_received_data()
+    }
+
+    fn set_transmit_buffer(&mut self, data: &[u8]) {
+        I2CSlave::set_transmit_buffer(self, data);
+    }
+}
@@ -12,10 +12,8 @@ mod blocks;
 mod board;
 mod can;
 mod i2c;
-mod protocol;
 mod sm4;
 pub mod staging;
-mod state;
 
 pub mod prelude {
     pub use crate::blocks::*;
@@ -23,8 +21,7 @@ pub mod prelude {
     pub use crate::board::*;
     pub use crate::can::*;
     pub use crate::i2c::*;
-    pub use crate::protocol::*;
-    pub use crate::state::DriverState;
+    pub use sm4_core::prelude::*;
 }
 
 // same panicking *behavior* as `panic-probe` but doesn't print a panic message
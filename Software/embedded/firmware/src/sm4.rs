@@ -1,8 +1,6 @@
 use crate::prelude::config::{CAN_ID, ENCODER_RESOLUTION, SENSE_R};
 use crate::prelude::*;
-use crate::state::DriverState;
-use core::convert::TryFrom;
-use core::{cell::RefCell, convert::TryInto};
+use core::cell::RefCell;
 use embedded_time::duration::Microseconds;
 use hal::dma::StreamsTuple;
 use hal::prelude::*;
@@ -17,12 +15,13 @@ pub struct SM4 {
     usb: USBProtocol,
     can: CANOpen,
     monitoring: Monitoring,
-    state: DriverState<
+    node: Node<
         PersistentStoreObjectDictionary<Storage, { ENCODER_RESOLUTION }>,
         { ENCODER_RESOLUTION },
     >,
     axis1: Axis1,
     axis2: Axis2,
+    coordinated_motion: CoordinatedMotion,
     i2c: I2CSlave<
         stm32f4xx_hal::pac::I2C2,
         crate::board::definitions::SDA,
@@ -59,10 +58,7 @@ impl SM4 {
             clocks,
         );
 
-        let can = CANOpen::new(
-            hal::can::Can::new(device.CAN1, (gpio.can_tx, gpio.can_rx)),
-            CAN_ID,
-        );
+        let can = CANOpen::new(hal::can::Can::new(device.CAN1, (gpio.can_tx, gpio.can_rx)));
 
         let dma2 = StreamsTuple::new(device.DMA2);
         let mut leds = LEDs::new(gpio.status_led, gpio.error_led);
@@ -72,7 +68,8 @@ impl SM4 {
         let timer1 = StepGeneratorTimer::init_tim8(device.TIM8, clocks);
         let timer2 = StepGeneratorTimer::init_tim1(device.TIM1, clocks);
 
-        let sampling_period = Microseconds(1000);
+        let control_period = Microseconds(Self::control_period() / (SECOND / 1_000_000));
+        let ramping_period = Microseconds(Self::ramping_period() / (SECOND / 1_000_000));
 
         let axis1 = AxisMotionController::new(
             TMC2100::new(
@@ -80,11 +77,20 @@ impl SM4 {
                 gpio.step1,
                 gpio.dir1,
                 ref1,
+                gpio.en1,
+                gpio.err1,
+                ModePin::new(gpio.mode1),
                 SENSE_R,
-                config::MICROSTEPS_PER_REV,
+                config::STEPS_PER_REV,
+                config::MICROSTEPS,
+            ),
+            FilteredEncoder::new(
+                StepCounterEncoder::tim5(device.TIM5, control_period),
+                control_period,
             ),
-            StepCounterEncoder::tim5(device.TIM5, sampling_period),
-            sampling_period,
+            NoHomeSwitch,
+            control_period,
+            ramping_period,
         );
         let axis2 = AxisMotionController::new(
             TMC2100::new(
@@ -92,11 +98,20 @@ impl SM4 {
                 gpio.step2,
                 gpio.dir2,
                 ref2,
+                gpio.en2,
+                gpio.err2,
+                ModePin::new(gpio.mode2),
                 SENSE_R,
-                config::MICROSTEPS_PER_REV,
+                config::STEPS_PER_REV,
+                config::MICROSTEPS,
+            ),
+            FilteredEncoder::new(
+                StepCounterEncoder::tim2(device.TIM2, control_period),
+                control_period,
             ),
-            StepCounterEncoder::tim2(device.TIM2, sampling_period),
-            sampling_period,
+            NoHomeSwitch,
+            control_period,
+            ramping_period,
         );
 
         static STORAGE: Mutex<RefCell<Storage>> = Mutex::new(RefCell::new(Storage::new()));
@@ -107,9 +122,12 @@ impl SM4 {
             .init()
             .on_error(|_| defmt::error!("Initialization of storage failed."));
 
-        let od = PersistentStoreObjectDictionary::<_, { ENCODER_RESOLUTION }>::new(&STORAGE);
-        let mut state = DriverState::new(od);
-        state.go_to_preoperational_if_needed();
+        let mut od = PersistentStoreObjectDictionary::<_, { ENCODER_RESOLUTION }>::new(&STORAGE);
+        od.axis_mut(Axis::Axis1)
+            .restrict_microsteps(TMC2100_MICROSTEPS);
+        od.axis_mut(Axis::Axis2)
+            .restrict_microsteps(TMC2100_MICROSTEPS);
+        let node = Node::new(CAN_ID, od);
 
         defmt::debug!("Init done.");
         Self {
@@ -117,47 +135,76 @@ impl SM4 {
             leds,
             usb,
             monitoring: Monitoring::new(device.ADC1, gpio.battery_voltage, dma2.0),
-            state,
+            node,
             axis1,
             axis2,
+            coordinated_motion: CoordinatedMotion::new(control_period),
             i2c: I2CSlave::new(device.I2C2, 0x55, gpio.sda, gpio.scl),
         }
     }
 
     pub fn control(&mut self) {
-        self.axis1.control(
-            self.state.is_movement_blocked(),
-            self.state.object_dictionary().axis_mut(Axis::Axis1),
-        );
-        self.axis2.control(
-            self.state.is_movement_blocked(),
-            self.state.object_dictionary().axis_mut(Axis::Axis2),
-        );
+        let blocked = self.node.state().is_movement_blocked();
+        match self
+            .coordinated_motion
+            .control(blocked, self.node.state_mut().object_dictionary())
+        {
+            Some([reference1, reference2]) => {
+                self.axis1.follow(
+                    blocked,
+                    self.node
+                        .state_mut()
+                        .object_dictionary()
+                        .axis_mut(Axis::Axis1),
+                    reference1,
+                );
+                self.axis2.follow(
+                    blocked,
+                    self.node
+                        .state_mut()
+                        .object_dictionary()
+                        .axis_mut(Axis::Axis2),
+                    reference2,
+                );
+            }
+            None => {
+                self.axis1.control_axis(
+                    blocked,
+                    self.node.state_mut().object_dictionary(),
+                    Axis::Axis1,
+                );
+                self.axis2.control_axis(
+                    blocked,
+                    self.node.state_mut().object_dictionary(),
+                    Axis::Axis2,
+                );
+            }
+        }
     }
 
     pub fn ramp(&mut self) {
         self.axis1.ramp(
-            self.state.is_movement_blocked(),
-            self.state.object_dictionary().axis_mut(Axis::Axis1),
+            self.node.state().is_movement_blocked(),
+            self.node
+                .state_mut()
+                .object_dictionary()
+                .axis_mut(Axis::Axis1),
         );
         self.axis2.ramp(
-            self.state.is_movement_blocked(),
-            self.state.object_dictionary().axis_mut(Axis::Axis2),
+            self.node.state().is_movement_blocked(),
+            self.node
+                .state_mut()
+                .object_dictionary()
+                .axis_mut(Axis::Axis2),
         );
     }
 
     pub fn failsafe_tick(&mut self) {
-        self.state.decrement_last_received_speed_command_counter();
+        self.node.failsafe_tick();
     }
 
     pub fn heartbeat_tick(&mut self) {
-        self.leds.heartbeat();
-        self.can
-            .send(
-                CANOpenMessage::NMTNodeMonitoring,
-                &[u8::from(self.state.nmt_state())],
-            )
-            .on_error(|_| self.leds.signalize_can_error());
+        self.node.heartbeat(&mut self.can, &mut self.leds);
     }
 
     pub fn blink_leds(&mut self) {
@@ -170,244 +217,27 @@ impl SM4 {
 
     pub fn monitoring_complete(&mut self) {
         self.monitoring.transfer_complete();
-        self.state
+        self.node
+            .state_mut()
             .object_dictionary()
             .set_battery_voltage(self.monitoring.get_battery_voltage());
-        self.state
+        self.node
+            .state_mut()
             .object_dictionary()
             .set_temperature(self.monitoring.get_temperature());
+        monitor_supply(self.node.state_mut().object_dictionary());
     }
 
     pub fn process_usb(&mut self) {
-        match self.usb.process_interrupt() {
-            Some(USBMessage::Request(index, subindex)) => {
-                let (data, len) =
-                    read_object_dictionary(index, subindex, self.state.object_dictionary());
-                self.usb
-                    .send(USBMessage::Transfer(index, subindex, len as u8, data));
-            }
-            Some(USBMessage::Transfer(index, subindex, length, data)) => update_object_dictionary(
-                index,
-                subindex,
-                &data[..length as usize],
-                self.state.object_dictionary(),
-            ),
-            None => {}
-        }
+        self.node.process_usb(&mut self.usb);
     }
 
     pub fn process_can(&mut self) {
-        if let Some((message, frame)) = self.can.process_incoming_frame() {
-            match message {
-                CANOpenMessage::NMTNodeControl => {
-                    nmt_received(CAN_ID, &frame, &mut self.state);
-                }
-                CANOpenMessage::GlobalFailsafeCommand => {}
-                CANOpenMessage::Sync => {
-                    self.leds.signalize_sync();
-                    sync(&mut self.can, &mut self.state, &mut self.leds);
-                }
-                CANOpenMessage::Emergency => {}
-                CANOpenMessage::TimeStamp => {}
-                CANOpenMessage::RxPDO1 => rx_pdo1(&frame, &mut self.state),
-                CANOpenMessage::RxPDO2 => rx_pdo2(&frame, &mut self.state),
-                CANOpenMessage::RxPDO3 => rx_pdo3(&frame, &mut self.state),
-                CANOpenMessage::RxPDO4 => rx_pdo4(&frame, &mut self.state),
-                CANOpenMessage::RxSDO => {
-                    if frame.dlc() != 8 {
-                        return;
-                    }
-                    let data = frame.data().unwrap();
-                    let command = data[0];
-                    let ccs = (command & 0xe0) >> 5;
-                    let length = (4 - ((command & 0x0c) >> 2)) as usize; // 4 - n
-                    let expedited = (command & 0x02) > 0; // e
-                    let size_in_command = (command & 0x01) > 0; // s
-
-                    if !(expedited && size_in_command) {
-                        return; // ignore not expedited frames
-                    }
-
-                    let index = u16::from_le_bytes(data[1..3].try_into().unwrap());
-                    let subindex = data[3];
-
-                    if ccs == 1 {
-                        // initiate download, data are written to the SM4 controller
-                        let raw_data: [u8; 4] = data[4..].try_into().unwrap();
-                        update_object_dictionary(
-                            index,
-                            subindex,
-                            &raw_data[..length],
-                            self.state.object_dictionary(),
-                        );
-                        self.can
-                            .send(
-                                CANOpenMessage::TxSDO,
-                                &[
-                                    0b011_0_00_00,
-                                    (index & 0xff) as u8,
-                                    ((index & 0x00ff) >> 8) as u8,
-                                    subindex,
-                                    0,
-                                    0,
-                                    0,
-                                    0,
-                                ],
-                            )
-                            .on_error(|_| defmt::error!("Failed to send TxSDO."));
-                    } else if ccs == 2 {
-                        // initiate upload, data are read from the SM4 controller
-                        let (data, _) =
-                            read_object_dictionary(index, subindex, self.state.object_dictionary());
-                        self.can
-                            .send(
-                                CANOpenMessage::TxSDO,
-                                &[
-                                    0b010_0_00_00,
-                                    (index & 0xff) as u8,
-                                    ((index & 0x00ff) >> 8) as u8,
-                                    subindex,
-                                    data[0],
-                                    data[1],
-                                    data[2],
-                                    data[3],
-                                ],
-                            )
-                            .on_error(|_| defmt::error!("Failed to send TxSDO."));
-                    }
-                }
-                _ => {}
-            }
-        }
+        self.node.process_can(&mut self.can, &mut self.leds);
     }
 
     pub fn process_i2c_event(&mut self) {
-        self.i2c.event_interrupt();
-        defmt::error!("event");
-        match self.i2c.get_state() {
-            None => {}
-            Some(State::DataRequested(register)) => {
-                if let Ok(register) = I2CRegister::try_from(register) {
-                    if !register.readable() {
-                        defmt::error!("Register not readable");
-                        // TOOD nack
-                    }
-                    match register {
-                        I2CRegister::AxisSettings => {
-                            self.i2c.set_transmit_buffer(&axis_settings(
-                                self.state.object_dictionary(),
-                            ));
-                        }
-                        I2CRegister::Axis1Velocity => self.i2c.set_transmit_buffer(
-                            &self
-                                .state
-                                .object_dictionary()
-                                .axis(Axis::Axis1)
-                                .actual_velocity()
-                                .get_rps()
-                                .to_le_bytes(),
-                        ),
-                        I2CRegister::Axis2Velocity => self.i2c.set_transmit_buffer(
-                            &self
-                                .state
-                                .object_dictionary()
-                                .axis(Axis::Axis2)
-                                .actual_velocity()
-                                .get_rps()
-                                .to_le_bytes(),
-                        ),
-                        I2CRegister::BothAxesVelocity => {
-                            // nacked before
-                        }
-                        I2CRegister::Axis1Position => {
-                            self.i2c.set_transmit_buffer(&position(
-                                &self
-                                    .state
-                                    .object_dictionary()
-                                    .axis(Axis::Axis1)
-                                    .actual_position(),
-                            ));
-                        }
-                        I2CRegister::Axis2Position => {
-                            self.i2c.set_transmit_buffer(&position(
-                                &self
-                                    .state
-                                    .object_dictionary()
-                                    .axis(Axis::Axis2)
-                                    .actual_position(),
-                            ));
-                        }
-                        I2CRegister::BothAxesPosition => {
-                            self.i2c.set_transmit_buffer(&both_axes_position(
-                                self.state.object_dictionary(),
-                            ));
-                        }
-                    }
-                }
-            }
-            Some(State::DataReceived(register)) => {
-                if let Ok(register) = I2CRegister::try_from(register) {
-                    if !register.writeable() {
-                        // TOOD nack
-                    }
-                    match register {
-                        I2CRegister::AxisSettings => {
-                            set_axis_settings(
-                                self.i2c.get_received_data(),
-                                self.state.object_dictionary(),
-                            );
-                            self.state.go_to_operational();
-                        }
-                        I2CRegister::Axis1Velocity => {
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis1)
-                                .set_target_velocity(parse_velocity(self.i2c.get_received_data()));
-                            self.state.invalidate_last_received_speed_command_counter();
-                        }
-                        I2CRegister::Axis2Velocity => {
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis2)
-                                .set_target_velocity(parse_velocity(self.i2c.get_received_data()));
-                            self.state.invalidate_last_received_speed_command_counter();
-                        }
-                        I2CRegister::BothAxesVelocity => {
-                            let (axis1_velocity, axis2_velocity) =
-                                parse_both_axes_velocities(self.i2c.get_received_data());
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis1)
-                                .set_target_velocity(axis1_velocity);
-
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis2)
-                                .set_target_velocity(axis2_velocity);
-
-                            self.state.invalidate_last_received_speed_command_counter();
-                        }
-                        I2CRegister::Axis1Position => {
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis1)
-                                .set_target_position(parse_position(self.i2c.get_received_data()));
-
-                            self.state.invalidate_last_received_speed_command_counter();
-                        }
-                        I2CRegister::Axis2Position => {
-                            self.state
-                                .object_dictionary()
-                                .axis_mut(Axis::Axis2)
-                                .set_target_position(parse_position(self.i2c.get_received_data()));
-
-                            self.state.invalidate_last_received_speed_command_counter();
-                        }
-                        I2CRegister::BothAxesPosition => {}
-                    }
-                }
-            }
-        }
+        self.node.process_i2c_event(&mut self.i2c);
     }
 
     pub fn process_i2c_error(&mut self) {
@@ -0,0 +1,49 @@
+//! The single wire UART of the Trinamic drivers over the USART in the half-duplex mode.
+use super::{Error, Instance, Pins, Serial};
+use embedded_hal::blocking::serial::Write;
+use embedded_hal::serial;
+use sm4_shared::prelude::TrinamicUart;
+
+/// The number of polls of the receiver before the reception of a byte times out.
+const READ_TIMEOUT: u32 = 100_000;
+
+impl<USART, PINS> Serial<USART, PINS, u8>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    fn read_byte(&mut self) -> Result<u8, Error> {
+        for _ in 0..READ_TIMEOUT {
+            match serial::Read::read(self) {
+                Ok(byte) => return Ok(byte),
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(error)) => return Err(error),
+            }
+        }
+        Err(Error::Timeout)
+    }
+}
+
+impl<USART, PINS> TrinamicUart for Serial<USART, PINS, u8>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    type Error = Error;
+
+    /// The transmitted bytes are received back on the single wire, they are read and discarded.
+    fn write(&mut self, datagram: &[u8]) -> Result<(), Self::Error> {
+        for byte in datagram {
+            self.bwrite_all(&[*byte])?;
+            self.read_byte()?;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        for byte in buffer.iter_mut() {
+            *byte = self.read_byte()?;
+        }
+        Ok(())
+    }
+}
@@ -26,6 +26,8 @@ use nb::block;
 
 use stm32f4xx_hal::stm32::{RCC, USART1};
 
+mod half_duplex_uart;
+
 /// Serial error
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Error {
@@ -37,6 +39,8 @@ pub enum Error {
     Overrun,
     /// Parity check error
     Parity,
+    /// No data received in time
+    Timeout,
     #[doc(hidden)]
     _Extensible,
 }
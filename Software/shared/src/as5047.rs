@@ -0,0 +1,321 @@
+//! Absolute magnetic encoder AS5047 connected over SPI.
+//!
+//! The encoder measures the single-turn angle with the resolution of 14 bits. The frames are 16 bits long,
+//! the most significant bit is the even parity of the frame. The response to a read command is received
+//! during the transfer of the next frame, so every read is followed by the NOP command.
+//!
+//! The revolutions are tracked on top of the single-turn angle by counting the crossings of the zero angle,
+//! which requires the encoder to be sampled more often than it turns by the half of a revolution.
+//! The zero offset is the raw angle that corresponds to the zero position of the axis.
+use crate::encoder::{Direction, Encoder};
+use crate::hal::EncoderRegisters;
+use crate::models::{Position, Velocity};
+use embedded_time::duration::Microseconds;
+
+/// The number of increments of the single-turn angle measured by the encoder.
+pub const AS5047_RESOLUTION: u32 = 1 << 14;
+
+const ANGLE_MASK: u16 = 0x3fff;
+const READ: u16 = 0x4000;
+const ERROR_FLAG: u16 = 0x4000;
+const PARITY: u16 = 0x8000;
+
+/// The registers of the encoder that are read by the driver.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum As5047Register {
+    Nop,
+    ErrorFlags,
+    /// The measured angle with the dynamic angle error compensation.
+    AngleCompensated,
+}
+
+impl From<As5047Register> for u16 {
+    fn from(register: As5047Register) -> Self {
+        match register {
+            As5047Register::Nop => 0x0000,
+            As5047Register::ErrorFlags => 0x0001,
+            As5047Register::AngleCompensated => 0x3fff,
+        }
+    }
+}
+
+/// The errors of the communication with the encoder.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum As5047Error<E> {
+    /// The transfer of the frame failed.
+    Bus(E),
+    /// The parity of the received frame does not match.
+    Parity,
+    /// The encoder reported an error of the previous command.
+    Frame,
+}
+
+/// Returns the `frame` with the parity bit set, so the frame has even parity.
+fn with_parity(frame: u16) -> u16 {
+    if (frame & !PARITY).count_ones() % 2 == 1 {
+        frame | PARITY
+    } else {
+        frame & !PARITY
+    }
+}
+
+/// Returns the command frame reading the `register`.
+pub fn read_command(register: As5047Register) -> u16 {
+    with_parity(READ | u16::from(register))
+}
+
+/// Returns the data of the received `frame`, when the frame is valid.
+pub fn parse_frame<E>(frame: u16) -> Result<u16, As5047Error<E>> {
+    if with_parity(frame) != frame {
+        Err(As5047Error::Parity)
+    } else if frame & ERROR_FLAG != 0 {
+        Err(As5047Error::Frame)
+    } else {
+        Ok(frame & ANGLE_MASK)
+    }
+}
+
+/// Counter of the revolutions of an absolute single-turn encoder.
+#[derive(Copy, Clone, Default)]
+pub struct MultiTurn {
+    revolutions: i32,
+    past_angle: Option<u16>,
+}
+
+impl MultiTurn {
+    /// Updates the revolutions with the single-turn `angle` and returns the revolutions.
+    pub fn update(&mut self, angle: u16) -> i32 {
+        if let Some(past) = self.past_angle {
+            let half = (AS5047_RESOLUTION / 2) as i32;
+            let step = angle as i32 - past as i32;
+            if step < -half {
+                self.revolutions += 1;
+            } else if step > half {
+                self.revolutions -= 1;
+            }
+        }
+        self.past_angle = Some(angle);
+        self.revolutions
+    }
+
+    /// Resets the revolutions to zero, the next angle is taken as it is.
+    pub fn reset(&mut self) {
+        self.revolutions = 0;
+        self.past_angle = None;
+    }
+}
+
+/// The AS5047 encoder, the angle is scaled to the `RESOLUTION` of the axis.
+pub struct As5047Encoder<B: EncoderRegisters, const RESOLUTION: u32> {
+    bus: B,
+    zero_offset: u16,
+    multi_turn: MultiTurn,
+    past_position: Position<RESOLUTION>,
+    current_position: Position<RESOLUTION>,
+    current_velocity: Velocity,
+    sampling_period: Microseconds,
+    last_error: Option<As5047Error<B::Error>>,
+}
+
+impl<B: EncoderRegisters, const RESOLUTION: u32> As5047Encoder<B, RESOLUTION>
+where
+    B::Error: Copy,
+{
+    /// Creates a new encoder.
+    ///
+    /// # Arguments
+    /// * `bus` - the access to the registers of the encoder
+    /// * `zero_offset` - the raw angle corresponding to the zero position
+    /// * `sampling_period` - period in which the encoder is sampled
+    pub fn new(bus: B, zero_offset: u16, sampling_period: Microseconds) -> Self {
+        Self {
+            bus,
+            zero_offset: zero_offset & ANGLE_MASK,
+            multi_turn: MultiTurn::default(),
+            past_position: Position::zero(),
+            current_position: Position::zero(),
+            current_velocity: Velocity::zero(),
+            sampling_period,
+            last_error: None,
+        }
+    }
+
+    pub fn zero_offset(&self) -> u16 {
+        self.zero_offset
+    }
+
+    /// Sets the raw angle corresponding to the zero position, the revolutions are counted from the zero again.
+    pub fn set_zero_offset(&mut self, zero_offset: u16) {
+        self.zero_offset = zero_offset & ANGLE_MASK;
+        self.multi_turn.reset();
+    }
+
+    /// Returns the error of the last sample, `None` when the last sample was successful.
+    pub fn last_error(&self) -> Option<As5047Error<B::Error>> {
+        self.last_error
+    }
+
+    /// Reads the `register` of the encoder.
+    pub fn read(&mut self, register: As5047Register) -> Result<u16, As5047Error<B::Error>> {
+        self.bus
+            .transfer(read_command(register))
+            .map_err(As5047Error::Bus)?;
+        let response = self
+            .bus
+            .transfer(read_command(As5047Register::Nop))
+            .map_err(As5047Error::Bus)?;
+        parse_frame(response)
+    }
+
+    /// Reads the raw single-turn angle.
+    pub fn raw_angle(&mut self) -> Result<u16, As5047Error<B::Error>> {
+        self.read(As5047Register::AngleCompensated)
+    }
+
+    fn position(&mut self, raw_angle: u16) -> Position<RESOLUTION> {
+        let angle = raw_angle.wrapping_sub(self.zero_offset) & ANGLE_MASK;
+        let revolutions = self.multi_turn.update(angle);
+        let angle = (angle as u64 * RESOLUTION as u64 / AS5047_RESOLUTION as u64) as u32;
+        Position::new(revolutions, angle)
+    }
+}
+
+impl<B: EncoderRegisters, const RESOLUTION: u32> Encoder<RESOLUTION>
+    for As5047Encoder<B, RESOLUTION>
+where
+    B::Error: Copy,
+{
+    fn get_velocity(&self) -> Velocity {
+        self.current_velocity
+    }
+
+    fn get_position(&self) -> Position<RESOLUTION> {
+        self.current_position
+    }
+
+    /// The zero offset is moved to the current angle.
+    fn reset_position(&mut self) -> Position<RESOLUTION> {
+        let past = self.current_position;
+        if let Ok(angle) = self.raw_angle() {
+            self.set_zero_offset(angle);
+            self.multi_turn.update(0);
+        }
+        self.current_position = Position::zero();
+        self.past_position = Position::zero();
+        self.current_velocity = Velocity::zero();
+        past
+    }
+
+    /// When the angle cannot be read, the last position is kept.
+    fn sample(&mut self) {
+        match self.raw_angle() {
+            Ok(angle) => {
+                self.current_position = self.position(angle);
+                self.last_error = None;
+            }
+            Err(error) => self.last_error = Some(error),
+        }
+
+        self.current_velocity = Velocity::from_positions(
+            &self.current_position,
+            &self.past_position,
+            self.sampling_period,
+        );
+        self.past_position = self.current_position;
+    }
+
+    /// The absolute encoder measures the direction by itself, the notification is ignored.
+    fn notify_direction_changed(&mut self, _direction: Direction) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    /// The encoder responding with the angle to the command sent in the previous transfer.
+    struct MockBus {
+        angle: u16,
+        pending: u16,
+        fail: bool,
+    }
+
+    impl EncoderRegisters for MockBus {
+        type Error = ();
+
+        fn transfer(&mut self, frame: u16) -> Result<u16, Self::Error> {
+            if self.fail {
+                return Err(());
+            }
+            let response = self.pending;
+            self.pending = if frame == read_command(As5047Register::AngleCompensated) {
+                with_parity(self.angle)
+            } else {
+                with_parity(0)
+            };
+            Ok(response)
+        }
+    }
+
+    fn encoder(angle: u16, zero_offset: u16) -> As5047Encoder<MockBus, 4096> {
+        let bus = MockBus {
+            angle,
+            pending: 0,
+            fail: false,
+        };
+        As5047Encoder::new(bus, zero_offset, PERIOD)
+    }
+
+    #[test]
+    fn frames() {
+        assert_eq!(read_command(As5047Register::AngleCompensated), 0xffff);
+        assert_eq!(read_command(As5047Register::ErrorFlags), 0x4001);
+        assert_eq!(parse_frame::<()>(0x0122), Err(As5047Error::Parity));
+        assert_eq!(parse_frame::<()>(0x8122), Ok(0x0122));
+        assert_eq!(parse_frame::<()>(0xc000), Err(As5047Error::Frame));
+    }
+
+    #[test]
+    fn multi_turn() {
+        let mut encoder = encoder(16000, 1000);
+        encoder.sample();
+        assert_eq!(encoder.get_position().get_revolutions(), 0);
+        assert_eq!(encoder.get_position().get_angle(), 3750);
+
+        // crossing the zero offset forwards
+        encoder.bus.angle = 1100;
+        encoder.sample();
+        assert_eq!(encoder.get_position().get_revolutions(), 1);
+        assert_eq!(encoder.get_position().get_angle(), 25);
+
+        // and backwards twice
+        for angle in [900, 10000, 2000, 900] {
+            encoder.bus.angle = angle;
+            encoder.sample();
+        }
+        assert_eq!(encoder.get_position().get_revolutions(), -1);
+        assert_eq!(encoder.get_position().get_angle(), 4071);
+    }
+
+    #[test]
+    fn errors() {
+        let mut encoder = encoder(2000, 0);
+        encoder.sample();
+        let position = encoder.get_position();
+        encoder.bus.fail = true;
+        encoder.bus.angle = 3000;
+        encoder.sample();
+        assert_eq!(encoder.last_error(), Some(As5047Error::Bus(())));
+        assert_eq!(
+            encoder.get_position().get_increments(),
+            position.get_increments()
+        );
+
+        encoder.bus.fail = false;
+        encoder.reset_position();
+        assert_eq!(encoder.zero_offset(), 3000);
+        encoder.sample();
+        assert_eq!(encoder.get_position().get_increments(), 0);
+    }
+}
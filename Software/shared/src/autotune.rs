@@ -0,0 +1,362 @@
+//! Auto-tuning of the controller gains by the relay feedback experiment (Åström–Hägglund).
+//!
+//! The controller of the tuned loop is replaced by a relay, which switches the output between
+//! the plus and minus amplitude whenever the measured value crosses the setpoint. Most plants settle
+//! into a stable oscillation, whose period is the ultimate period. The ultimate gain follows from
+//! the describing function of the relay with hysteresis, `Ku = 4d / (π √(a² - h²))`,
+//! where `d` is the relay amplitude, `a` the amplitude of the oscillation and `h` the hysteresis.
+//!
+//! The first cycle is discarded, as the oscillation is not settled yet, and the period and the amplitude
+//! are averaged over the following cycles. The gains are then calculated by the selected [TuningRule].
+use crate::models::{TuningLoop, TuningRule, TuningState};
+use core::f32::consts::PI;
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// The number of cycles discarded at the start of the experiment.
+const DISCARDED_CYCLES: u8 = 1;
+/// The number of cycles the period and the amplitude are averaged over.
+const MEASURED_CYCLES: u8 = 4;
+/// The maximal time between two switches of the relay in seconds, the experiment fails when it elapses.
+const SWITCH_TIMEOUT: f32 = 10.0;
+
+#[derive(Copy, Clone)]
+pub struct AutoTuneSettings {
+    tuning_loop: TuningLoop,
+    rule: TuningRule,
+    amplitude: f32,
+    hysteresis: f32,
+}
+
+impl AutoTuneSettings {
+    /// Creates new auto-tuning settings.
+    ///
+    /// # Arguments
+    /// * `tuning_loop` - the controller that is tuned
+    /// * `rule` - the rule calculating the gains
+    /// * `amplitude` - the amplitude of the relay output in revolutions per second
+    /// * `hysteresis` - the hysteresis of the relay in revolutions per second for the velocity loop
+    ///   and in revolutions for the position loop, it shall exceed the noise of the measured value
+    pub fn new(tuning_loop: TuningLoop, rule: TuningRule, amplitude: f32, hysteresis: f32) -> Self {
+        Self {
+            tuning_loop,
+            rule,
+            amplitude,
+            hysteresis,
+        }
+    }
+
+    pub fn tuning_loop(&self) -> TuningLoop {
+        self.tuning_loop
+    }
+    pub fn rule(&self) -> TuningRule {
+        self.rule
+    }
+    pub fn amplitude(&self) -> f32 {
+        self.amplitude
+    }
+    pub fn hysteresis(&self) -> f32 {
+        self.hysteresis
+    }
+    pub fn set_tuning_loop(&mut self, tuning_loop: TuningLoop) {
+        self.tuning_loop = tuning_loop;
+    }
+    pub fn set_rule(&mut self, rule: TuningRule) {
+        self.rule = rule;
+    }
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis = hysteresis;
+    }
+}
+
+impl Default for AutoTuneSettings {
+    fn default() -> Self {
+        Self::new(TuningLoop::default(), TuningRule::default(), 1.0, 0.05)
+    }
+}
+
+/// The result of the relay feedback experiment and the proposed gains of the controller.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TuningResult {
+    /// The controller the gains are proposed for.
+    pub tuning_loop: TuningLoop,
+    pub ultimate_gain: f32,
+    /// The ultimate period in seconds.
+    pub ultimate_period: f32,
+    pub proportional: f32,
+    pub integral: f32,
+    pub derivative: f32,
+}
+
+impl TuningResult {
+    /// Calculates the gains of the [crate::psd::PSDController] from the ultimate gain and period
+    /// by the rule selected in the `settings`.
+    pub fn new(ultimate_gain: f32, ultimate_period: f32, settings: &AutoTuneSettings) -> Self {
+        // the proportional gain and the integral and derivative times
+        let (kp, ti, td) = match settings.rule {
+            TuningRule::ZieglerNichols => (
+                0.6 * ultimate_gain,
+                ultimate_period / 2.0,
+                ultimate_period / 8.0,
+            ),
+            TuningRule::ZieglerNicholsPI => (0.45 * ultimate_gain, ultimate_period / 1.2, 0.0),
+            TuningRule::TyreusLuyben => (
+                ultimate_gain / 2.2,
+                2.2 * ultimate_period,
+                ultimate_period / 6.3,
+            ),
+            TuningRule::NoOvershoot => (
+                0.2 * ultimate_gain,
+                ultimate_period / 2.0,
+                ultimate_period / 3.0,
+            ),
+        };
+        Self {
+            tuning_loop: settings.tuning_loop,
+            ultimate_gain,
+            ultimate_period,
+            proportional: kp,
+            integral: kp / ti,
+            derivative: kp * td,
+        }
+    }
+}
+
+/// The relay feedback experiment of a single axis.
+pub struct RelayAutoTuner {
+    period: f32,
+    state: TuningState,
+    setpoint: f32,
+    /// The sign of the relay output.
+    direction: f32,
+    /// The time since the start of the experiment in seconds.
+    time: f32,
+    last_switch: f32,
+    /// The time of the last switch to the positive output, `None` before the first one.
+    last_rising: Option<f32>,
+    cycles: u8,
+    period_sum: f32,
+    amplitude_sum: f32,
+    /// The extremes of the deviation from the setpoint in the current cycle.
+    max: f32,
+    min: f32,
+    result: TuningResult,
+}
+
+impl RelayAutoTuner {
+    pub fn new(sampling_period: Microseconds) -> Self {
+        Self {
+            period: sampling_period.0 as f32 / 1_000_000.0,
+            state: TuningState::Idle,
+            setpoint: 0.0,
+            direction: 1.0,
+            time: 0.0,
+            last_switch: 0.0,
+            last_rising: None,
+            cycles: 0,
+            period_sum: 0.0,
+            amplitude_sum: 0.0,
+            max: 0.0,
+            min: 0.0,
+            result: TuningResult::default(),
+        }
+    }
+
+    pub fn state(&self) -> TuningState {
+        self.state
+    }
+
+    /// Returns the value the measured value oscillates around.
+    pub fn setpoint(&self) -> f32 {
+        self.setpoint
+    }
+
+    /// Returns the result of the last finished experiment.
+    pub fn result(&self) -> TuningResult {
+        self.result
+    }
+
+    /// Starts the experiment, the measured value oscillates around the `setpoint`.
+    pub fn start(&mut self, setpoint: f32) {
+        *self = Self {
+            period: self.period,
+            state: TuningState::InProgress,
+            setpoint,
+            ..Self::new(Microseconds(0))
+        };
+    }
+
+    /// Stops the experiment. When the experiment was not finished, it ends with an error.
+    pub fn abort(&mut self) {
+        if self.state == TuningState::InProgress {
+            self.state = TuningState::Error;
+        }
+    }
+
+    /// Advances the experiment with the `measured` value and returns the relay output,
+    /// which is zero when no experiment is in progress.
+    pub fn sample(&mut self, measured: f32, settings: &AutoTuneSettings) -> f32 {
+        if self.state != TuningState::InProgress {
+            return 0.0;
+        }
+        self.time += self.period;
+
+        let deviation = measured - self.setpoint;
+        self.max = self.max.max(deviation);
+        self.min = self.min.min(deviation);
+
+        if self.direction > 0.0 && deviation > settings.hysteresis {
+            self.direction = -1.0;
+            self.last_switch = self.time;
+        } else if self.direction < 0.0 && deviation < -settings.hysteresis {
+            self.direction = 1.0;
+            self.last_switch = self.time;
+            self.complete_cycle(deviation, settings);
+        } else if self.time - self.last_switch > SWITCH_TIMEOUT {
+            self.state = TuningState::Error;
+        }
+
+        if self.state == TuningState::InProgress {
+            self.direction * settings.amplitude
+        } else {
+            0.0
+        }
+    }
+
+    /// Accounts the cycle ending with the switch to the positive output.
+    fn complete_cycle(&mut self, deviation: f32, settings: &AutoTuneSettings) {
+        if let Some(last_rising) = self.last_rising {
+            self.cycles += 1;
+            if self.cycles > DISCARDED_CYCLES {
+                self.period_sum += self.time - last_rising;
+                self.amplitude_sum += (self.max - self.min) / 2.0;
+            }
+            if self.cycles == DISCARDED_CYCLES + MEASURED_CYCLES {
+                self.finish(settings);
+            }
+        }
+        self.last_rising = Some(self.time);
+        self.max = deviation;
+        self.min = deviation;
+    }
+
+    fn finish(&mut self, settings: &AutoTuneSettings) {
+        let period = self.period_sum / MEASURED_CYCLES as f32;
+        let amplitude = self.amplitude_sum / MEASURED_CYCLES as f32;
+        let hysteresis = settings.hysteresis.abs();
+        if amplitude <= hysteresis {
+            self.state = TuningState::Error;
+            return;
+        }
+
+        let ultimate_gain = 4.0 * settings.amplitude.abs()
+            / (PI * (amplitude * amplitude - hysteresis * hysteresis).sqrt());
+        self.result = TuningResult::new(ultimate_gain, period, settings);
+        self.state = TuningState::Finished;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    /// First-order plant with the dead time, `y' = (gain * u(t - delay) - y) / time_constant`.
+    struct Plant {
+        gain: f32,
+        time_constant: f32,
+        delayed: [f32; 20],
+        output: f32,
+    }
+
+    impl Plant {
+        fn new(gain: f32, time_constant: f32) -> Self {
+            Self {
+                gain,
+                time_constant,
+                delayed: [0.0; 20],
+                output: 0.0,
+            }
+        }
+
+        fn sample(&mut self, input: f32) -> f32 {
+            let delayed = self.delayed[0];
+            self.delayed.rotate_left(1);
+            self.delayed[19] = input;
+            self.output += 0.001 * (self.gain * delayed - self.output) / self.time_constant;
+            self.output
+        }
+    }
+
+    fn run(tuner: &mut RelayAutoTuner, plant: &mut Plant, settings: &AutoTuneSettings) {
+        let mut output = 0.0;
+        for _ in 0..20_000 {
+            let measured = plant.sample(output);
+            output = tuner.sample(measured, settings);
+            if tuner.state() != TuningState::InProgress {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn first_order_plant_with_delay() {
+        // the ultimate gain of the plant is 4.25 and the ultimate period is 74.4 ms
+        let settings =
+            AutoTuneSettings::new(TuningLoop::Velocity, TuningRule::ZieglerNichols, 1.0, 0.0);
+        let mut tuner = RelayAutoTuner::new(PERIOD);
+        let mut plant = Plant::new(2.0, 0.1);
+        tuner.start(0.0);
+        run(&mut tuner, &mut plant, &settings);
+
+        assert_eq!(tuner.state(), TuningState::Finished);
+        let result = tuner.result();
+        // the describing function is only an approximation of the ultimate gain
+        assert!((result.ultimate_gain - 4.25).abs() < 0.25 * 4.25);
+        assert!((result.ultimate_period - 0.0744).abs() < 0.005);
+        assert_eq!(result.proportional, 0.6 * result.ultimate_gain);
+        assert!(
+            (result.integral - result.proportional * 2.0 / result.ultimate_period).abs() < 1e-3
+        );
+        assert!(
+            (result.derivative - result.proportional * result.ultimate_period / 8.0).abs() < 1e-6
+        );
+    }
+
+    #[test]
+    fn hysteresis() {
+        let settings =
+            AutoTuneSettings::new(TuningLoop::Velocity, TuningRule::ZieglerNicholsPI, 1.0, 0.2);
+        let mut tuner = RelayAutoTuner::new(PERIOD);
+        let mut plant = Plant::new(2.0, 0.1);
+        tuner.start(0.5);
+        run(&mut tuner, &mut plant, &settings);
+
+        assert_eq!(tuner.state(), TuningState::Finished);
+        let result = tuner.result();
+        // the hysteresis delays the switching, so the oscillation is slower
+        assert!(result.ultimate_period > 0.0744);
+        assert_eq!(result.derivative, 0.0);
+    }
+
+    #[test]
+    fn no_oscillation() {
+        let settings = AutoTuneSettings::default();
+        let mut tuner = RelayAutoTuner::new(PERIOD);
+        // the plant does not respond at all
+        let mut plant = Plant::new(0.0, 0.1);
+        tuner.start(0.0);
+        run(&mut tuner, &mut plant, &settings);
+        assert_eq!(tuner.state(), TuningState::Error);
+        assert_eq!(tuner.sample(0.0, &settings), 0.0);
+
+        tuner.start(0.0);
+        tuner.abort();
+        assert_eq!(tuner.state(), TuningState::Error);
+    }
+}
@@ -0,0 +1,179 @@
+//! Compensation of the backlash between the motor and the load of an axis.
+//!
+//! When the commanded direction of the axis reverses, the motor has to travel across the backlash
+//! before the load starts to move. The compensation shifts the reference of the motor by the half of the backlash
+//! in the commanded direction, so the load follows its reference in both directions.
+//! The load is expected to start in the middle of the backlash, so there is no compensation before the first move.
+//! The shift is ramped with the compensation velocity, so the motor never jumps.
+//!
+//! The encoder measures the position of the motor, the position of the load is the measured position
+//! without the compensation.
+use crate::models::Position;
+use crate::planner::TrajectoryPoint;
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+#[derive(Copy, Clone)]
+pub struct BacklashSettings {
+    distance: f32,
+    velocity: f32,
+}
+
+impl BacklashSettings {
+    /// Creates new backlash settings.
+    ///
+    /// # Arguments
+    /// * `distance` - the backlash in revolutions, zero disables the compensation
+    /// * `velocity` - the velocity the compensation is applied with in revolutions per second
+    pub fn new(distance: f32, velocity: f32) -> Self {
+        Self { distance, velocity }
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance;
+    }
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity;
+    }
+}
+
+impl Default for BacklashSettings {
+    fn default() -> Self {
+        Self::new(0.0, 1.0)
+    }
+}
+
+/// Generator of the backlash compensation of a single axis, sampled with the control period.
+pub struct BacklashCompensation {
+    period: f32,
+    /// The shift of the motor from the load in revolutions.
+    compensation: f32,
+    /// The last commanded direction, positive or negative one, zero before the first move.
+    direction: f32,
+}
+
+impl BacklashCompensation {
+    pub fn new(control_period: Microseconds) -> Self {
+        Self {
+            period: control_period.0 as f32 / 1_000_000.0,
+            compensation: 0.0,
+            direction: 0.0,
+        }
+    }
+
+    /// Removes the compensation, the motor and the load are considered to be in the same position.
+    pub fn reset(&mut self) {
+        self.compensation = 0.0;
+        self.direction = 0.0;
+    }
+
+    /// Returns the shift of the motor from the load in revolutions.
+    pub fn compensation(&self) -> f32 {
+        self.compensation
+    }
+
+    /// Returns the position of the load corresponding to the position of the `motor`.
+    pub fn load_position<const RESOLUTION: u32>(
+        &self,
+        motor: Position<RESOLUTION>,
+    ) -> Position<RESOLUTION> {
+        let mut load = motor;
+        load -= (self.compensation * RESOLUTION as f32).round() as i32;
+        load
+    }
+
+    /// Advances the compensation by a single period and returns the reference of the motor
+    /// corresponding to the `reference` of the load.
+    pub fn compensate(
+        &mut self,
+        reference: TrajectoryPoint,
+        settings: &BacklashSettings,
+    ) -> TrajectoryPoint {
+        if reference.velocity > 0.0 {
+            self.direction = 1.0;
+        } else if reference.velocity < 0.0 {
+            self.direction = -1.0;
+        }
+
+        let target = self.direction * settings.distance / 2.0;
+        let step = settings.velocity.abs() * self.period;
+        let change = (target - self.compensation).max(-step).min(step);
+        self.compensation += change;
+
+        TrajectoryPoint {
+            position: reference.position + self.compensation,
+            velocity: reference.velocity + change / self.period,
+            acceleration: reference.acceleration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::mock::{MockEncoder, ENCODER_RESOLUTION};
+    use crate::encoder::{Direction, Encoder};
+
+    const PERIOD: Microseconds = Microseconds(10_000);
+
+    fn reference(position: f32, velocity: f32) -> TrajectoryPoint {
+        TrajectoryPoint {
+            position,
+            velocity,
+            acceleration: 0.0,
+        }
+    }
+
+    #[test]
+    fn reversal() {
+        let settings = BacklashSettings::new(1.0, 10.0);
+        let mut backlash = BacklashCompensation::new(PERIOD);
+        // no compensation while standing still
+        assert_eq!(
+            backlash.compensate(reference(0.0, 0.0), &settings).position,
+            0.0
+        );
+
+        // the compensation is ramped with the compensation velocity
+        let motor = backlash.compensate(reference(0.0, 1.0), &settings);
+        assert!((motor.position - 0.1).abs() < 1e-5);
+        assert!((motor.velocity - 11.0).abs() < 1e-3);
+        for _ in 0..10 {
+            backlash.compensate(reference(1.0, 1.0), &settings);
+        }
+        assert!((backlash.compensation() - 0.5).abs() < 1e-5);
+
+        // the compensation stays when the axis stops
+        backlash.compensate(reference(1.0, 0.0), &settings);
+        assert!((backlash.compensation() - 0.5).abs() < 1e-5);
+
+        // the reversal moves the motor across the whole backlash
+        for _ in 0..10 {
+            backlash.compensate(reference(1.0, -1.0), &settings);
+        }
+        assert!((backlash.compensation() + 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn load_position() {
+        let settings = BacklashSettings::new(1.0, 100.0);
+        let mut backlash = BacklashCompensation::new(PERIOD);
+        let mut encoder = MockEncoder::new();
+        encoder.notify_direction_changed(Direction::Clockwise);
+        for _ in 0..6 {
+            encoder.sample();
+        }
+        backlash.compensate(reference(1.0, 1.0), &settings);
+
+        // the motor is ahead of the load by the half of the backlash
+        let load = backlash.load_position(encoder.get_position());
+        assert_eq!(load.get_increments(), 6 - ENCODER_RESOLUTION as i32 / 2);
+    }
+}
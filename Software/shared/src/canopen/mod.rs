@@ -1,21 +1,25 @@
 //! This module contains the definition of objects that are used for CANOpen communication.
 //! There are the PDO definitions and the object dictionary.
 //! Some of the PDOs are abstracted into the PositionPDO or VelocityPDO to keep the code DRY.
+//! The UnitsPDO replaces the velocity and position PDOs when the user units are selected.
 
 mod object_dictionary;
 mod persistent_dictionary;
 mod position_pdo;
 mod rx_pdo1;
 mod tx_pdo1;
+mod units_pdo;
 mod velocity_pdo;
 
 pub use pdos::{RxPDO1, RxPDO2, RxPDO3, RxPDO4, TxPDO1, TxPDO2, TxPDO3, TxPDO4};
 
 use core::convert::TryFrom;
 pub use object_dictionary::{
-    AxisDictionary, AxisKey, Key, ObjectDictionary, ObjectDictionaryKey, ObjectDictionaryStorage,
+    is_supported_microstepping, AxisDictionary, AxisKey, CurrentSettings, Key, ObjectDictionary,
+    ObjectDictionaryKey, ObjectDictionaryStorage, DEFAULT_MICROSTEPS, MAX_MICROSTEPS,
 };
 pub use persistent_dictionary::{PersistentStoreAxisDictionary, PersistentStoreObjectDictionary};
+pub use units_pdo::UnitsPDO;
 
 mod pdos {
     use crate::canopen::position_pdo::PositionPDO;
@@ -1,5 +1,21 @@
-use crate::models::{Axis, AxisMode, Position, Velocity};
+use crate::autotune::{AutoTuneSettings, TuningResult};
+use crate::backlash::BacklashSettings;
+use crate::current::CurrentPolicySettings;
+use crate::following_error::FollowingErrorSettings;
+use crate::gearing::GearingSettings;
+use crate::homing::HomingSettings;
+use crate::limits::PositionLimits;
+use crate::models::{
+    Axis, AxisMode, ErrorCode, FaultReaction, GearingState, HomingMethod, HomingState,
+    LimitViolation, PdoUnits, Position, PvtState, RampProfile, StepLossReaction, TuningLoop,
+    TuningRule, TuningState, Velocity, VelocityFilter,
+};
 use crate::psd::ControllerSettings;
+use crate::pvt::{PvtPoint, PvtQueue, PVT_QUEUE_CAPACITY};
+use crate::stall::StallSettings;
+use crate::step_loss::StepLossSettings;
+use crate::units::UnitScaling;
+use crate::velocity_estimator::VelocityEstimatorSettings;
 use core::convert::TryFrom;
 
 /// Trait for Object Dictionary abstraction
@@ -12,6 +28,21 @@ pub trait ObjectDictionary<const RESOLUTION: u32> {
     fn set_battery_voltage(&mut self, battery_voltage: f32);
     /// Sets the temperature value in the Object Dictionary.
     fn set_temperature(&mut self, temperature: f32);
+    /// Returns the temperature above which the axes are faulted.
+    fn max_temperature(&self) -> f32;
+    fn set_max_temperature(&mut self, temperature: f32);
+    /// Returns the battery voltage below which the axes are faulted.
+    fn min_battery_voltage(&self) -> f32;
+    fn set_min_battery_voltage(&mut self, battery_voltage: f32);
+    /// Returns the units of the velocity and position PDOs.
+    fn pdo_units(&self) -> PdoUnits;
+    fn set_pdo_units(&mut self, units: PdoUnits);
+
+    /// Returns true when both axes in position mode shall be moved together along a straight line.
+    fn coordinated_motion_enabled(&self) -> bool;
+    /// Enables or disables the coordinated motion of both axes.
+    fn set_coordinated_motion_enabled(&mut self, enabled: bool);
+
     /// Returns the configuration of a specific axis.
     fn axis(&self, axis: Axis) -> &dyn AxisDictionary<RESOLUTION>;
     /// Returns a mutable reference the configuration of a specific axis.
@@ -45,15 +76,143 @@ pub trait AxisDictionary<const RESOLUTION: u32> {
     fn set_velocity_controller_s(&mut self, value: f32);
     fn set_velocity_controller_d(&mut self, value: f32);
     fn set_velocity_controller_max_output(&mut self, value: f32);
+    fn set_velocity_controller_integral_limit(&mut self, value: f32);
+    fn set_velocity_controller_derivative_filter(&mut self, value: f32);
 
     fn set_position_controller_p(&mut self, value: f32);
     fn set_position_controller_s(&mut self, value: f32);
     fn set_position_controller_d(&mut self, value: f32);
     fn set_position_controller_max_output(&mut self, value: f32);
+    fn set_position_controller_integral_limit(&mut self, value: f32);
+    fn set_position_controller_derivative_filter(&mut self, value: f32);
 
     fn set_velocity_feedback_control_enabled(&mut self, velocity_feedback_control_enabled: bool);
     fn acceleration(&self) -> f32;
     fn set_acceleration(&mut self, acceleration: f32);
+    fn ramp_profile(&self) -> RampProfile;
+    fn set_ramp_profile(&mut self, ramp_profile: RampProfile);
+    fn jerk(&self) -> f32;
+    fn set_jerk(&mut self, jerk: f32);
+    fn max_velocity(&self) -> f32;
+    fn set_max_velocity(&mut self, max_velocity: f32);
+    fn deceleration(&self) -> f32;
+    fn set_deceleration(&mut self, deceleration: f32);
+    fn velocity_feedforward(&self) -> f32;
+    fn set_velocity_feedforward(&mut self, velocity_feedforward: f32);
+    fn acceleration_feedforward(&self) -> f32;
+    fn set_acceleration_feedforward(&mut self, acceleration_feedforward: f32);
+    fn homing_settings(&self) -> HomingSettings;
+    fn set_homing_method(&mut self, method: HomingMethod);
+    fn set_homing_search_velocity(&mut self, value: f32);
+    fn set_homing_latch_velocity(&mut self, value: f32);
+    fn set_homing_backoff(&mut self, value: f32);
+    fn set_homing_offset(&mut self, value: f32);
+    fn homing_state(&self) -> HomingState;
+    fn set_homing_state(&mut self, homing_state: HomingState);
+    fn position_limits(&self) -> PositionLimits;
+    fn set_position_limits_enabled(&mut self, enabled: bool);
+    fn set_min_position(&mut self, value: f32);
+    fn set_max_position(&mut self, value: f32);
+    fn set_limit_tolerance(&mut self, value: f32);
+    fn limit_violation(&self) -> LimitViolation;
+    fn set_limit_violation(&mut self, limit_violation: LimitViolation);
+    /// Returns the point that is being prepared to be pushed to the PVT queue.
+    fn pvt_point(&self) -> PvtPoint;
+    fn set_pvt_position(&mut self, value: f32);
+    fn set_pvt_velocity(&mut self, value: f32);
+    fn set_pvt_time(&mut self, value: f32);
+    fn pvt_queue(&self) -> &PvtQueue<PVT_QUEUE_CAPACITY>;
+    fn pvt_queue_mut(&mut self) -> &mut PvtQueue<PVT_QUEUE_CAPACITY>;
+    fn pvt_state(&self) -> PvtState;
+    fn set_pvt_state(&mut self, pvt_state: PvtState);
+    fn gearing_settings(&self) -> GearingSettings;
+    fn set_gear_numerator(&mut self, value: i32);
+    fn set_gear_denominator(&mut self, value: i32);
+    fn set_gear_offset(&mut self, value: f32);
+    fn gearing_state(&self) -> GearingState;
+    fn set_gearing_state(&mut self, gearing_state: GearingState);
+    fn current_policy(&self) -> CurrentPolicySettings;
+    fn set_idle_current(&mut self, value: f32);
+    fn set_idle_delay(&mut self, value: f32);
+    fn set_full_current_velocity(&mut self, value: f32);
+    fn set_current_load_boost(&mut self, value: f32);
+    fn set_current_slew_rate(&mut self, value: f32);
+    fn following_error_settings(&self) -> FollowingErrorSettings;
+    fn set_position_error_window(&mut self, value: f32);
+    fn set_position_error_time(&mut self, value: f32);
+    fn set_velocity_error_window(&mut self, value: f32);
+    fn set_velocity_error_time(&mut self, value: f32);
+    fn set_following_error_reaction(&mut self, reaction: FaultReaction);
+    /// Returns true when the following error has tripped, the error stays latched until it is cleared.
+    fn following_error(&self) -> bool;
+    fn set_following_error(&mut self, following_error: bool);
+    /// Returns the code of the latched fault, [ErrorCode::None] when the axis is not faulted.
+    fn fault(&self) -> ErrorCode;
+    /// Latches the fault unless another fault is latched already.
+    fn raise_fault(&mut self, code: ErrorCode);
+    /// Clears the latched fault and the following error.
+    fn reset_fault(&mut self);
+    fn backlash_settings(&self) -> BacklashSettings;
+    fn set_backlash(&mut self, value: f32);
+    fn set_backlash_velocity(&mut self, value: f32);
+    fn unit_scaling(&self) -> UnitScaling;
+    fn set_units_per_revolution(&mut self, value: f32);
+    fn set_scaling_numerator(&mut self, value: i32);
+    fn set_scaling_denominator(&mut self, value: i32);
+    fn set_polarity_inverted(&mut self, inverted: bool);
+    fn velocity_estimator_settings(&self) -> VelocityEstimatorSettings;
+    fn set_velocity_filter(&mut self, filter: VelocityFilter);
+    fn set_velocity_filter_window(&mut self, window: u8);
+    fn set_velocity_filter_cutoff(&mut self, cutoff: f32);
+    fn step_loss_settings(&self) -> StepLossSettings;
+    fn set_step_loss_threshold(&mut self, value: f32);
+    fn set_encoder_ratio(&mut self, value: f32);
+    fn set_step_loss_reaction(&mut self, reaction: StepLossReaction);
+    /// Returns the deviation of the motor from the commanded position in revolutions.
+    fn step_loss_deviation(&self) -> f32;
+    fn set_step_loss_deviation(&mut self, deviation: f32);
+    /// Returns the number of microsteps per a full step the driver of the axis is configured with.
+    fn microsteps(&self) -> u16;
+    /// Sets the microstepping, it shall be checked by the [AxisDictionary::supports_microsteps()].
+    fn set_microsteps(&mut self, microsteps: u16);
+    /// Returns true when the axis can be configured with the `microsteps` per a full step,
+    /// i.e. the microstepping is supported by both the [`is_supported_microstepping`] and the driver of the axis.
+    fn supports_microsteps(&self, microsteps: u16) -> bool;
+    /// Restricts the microstepping to the `microsteps` the driver of the axis can be switched to.
+    /// An unsupported configured microstepping is replaced by a supported one, which is not stored.
+    fn restrict_microsteps(&mut self, microsteps: &'static [u16]);
+    fn auto_tune_settings(&self) -> AutoTuneSettings;
+    fn set_auto_tune_loop(&mut self, tuning_loop: TuningLoop);
+    fn set_auto_tune_rule(&mut self, rule: TuningRule);
+    fn set_auto_tune_amplitude(&mut self, value: f32);
+    fn set_auto_tune_hysteresis(&mut self, value: f32);
+    fn auto_tune_state(&self) -> TuningState;
+    fn set_auto_tune_state(&mut self, state: TuningState);
+    /// Returns the result of the last finished auto-tuning with the proposed gains.
+    fn auto_tune_result(&self) -> TuningResult;
+    fn set_auto_tune_result(&mut self, result: TuningResult);
+    /// Copies the proposed gains to the settings of the tuned controller.
+    /// Returns false when there are no gains to be committed, because the auto-tuning has not finished.
+    fn commit_auto_tune_result(&mut self) -> bool;
+    fn stall_settings(&self) -> StallSettings;
+    fn set_stall_threshold(&mut self, threshold: u16);
+    fn set_stall_min_velocity(&mut self, value: f32);
+    /// Returns the last StallGuard result read from the driver of the axis.
+    fn stall_guard_result(&self) -> u16;
+    fn set_stall_guard_result(&mut self, result: u16);
+}
+
+/// The microstepping the axes are configured with when none or an unsupported one is stored.
+pub const DEFAULT_MICROSTEPS: u16 = 16;
+
+/// The finest microstepping of the axes. The resolution of the positions shall represent a single microstep of it,
+/// so the positions are exact for every microstepping the axes can be configured with.
+pub const MAX_MICROSTEPS: u16 = 256;
+
+/// Returns true when the axes can be configured with the `microsteps` per a full step,
+/// a power of two up to the [`MAX_MICROSTEPS`].
+pub fn is_supported_microstepping(microsteps: u16) -> bool {
+    microsteps.is_power_of_two() && microsteps <= MAX_MICROSTEPS
 }
 
 pub trait ObjectDictionaryKey {
@@ -83,6 +242,94 @@ pub enum AxisKey {
     PositionS,
     PositionD,
     PositionMaxAction,
+    RampProfile,
+    Jerk,
+    MaxVelocity,
+    Deceleration,
+    VelocityFeedforward,
+    AccelerationFeedforward,
+    VelocityIntegralLimit,
+    VelocityDerivativeFilter,
+    PositionIntegralLimit,
+    PositionDerivativeFilter,
+    HomingMethod,
+    HomingSearchVelocity,
+    HomingLatchVelocity,
+    HomingBackoff,
+    HomingOffset,
+    HomingState,
+    PositionLimitsEnabled,
+    MinPosition,
+    MaxPosition,
+    LimitViolation,
+    PvtPosition,
+    PvtVelocity,
+    /// Writing the duration of the segment pushes the prepared PVT point to the queue.
+    PvtTime,
+    /// The number of points in the PVT queue, writing any value clears the queue.
+    PvtQueueLength,
+    PvtState,
+    GearNumerator,
+    GearDenominator,
+    GearOffset,
+    GearingState,
+    IdleCurrent,
+    IdleDelay,
+    FullCurrentVelocity,
+    CurrentLoadBoost,
+    CurrentSlewRate,
+    PositionErrorWindow,
+    PositionErrorTime,
+    VelocityErrorWindow,
+    VelocityErrorTime,
+    FollowingErrorReaction,
+    /// The latched following error, writing zero clears it.
+    FollowingError,
+    /// The code of the latched fault.
+    ErrorCode,
+    /// Writing any value resets the latched fault.
+    FaultReset,
+    Backlash,
+    BacklashVelocity,
+    UnitsPerRevolution,
+    ScalingNumerator,
+    ScalingDenominator,
+    PolarityInverted,
+    /// The target position in user units.
+    TargetPositionUnits,
+    /// The actual position in user units.
+    ActualPositionUnits,
+    /// The target velocity in user units per second.
+    TargetVelocityUnits,
+    /// The actual velocity in user units per second.
+    ActualVelocityUnits,
+    VelocityFilter,
+    VelocityFilterWindow,
+    VelocityFilterCutoff,
+    StepLossThreshold,
+    EncoderRatio,
+    StepLossReaction,
+    StepLossDeviation,
+    /// The microstepping of the driver, the writes of microsteppings the driver does not support are rejected,
+    /// see [AxisDictionary::supports_microsteps()].
+    Microsteps,
+    AutoTuneLoop,
+    AutoTuneRule,
+    AutoTuneAmplitude,
+    AutoTuneHysteresis,
+    AutoTuneState,
+    UltimateGain,
+    UltimatePeriod,
+    ProposedP,
+    ProposedS,
+    ProposedD,
+    /// Writing any value commits the proposed gains to the tuned controller.
+    AutoTuneCommit,
+    StallThreshold,
+    StallMinVelocity,
+    StallGuardResult,
+    /// The distance the axis may get beyond the position limits before the limit hit fault is raised.
+    LimitTolerance,
 }
 
 impl ObjectDictionaryKey for AxisKey {
@@ -109,6 +356,81 @@ impl ObjectDictionaryKey for AxisKey {
             AxisKey::PositionS => 0x13,
             AxisKey::PositionD => 0x14,
             AxisKey::PositionMaxAction => 0x15,
+            AxisKey::RampProfile => 0x16,
+            AxisKey::Jerk => 0x17,
+            AxisKey::MaxVelocity => 0x18,
+            AxisKey::Deceleration => 0x19,
+            AxisKey::VelocityFeedforward => 0x1a,
+            AxisKey::AccelerationFeedforward => 0x1b,
+            AxisKey::VelocityIntegralLimit => 0x1c,
+            AxisKey::VelocityDerivativeFilter => 0x1d,
+            AxisKey::PositionIntegralLimit => 0x1e,
+            AxisKey::PositionDerivativeFilter => 0x1f,
+            AxisKey::HomingMethod => 0x20,
+            AxisKey::HomingSearchVelocity => 0x21,
+            AxisKey::HomingLatchVelocity => 0x22,
+            AxisKey::HomingBackoff => 0x23,
+            AxisKey::HomingOffset => 0x24,
+            AxisKey::HomingState => 0x25,
+            AxisKey::PositionLimitsEnabled => 0x26,
+            AxisKey::MinPosition => 0x27,
+            AxisKey::MaxPosition => 0x28,
+            AxisKey::LimitViolation => 0x29,
+            AxisKey::PvtPosition => 0x2a,
+            AxisKey::PvtVelocity => 0x2b,
+            AxisKey::PvtTime => 0x2c,
+            AxisKey::PvtQueueLength => 0x2d,
+            AxisKey::PvtState => 0x2e,
+            AxisKey::GearNumerator => 0x2f,
+            AxisKey::GearDenominator => 0x30,
+            AxisKey::GearOffset => 0x31,
+            AxisKey::GearingState => 0x32,
+            AxisKey::IdleCurrent => 0x33,
+            AxisKey::IdleDelay => 0x34,
+            AxisKey::FullCurrentVelocity => 0x35,
+            AxisKey::CurrentLoadBoost => 0x36,
+            AxisKey::CurrentSlewRate => 0x37,
+            AxisKey::PositionErrorWindow => 0x38,
+            AxisKey::PositionErrorTime => 0x39,
+            AxisKey::VelocityErrorWindow => 0x3a,
+            AxisKey::VelocityErrorTime => 0x3b,
+            AxisKey::FollowingErrorReaction => 0x3c,
+            AxisKey::FollowingError => 0x3d,
+            AxisKey::ErrorCode => 0x3e,
+            AxisKey::FaultReset => 0x3f,
+            AxisKey::Backlash => 0x40,
+            AxisKey::BacklashVelocity => 0x41,
+            AxisKey::UnitsPerRevolution => 0x42,
+            AxisKey::ScalingNumerator => 0x43,
+            AxisKey::ScalingDenominator => 0x44,
+            AxisKey::PolarityInverted => 0x45,
+            AxisKey::TargetPositionUnits => 0x46,
+            AxisKey::ActualPositionUnits => 0x47,
+            AxisKey::TargetVelocityUnits => 0x48,
+            AxisKey::ActualVelocityUnits => 0x49,
+            AxisKey::VelocityFilter => 0x4a,
+            AxisKey::VelocityFilterWindow => 0x4b,
+            AxisKey::VelocityFilterCutoff => 0x4c,
+            AxisKey::StepLossThreshold => 0x4d,
+            AxisKey::EncoderRatio => 0x4e,
+            AxisKey::StepLossReaction => 0x4f,
+            AxisKey::StepLossDeviation => 0x50,
+            AxisKey::Microsteps => 0x51,
+            AxisKey::AutoTuneLoop => 0x52,
+            AxisKey::AutoTuneRule => 0x53,
+            AxisKey::AutoTuneAmplitude => 0x54,
+            AxisKey::AutoTuneHysteresis => 0x55,
+            AxisKey::AutoTuneState => 0x56,
+            AxisKey::UltimateGain => 0x57,
+            AxisKey::UltimatePeriod => 0x58,
+            AxisKey::ProposedP => 0x59,
+            AxisKey::ProposedS => 0x5a,
+            AxisKey::ProposedD => 0x5b,
+            AxisKey::AutoTuneCommit => 0x5c,
+            AxisKey::StallThreshold => 0x5d,
+            AxisKey::StallMinVelocity => 0x5e,
+            AxisKey::StallGuardResult => 0x5f,
+            AxisKey::LimitTolerance => 0x60,
         }
     }
 }
@@ -139,6 +461,81 @@ impl TryFrom<u8> for AxisKey {
             0x13 => Ok(AxisKey::PositionS),
             0x14 => Ok(AxisKey::PositionD),
             0x15 => Ok(AxisKey::PositionMaxAction),
+            0x16 => Ok(AxisKey::RampProfile),
+            0x17 => Ok(AxisKey::Jerk),
+            0x18 => Ok(AxisKey::MaxVelocity),
+            0x19 => Ok(AxisKey::Deceleration),
+            0x1a => Ok(AxisKey::VelocityFeedforward),
+            0x1b => Ok(AxisKey::AccelerationFeedforward),
+            0x1c => Ok(AxisKey::VelocityIntegralLimit),
+            0x1d => Ok(AxisKey::VelocityDerivativeFilter),
+            0x1e => Ok(AxisKey::PositionIntegralLimit),
+            0x1f => Ok(AxisKey::PositionDerivativeFilter),
+            0x20 => Ok(AxisKey::HomingMethod),
+            0x21 => Ok(AxisKey::HomingSearchVelocity),
+            0x22 => Ok(AxisKey::HomingLatchVelocity),
+            0x23 => Ok(AxisKey::HomingBackoff),
+            0x24 => Ok(AxisKey::HomingOffset),
+            0x25 => Ok(AxisKey::HomingState),
+            0x26 => Ok(AxisKey::PositionLimitsEnabled),
+            0x27 => Ok(AxisKey::MinPosition),
+            0x28 => Ok(AxisKey::MaxPosition),
+            0x29 => Ok(AxisKey::LimitViolation),
+            0x2a => Ok(AxisKey::PvtPosition),
+            0x2b => Ok(AxisKey::PvtVelocity),
+            0x2c => Ok(AxisKey::PvtTime),
+            0x2d => Ok(AxisKey::PvtQueueLength),
+            0x2e => Ok(AxisKey::PvtState),
+            0x2f => Ok(AxisKey::GearNumerator),
+            0x30 => Ok(AxisKey::GearDenominator),
+            0x31 => Ok(AxisKey::GearOffset),
+            0x32 => Ok(AxisKey::GearingState),
+            0x33 => Ok(AxisKey::IdleCurrent),
+            0x34 => Ok(AxisKey::IdleDelay),
+            0x35 => Ok(AxisKey::FullCurrentVelocity),
+            0x36 => Ok(AxisKey::CurrentLoadBoost),
+            0x37 => Ok(AxisKey::CurrentSlewRate),
+            0x38 => Ok(AxisKey::PositionErrorWindow),
+            0x39 => Ok(AxisKey::PositionErrorTime),
+            0x3a => Ok(AxisKey::VelocityErrorWindow),
+            0x3b => Ok(AxisKey::VelocityErrorTime),
+            0x3c => Ok(AxisKey::FollowingErrorReaction),
+            0x3d => Ok(AxisKey::FollowingError),
+            0x3e => Ok(AxisKey::ErrorCode),
+            0x3f => Ok(AxisKey::FaultReset),
+            0x40 => Ok(AxisKey::Backlash),
+            0x41 => Ok(AxisKey::BacklashVelocity),
+            0x42 => Ok(AxisKey::UnitsPerRevolution),
+            0x43 => Ok(AxisKey::ScalingNumerator),
+            0x44 => Ok(AxisKey::ScalingDenominator),
+            0x45 => Ok(AxisKey::PolarityInverted),
+            0x46 => Ok(AxisKey::TargetPositionUnits),
+            0x47 => Ok(AxisKey::ActualPositionUnits),
+            0x48 => Ok(AxisKey::TargetVelocityUnits),
+            0x49 => Ok(AxisKey::ActualVelocityUnits),
+            0x4a => Ok(AxisKey::VelocityFilter),
+            0x4b => Ok(AxisKey::VelocityFilterWindow),
+            0x4c => Ok(AxisKey::VelocityFilterCutoff),
+            0x4d => Ok(AxisKey::StepLossThreshold),
+            0x4e => Ok(AxisKey::EncoderRatio),
+            0x4f => Ok(AxisKey::StepLossReaction),
+            0x50 => Ok(AxisKey::StepLossDeviation),
+            0x51 => Ok(AxisKey::Microsteps),
+            0x52 => Ok(AxisKey::AutoTuneLoop),
+            0x53 => Ok(AxisKey::AutoTuneRule),
+            0x54 => Ok(AxisKey::AutoTuneAmplitude),
+            0x55 => Ok(AxisKey::AutoTuneHysteresis),
+            0x56 => Ok(AxisKey::AutoTuneState),
+            0x57 => Ok(AxisKey::UltimateGain),
+            0x58 => Ok(AxisKey::UltimatePeriod),
+            0x59 => Ok(AxisKey::ProposedP),
+            0x5a => Ok(AxisKey::ProposedS),
+            0x5b => Ok(AxisKey::ProposedD),
+            0x5c => Ok(AxisKey::AutoTuneCommit),
+            0x5d => Ok(AxisKey::StallThreshold),
+            0x5e => Ok(AxisKey::StallMinVelocity),
+            0x5f => Ok(AxisKey::StallGuardResult),
+            0x60 => Ok(AxisKey::LimitTolerance),
             _ => Err(()),
         }
     }
@@ -148,6 +545,10 @@ impl TryFrom<u8> for AxisKey {
 pub enum Key {
     BatteryVoltage,
     Temperature,
+    CoordinatedMotion,
+    MaxTemperature,
+    MinBatteryVoltage,
+    PdoUnits,
     Axis1(AxisKey),
     Axis2(AxisKey),
 }
@@ -159,6 +560,10 @@ impl Key {
             0x2000 => match subindex {
                 0x01 => Some(Key::BatteryVoltage),
                 0x02 => Some(Key::Temperature),
+                0x03 => Some(Key::CoordinatedMotion),
+                0x04 => Some(Key::MaxTemperature),
+                0x05 => Some(Key::MinBatteryVoltage),
+                0x06 => Some(Key::PdoUnits),
                 _ => None,
             },
             0x2100 => AxisKey::try_from(subindex).map_or(None, |k| Some(Key::Axis1(k))),
@@ -171,6 +576,10 @@ impl Key {
         match self {
             Key::BatteryVoltage => 0x2000,
             Key::Temperature => 0x2000,
+            Key::CoordinatedMotion => 0x2000,
+            Key::MaxTemperature => 0x2000,
+            Key::MinBatteryVoltage => 0x2000,
+            Key::PdoUnits => 0x2000,
             Key::Axis1(_) => 0x2100,
             Key::Axis2(_) => 0x2200,
         }
@@ -189,6 +598,10 @@ impl ObjectDictionaryKey for Key {
         match self {
             Key::BatteryVoltage => 0x0001,
             Key::Temperature => 0x0002,
+            Key::CoordinatedMotion => 0x0003,
+            Key::MaxTemperature => 0x0004,
+            Key::MinBatteryVoltage => 0x0005,
+            Key::PdoUnits => 0x0006,
             Key::Axis1(key) => self.offset() + key.raw(),
             Key::Axis2(key) => self.offset() + key.raw(),
         }
@@ -198,8 +611,12 @@ impl ObjectDictionaryKey for Key {
 pub trait ObjectDictionaryStorage {
     fn save_f32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: f32);
     fn save_bool<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: bool);
+    fn save_u8<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: u8);
+    fn save_i32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: i32);
     fn load_f32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<f32>;
     fn load_bool<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<bool>;
+    fn load_u8<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<u8>;
+    fn load_i32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<i32>;
 }
 
 #[derive(Copy, Clone)]
@@ -1,10 +1,70 @@
-use crate::canopen::object_dictionary::{AxisKey, CurrentSettings, Key, ObjectDictionary};
-use crate::canopen::ObjectDictionaryStorage;
+use crate::backlash::BacklashSettings;
+use crate::canopen::object_dictionary::{
+    is_supported_microstepping, AxisKey, CurrentSettings, Key, ObjectDictionary, DEFAULT_MICROSTEPS,
+};
+use crate::canopen::{ObjectDictionaryKey, ObjectDictionaryStorage};
+use crate::current::CurrentPolicySettings;
+use crate::fault::FaultLatch;
+use crate::following_error::FollowingErrorSettings;
+use crate::gearing::GearingSettings;
+use crate::homing::HomingSettings;
+use crate::limits::PositionLimits;
 use crate::prelude::*;
 use crate::psd::ControllerSettings;
+use crate::stall::StallSettings;
+use crate::step_loss::StepLossSettings;
+use crate::units::UnitScaling;
+use crate::velocity_estimator::VelocityEstimatorSettings;
 use core::cell::RefCell;
+use core::convert::TryFrom;
 use spin::Mutex;
 
+/// The format of the stored values, it is increased whenever the meaning of a stored value changes.
+/// Since the format 1, the integral and derivative gains of the controllers are related to the control period.
+const STORAGE_FORMAT: u8 = 1;
+
+/// The ratio of the 1 ms period the gains were related to before the format 1 and the 10 ms control period,
+/// with which the controllers were actually sampled.
+const UNVERSIONED_PERIOD_RATIO: f32 = 0.1;
+
+/// The keys of the values stored outside of the object dictionary.
+enum StorageKey {
+    Format,
+}
+
+impl ObjectDictionaryKey for StorageKey {
+    fn raw(&self) -> u16 {
+        match self {
+            StorageKey::Format => 0x0000,
+        }
+    }
+}
+
+/// Converts the values stored in an older format, so the axes behave the same as before.
+fn migrate<STORAGE: ObjectDictionaryStorage>(storage: &mut STORAGE) {
+    let format = storage.load_u8(StorageKey::Format);
+    if format == Some(STORAGE_FORMAT) {
+        return;
+    }
+    if format.is_none() {
+        for axis in &[Axis::Axis1, Axis::Axis2] {
+            for key in &[AxisKey::VelocityS, AxisKey::PositionS] {
+                let key = Key::key_for_axis(*key, *axis);
+                if let Some(gain) = storage.load_f32(key) {
+                    storage.save_f32(key, gain * UNVERSIONED_PERIOD_RATIO);
+                }
+            }
+            for key in &[AxisKey::VelocityD, AxisKey::PositionD] {
+                let key = Key::key_for_axis(*key, *axis);
+                if let Some(gain) = storage.load_f32(key) {
+                    storage.save_f32(key, gain / UNVERSIONED_PERIOD_RATIO);
+                }
+            }
+        }
+    }
+    storage.save_u8(StorageKey::Format, STORAGE_FORMAT);
+}
+
 /// The object dictionary struct represents the global state of the driver
 #[derive(Copy, Clone)]
 pub struct PersistentStoreObjectDictionary<
@@ -13,19 +73,48 @@ pub struct PersistentStoreObjectDictionary<
 > {
     battery_voltage: f32,
     temperature: f32,
+    coordinated_motion_enabled: bool,
+    max_temperature: f32,
+    min_battery_voltage: f32,
+    pdo_units: PdoUnits,
     axis1: PersistentStoreAxisDictionary<STORAGE, RESOLUTION>,
     axis2: PersistentStoreAxisDictionary<STORAGE, RESOLUTION>,
+    storage: &'static Mutex<RefCell<STORAGE>>,
 }
 
 impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
     PersistentStoreObjectDictionary<STORAGE, RESOLUTION>
 {
     pub fn new(storage: &'static Mutex<RefCell<STORAGE>>) -> Self {
+        migrate(&mut *storage.lock().borrow_mut());
+
+        let pdo_units = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::PdoUnits)
+            .and_then(|raw| PdoUnits::try_from(raw).ok())
+            .unwrap_or_default();
+        let max_temperature = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::MaxTemperature)
+            .unwrap_or(85.0);
+        let min_battery_voltage = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::MinBatteryVoltage)
+            .unwrap_or(6.0);
+
         Self {
             battery_voltage: 0.0,
             temperature: 0.0,
+            coordinated_motion_enabled: false,
+            max_temperature,
+            min_battery_voltage,
+            pdo_units,
             axis1: PersistentStoreAxisDictionary::new(Axis::Axis1, storage),
             axis2: PersistentStoreAxisDictionary::new(Axis::Axis2, storage),
+            storage,
         }
     }
 }
@@ -49,6 +138,50 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32> ObjectDi
         self.temperature = temperature;
     }
 
+    fn max_temperature(&self) -> f32 {
+        self.max_temperature
+    }
+
+    fn set_max_temperature(&mut self, temperature: f32) {
+        self.max_temperature = temperature;
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::MaxTemperature, temperature);
+    }
+
+    fn min_battery_voltage(&self) -> f32 {
+        self.min_battery_voltage
+    }
+
+    fn set_min_battery_voltage(&mut self, battery_voltage: f32) {
+        self.min_battery_voltage = battery_voltage;
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::MinBatteryVoltage, battery_voltage);
+    }
+
+    fn pdo_units(&self) -> PdoUnits {
+        self.pdo_units
+    }
+
+    fn set_pdo_units(&mut self, units: PdoUnits) {
+        self.pdo_units = units;
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_u8(Key::PdoUnits, units.into());
+    }
+
+    fn coordinated_motion_enabled(&self) -> bool {
+        self.coordinated_motion_enabled
+    }
+
+    fn set_coordinated_motion_enabled(&mut self, enabled: bool) {
+        self.coordinated_motion_enabled = enabled;
+    }
+
     fn axis(&self, axis: Axis) -> &dyn AxisDictionary<RESOLUTION> {
         match axis {
             Axis::Axis1 => &self.axis1,
@@ -81,6 +214,38 @@ pub struct PersistentStoreAxisDictionary<
     position_controller_settings: ControllerSettings,
     velocity_feedback_control_enabled: bool,
     acceleration: f32,
+    ramp_profile: RampProfile,
+    jerk: f32,
+    max_velocity: f32,
+    deceleration: f32,
+    velocity_feedforward: f32,
+    acceleration_feedforward: f32,
+    homing_settings: HomingSettings,
+    homing_state: HomingState,
+    position_limits: PositionLimits,
+    limit_violation: LimitViolation,
+    pvt_point: PvtPoint,
+    pvt_queue: PvtQueue<PVT_QUEUE_CAPACITY>,
+    pvt_state: PvtState,
+    gearing_settings: GearingSettings,
+    gearing_state: GearingState,
+    current_policy: CurrentPolicySettings,
+    following_error_settings: FollowingErrorSettings,
+    following_error: bool,
+    fault: FaultLatch,
+    backlash_settings: BacklashSettings,
+    velocity_estimator_settings: VelocityEstimatorSettings,
+    step_loss_settings: StepLossSettings,
+    step_loss_deviation: f32,
+    microsteps: u16,
+    /// The microsteps the driver can be switched to, `None` when it supports all of them.
+    supported_microsteps: Option<&'static [u16]>,
+    auto_tune_settings: AutoTuneSettings,
+    auto_tune_state: TuningState,
+    auto_tune_result: TuningResult,
+    stall_settings: StallSettings,
+    stall_guard_result: u16,
+    unit_scaling: UnitScaling,
     storage: &'static Mutex<RefCell<STORAGE>>,
 }
 
@@ -93,6 +258,37 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
             .borrow()
             .load_f32(Key::key_for_axis(AxisKey::Acceleration, axis))
             .unwrap_or(50.0);
+        let ramp_profile = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::RampProfile, axis))
+            .map(RampProfile::from)
+            .unwrap_or_default();
+        let jerk = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::Jerk, axis))
+            .unwrap_or(500.0);
+        let max_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::MaxVelocity, axis))
+            .unwrap_or(3.0);
+        let deceleration = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::Deceleration, axis))
+            .unwrap_or(50.0);
+        let velocity_feedforward = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityFeedforward, axis))
+            .unwrap_or(1.0);
+        let acceleration_feedforward = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::AccelerationFeedforward, axis))
+            .unwrap_or(0.0);
         let velocity_feedback_control_enabled = storage
             .lock()
             .borrow()
@@ -141,7 +337,21 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
             .load_f32(Key::key_for_axis(AxisKey::VelocityMaxAction, axis))
             .unwrap_or(3.0);
 
-        let velocity_controller_settings = ControllerSettings::new(p, s, d, max);
+        let integral_limit = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityIntegralLimit, axis))
+            .unwrap_or(max);
+
+        let derivative_filter = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityDerivativeFilter, axis))
+            .unwrap_or(0.0);
+
+        let mut velocity_controller_settings = ControllerSettings::new(p, s, d, max);
+        velocity_controller_settings.set_integral_limit(integral_limit);
+        velocity_controller_settings.set_derivative_filter(derivative_filter);
 
         let p = storage
             .lock()
@@ -166,7 +376,293 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
             .load_f32(Key::key_for_axis(AxisKey::PositionMaxAction, axis))
             .unwrap_or(3.0);
 
-        let position_controller_settings = ControllerSettings::new(p, s, d, max);
+        let integral_limit = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::PositionIntegralLimit, axis))
+            .unwrap_or(max);
+
+        let derivative_filter = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::PositionDerivativeFilter, axis))
+            .unwrap_or(0.0);
+
+        let mut position_controller_settings = ControllerSettings::new(p, s, d, max);
+        position_controller_settings.set_integral_limit(integral_limit);
+        position_controller_settings.set_derivative_filter(derivative_filter);
+
+        let method = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::HomingMethod, axis))
+            .and_then(|raw| HomingMethod::try_from(raw).ok())
+            .unwrap_or_default();
+
+        let search_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::HomingSearchVelocity, axis))
+            .unwrap_or(1.0);
+
+        let latch_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::HomingLatchVelocity, axis))
+            .unwrap_or(0.1);
+
+        let backoff = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::HomingBackoff, axis))
+            .unwrap_or(0.5);
+
+        let offset = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::HomingOffset, axis))
+            .unwrap_or(0.0);
+
+        let homing_settings =
+            HomingSettings::new(method, search_velocity, latch_velocity, backoff, offset);
+
+        let limits_enabled = storage
+            .lock()
+            .borrow()
+            .load_bool(Key::key_for_axis(AxisKey::PositionLimitsEnabled, axis))
+            .unwrap_or(false);
+        let min_position = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::MinPosition, axis))
+            .unwrap_or(-100.0);
+        let max_position = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::MaxPosition, axis))
+            .unwrap_or(100.0);
+        let limit_tolerance = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::LimitTolerance, axis))
+            .unwrap_or(0.01);
+        let position_limits =
+            PositionLimits::new(limits_enabled, min_position, max_position, limit_tolerance);
+
+        let gear_numerator = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::GearNumerator, axis))
+            .unwrap_or(1);
+        let gear_denominator = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::GearDenominator, axis))
+            .unwrap_or(1);
+        let gear_offset = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::GearOffset, axis))
+            .unwrap_or(0.0);
+        let gearing_settings = GearingSettings::new(gear_numerator, gear_denominator, gear_offset);
+
+        let idle_current = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::IdleCurrent, axis))
+            .unwrap_or(0.2);
+        let idle_delay = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::IdleDelay, axis))
+            .unwrap_or(2.0);
+        let full_current_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::FullCurrentVelocity, axis))
+            .unwrap_or(0.5);
+        let load_boost = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::CurrentLoadBoost, axis))
+            .unwrap_or(0.0);
+        let slew_rate = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::CurrentSlewRate, axis))
+            .unwrap_or(5.0);
+        let current_policy = CurrentPolicySettings::new(
+            idle_current,
+            idle_delay,
+            full_current_velocity,
+            load_boost,
+            slew_rate,
+        );
+
+        let position_window = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::PositionErrorWindow, axis))
+            .unwrap_or(0.0);
+        let position_time = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::PositionErrorTime, axis))
+            .unwrap_or(0.1);
+        let velocity_window = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityErrorWindow, axis))
+            .unwrap_or(0.0);
+        let velocity_time = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityErrorTime, axis))
+            .unwrap_or(0.1);
+        let reaction = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::FollowingErrorReaction, axis))
+            .and_then(|raw| FaultReaction::try_from(raw).ok())
+            .unwrap_or_default();
+        let backlash = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::Backlash, axis))
+            .unwrap_or(0.0);
+        let backlash_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::BacklashVelocity, axis))
+            .unwrap_or(1.0);
+        let backlash_settings = BacklashSettings::new(backlash, backlash_velocity);
+
+        let units_per_revolution = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::UnitsPerRevolution, axis))
+            .unwrap_or(1.0);
+        let scaling_numerator = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::ScalingNumerator, axis))
+            .unwrap_or(1);
+        let scaling_denominator = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::ScalingDenominator, axis))
+            .unwrap_or(1);
+        let polarity_inverted = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::PolarityInverted, axis))
+            .map(|raw| raw > 0)
+            .unwrap_or(false);
+        let unit_scaling = UnitScaling::new(
+            units_per_revolution,
+            scaling_numerator,
+            scaling_denominator,
+            polarity_inverted,
+        );
+
+        let velocity_filter = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::VelocityFilter, axis))
+            .and_then(|raw| VelocityFilter::try_from(raw).ok())
+            .unwrap_or_default();
+        let velocity_filter_window = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::VelocityFilterWindow, axis))
+            .unwrap_or(4);
+        let velocity_filter_cutoff = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::VelocityFilterCutoff, axis))
+            .unwrap_or(20.0);
+        let velocity_estimator_settings = VelocityEstimatorSettings::new(
+            velocity_filter,
+            velocity_filter_window,
+            velocity_filter_cutoff,
+        );
+
+        let step_loss_threshold = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::StepLossThreshold, axis))
+            .unwrap_or(0.0);
+        let encoder_ratio = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::EncoderRatio, axis))
+            .unwrap_or(1.0);
+        let step_loss_reaction = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::StepLossReaction, axis))
+            .and_then(|raw| StepLossReaction::try_from(raw).ok())
+            .unwrap_or_default();
+        let step_loss_settings =
+            StepLossSettings::new(step_loss_threshold, encoder_ratio, step_loss_reaction);
+
+        let microsteps = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::Microsteps, axis))
+            .and_then(|raw| u16::try_from(raw).ok())
+            .filter(|microsteps| is_supported_microstepping(*microsteps))
+            .unwrap_or(DEFAULT_MICROSTEPS);
+
+        let auto_tune_loop = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::AutoTuneLoop, axis))
+            .and_then(|raw| TuningLoop::try_from(raw).ok())
+            .unwrap_or_default();
+        let auto_tune_rule = storage
+            .lock()
+            .borrow()
+            .load_u8(Key::key_for_axis(AxisKey::AutoTuneRule, axis))
+            .and_then(|raw| TuningRule::try_from(raw).ok())
+            .unwrap_or_default();
+        let auto_tune_amplitude = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::AutoTuneAmplitude, axis))
+            .unwrap_or(1.0);
+        let auto_tune_hysteresis = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::AutoTuneHysteresis, axis))
+            .unwrap_or(0.05);
+        let auto_tune_settings = AutoTuneSettings::new(
+            auto_tune_loop,
+            auto_tune_rule,
+            auto_tune_amplitude,
+            auto_tune_hysteresis,
+        );
+
+        let stall_threshold = storage
+            .lock()
+            .borrow()
+            .load_i32(Key::key_for_axis(AxisKey::StallThreshold, axis))
+            .map(|raw| raw as u16)
+            .unwrap_or(0);
+        let stall_min_velocity = storage
+            .lock()
+            .borrow()
+            .load_f32(Key::key_for_axis(AxisKey::StallMinVelocity, axis))
+            .unwrap_or(1.0);
+        let stall_settings = StallSettings::new(stall_threshold, stall_min_velocity);
+
+        let following_error_settings = FollowingErrorSettings::new(
+            position_window,
+            position_time,
+            velocity_window,
+            velocity_time,
+            reaction,
+        );
         Self {
             axis,
             mode: Default::default(),
@@ -180,6 +676,37 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
             position_controller_settings,
             velocity_feedback_control_enabled,
             acceleration,
+            ramp_profile,
+            jerk,
+            max_velocity,
+            deceleration,
+            velocity_feedforward,
+            acceleration_feedforward,
+            homing_settings,
+            homing_state: Default::default(),
+            position_limits,
+            limit_violation: Default::default(),
+            pvt_point: Default::default(),
+            pvt_queue: PvtQueue::new(),
+            pvt_state: Default::default(),
+            gearing_settings,
+            gearing_state: Default::default(),
+            current_policy,
+            following_error_settings,
+            following_error: false,
+            fault: FaultLatch::default(),
+            backlash_settings,
+            unit_scaling,
+            velocity_estimator_settings,
+            step_loss_settings,
+            step_loss_deviation: 0.0,
+            microsteps,
+            supported_microsteps: None,
+            auto_tune_settings,
+            auto_tune_state: Default::default(),
+            auto_tune_result: Default::default(),
+            stall_settings,
+            stall_guard_result: 0,
             storage,
         }
     }
@@ -292,6 +819,23 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
         );
     }
 
+    fn set_velocity_controller_integral_limit(&mut self, value: f32) {
+        self.velocity_controller_settings.set_integral_limit(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityIntegralLimit, self.axis),
+            value,
+        );
+    }
+
+    fn set_velocity_controller_derivative_filter(&mut self, value: f32) {
+        self.velocity_controller_settings
+            .set_derivative_filter(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityDerivativeFilter, self.axis),
+            value,
+        );
+    }
+
     fn set_position_controller_p(&mut self, value: f32) {
         self.position_controller_settings.set_proportional(value);
         self.storage
@@ -325,6 +869,23 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
         );
     }
 
+    fn set_position_controller_integral_limit(&mut self, value: f32) {
+        self.position_controller_settings.set_integral_limit(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::PositionIntegralLimit, self.axis),
+            value,
+        );
+    }
+
+    fn set_position_controller_derivative_filter(&mut self, value: f32) {
+        self.position_controller_settings
+            .set_derivative_filter(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::PositionDerivativeFilter, self.axis),
+            value,
+        );
+    }
+
     fn set_velocity_feedback_control_enabled(&mut self, velocity_feedback_control_enabled: bool) {
         self.velocity_feedback_control_enabled = velocity_feedback_control_enabled;
         self.storage.lock().borrow_mut().save_bool(
@@ -344,4 +905,727 @@ impl<STORAGE: 'static + ObjectDictionaryStorage, const RESOLUTION: u32>
             acceleration,
         );
     }
+
+    fn ramp_profile(&self) -> RampProfile {
+        self.ramp_profile
+    }
+
+    fn set_ramp_profile(&mut self, ramp_profile: RampProfile) {
+        self.ramp_profile = ramp_profile;
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::RampProfile, self.axis),
+            ramp_profile.into(),
+        );
+    }
+
+    fn jerk(&self) -> f32 {
+        self.jerk
+    }
+
+    fn set_jerk(&mut self, jerk: f32) {
+        self.jerk = jerk;
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::Jerk, self.axis), jerk);
+    }
+
+    fn max_velocity(&self) -> f32 {
+        self.max_velocity
+    }
+
+    fn set_max_velocity(&mut self, max_velocity: f32) {
+        self.max_velocity = max_velocity;
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::MaxVelocity, self.axis),
+            max_velocity,
+        );
+    }
+
+    fn deceleration(&self) -> f32 {
+        self.deceleration
+    }
+
+    fn set_deceleration(&mut self, deceleration: f32) {
+        self.deceleration = deceleration;
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::Deceleration, self.axis),
+            deceleration,
+        );
+    }
+
+    fn velocity_feedforward(&self) -> f32 {
+        self.velocity_feedforward
+    }
+
+    fn set_velocity_feedforward(&mut self, velocity_feedforward: f32) {
+        self.velocity_feedforward = velocity_feedforward;
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityFeedforward, self.axis),
+            velocity_feedforward,
+        );
+    }
+
+    fn acceleration_feedforward(&self) -> f32 {
+        self.acceleration_feedforward
+    }
+
+    fn set_acceleration_feedforward(&mut self, acceleration_feedforward: f32) {
+        self.acceleration_feedforward = acceleration_feedforward;
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::AccelerationFeedforward, self.axis),
+            acceleration_feedforward,
+        );
+    }
+
+    fn homing_settings(&self) -> HomingSettings {
+        self.homing_settings
+    }
+
+    fn set_homing_method(&mut self, method: HomingMethod) {
+        self.homing_settings.set_method(method);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::HomingMethod, self.axis),
+            method.into(),
+        );
+    }
+
+    fn set_homing_search_velocity(&mut self, value: f32) {
+        self.homing_settings.set_search_velocity(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::HomingSearchVelocity, self.axis),
+            value,
+        );
+    }
+
+    fn set_homing_latch_velocity(&mut self, value: f32) {
+        self.homing_settings.set_latch_velocity(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::HomingLatchVelocity, self.axis),
+            value,
+        );
+    }
+
+    fn set_homing_backoff(&mut self, value: f32) {
+        self.homing_settings.set_backoff(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::HomingBackoff, self.axis), value);
+    }
+
+    fn set_homing_offset(&mut self, value: f32) {
+        self.homing_settings.set_offset(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::HomingOffset, self.axis), value);
+    }
+
+    fn homing_state(&self) -> HomingState {
+        self.homing_state
+    }
+
+    fn set_homing_state(&mut self, homing_state: HomingState) {
+        self.homing_state = homing_state;
+    }
+
+    fn position_limits(&self) -> PositionLimits {
+        self.position_limits
+    }
+
+    fn set_position_limits_enabled(&mut self, enabled: bool) {
+        self.position_limits.set_enabled(enabled);
+        self.storage.lock().borrow_mut().save_bool(
+            Key::key_for_axis(AxisKey::PositionLimitsEnabled, self.axis),
+            enabled,
+        );
+    }
+
+    fn set_min_position(&mut self, value: f32) {
+        self.position_limits.set_min(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::MinPosition, self.axis), value);
+    }
+
+    fn set_max_position(&mut self, value: f32) {
+        self.position_limits.set_max(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::MaxPosition, self.axis), value);
+    }
+
+    fn set_limit_tolerance(&mut self, value: f32) {
+        self.position_limits.set_tolerance(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::LimitTolerance, self.axis), value);
+    }
+
+    fn limit_violation(&self) -> LimitViolation {
+        self.limit_violation
+    }
+
+    fn set_limit_violation(&mut self, limit_violation: LimitViolation) {
+        self.limit_violation = limit_violation;
+    }
+
+    fn pvt_point(&self) -> PvtPoint {
+        self.pvt_point
+    }
+
+    fn set_pvt_position(&mut self, value: f32) {
+        self.pvt_point.position = value;
+    }
+
+    fn set_pvt_velocity(&mut self, value: f32) {
+        self.pvt_point.velocity = value;
+    }
+
+    fn set_pvt_time(&mut self, value: f32) {
+        self.pvt_point.time = value;
+    }
+
+    fn pvt_queue(&self) -> &PvtQueue<PVT_QUEUE_CAPACITY> {
+        &self.pvt_queue
+    }
+
+    fn pvt_queue_mut(&mut self) -> &mut PvtQueue<PVT_QUEUE_CAPACITY> {
+        &mut self.pvt_queue
+    }
+
+    fn pvt_state(&self) -> PvtState {
+        self.pvt_state
+    }
+
+    fn set_pvt_state(&mut self, pvt_state: PvtState) {
+        self.pvt_state = pvt_state;
+    }
+
+    fn gearing_settings(&self) -> GearingSettings {
+        self.gearing_settings
+    }
+
+    fn set_gear_numerator(&mut self, value: i32) {
+        self.gearing_settings.set_numerator(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_i32(Key::key_for_axis(AxisKey::GearNumerator, self.axis), value);
+    }
+
+    fn set_gear_denominator(&mut self, value: i32) {
+        self.gearing_settings.set_denominator(value);
+        self.storage.lock().borrow_mut().save_i32(
+            Key::key_for_axis(AxisKey::GearDenominator, self.axis),
+            value,
+        );
+    }
+
+    fn set_gear_offset(&mut self, value: f32) {
+        self.gearing_settings.set_offset(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::GearOffset, self.axis), value);
+    }
+
+    fn gearing_state(&self) -> GearingState {
+        self.gearing_state
+    }
+
+    fn set_gearing_state(&mut self, gearing_state: GearingState) {
+        self.gearing_state = gearing_state;
+    }
+
+    fn current_policy(&self) -> CurrentPolicySettings {
+        self.current_policy
+    }
+
+    fn set_idle_current(&mut self, value: f32) {
+        self.current_policy.set_idle_current(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::IdleCurrent, self.axis), value);
+    }
+
+    fn set_idle_delay(&mut self, value: f32) {
+        self.current_policy.set_idle_delay(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::IdleDelay, self.axis), value);
+    }
+
+    fn set_full_current_velocity(&mut self, value: f32) {
+        self.current_policy.set_full_current_velocity(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::FullCurrentVelocity, self.axis),
+            value,
+        );
+    }
+
+    fn set_current_load_boost(&mut self, value: f32) {
+        self.current_policy.set_load_boost(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::CurrentLoadBoost, self.axis),
+            value,
+        );
+    }
+
+    fn set_current_slew_rate(&mut self, value: f32) {
+        self.current_policy.set_slew_rate(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::CurrentSlewRate, self.axis),
+            value,
+        );
+    }
+
+    fn following_error_settings(&self) -> FollowingErrorSettings {
+        self.following_error_settings
+    }
+
+    fn set_position_error_window(&mut self, value: f32) {
+        self.following_error_settings.set_position_window(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::PositionErrorWindow, self.axis),
+            value,
+        );
+    }
+
+    fn set_position_error_time(&mut self, value: f32) {
+        self.following_error_settings.set_position_time(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::PositionErrorTime, self.axis),
+            value,
+        );
+    }
+
+    fn set_velocity_error_window(&mut self, value: f32) {
+        self.following_error_settings.set_velocity_window(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityErrorWindow, self.axis),
+            value,
+        );
+    }
+
+    fn set_velocity_error_time(&mut self, value: f32) {
+        self.following_error_settings.set_velocity_time(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityErrorTime, self.axis),
+            value,
+        );
+    }
+
+    fn set_following_error_reaction(&mut self, reaction: FaultReaction) {
+        self.following_error_settings.set_reaction(reaction);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::FollowingErrorReaction, self.axis),
+            reaction.into(),
+        );
+    }
+
+    fn following_error(&self) -> bool {
+        self.following_error
+    }
+
+    fn set_following_error(&mut self, following_error: bool) {
+        self.following_error = following_error;
+    }
+
+    fn fault(&self) -> ErrorCode {
+        self.fault.code()
+    }
+
+    fn raise_fault(&mut self, code: ErrorCode) {
+        self.fault.raise(code);
+    }
+
+    fn reset_fault(&mut self) {
+        self.fault.reset();
+        self.following_error = false;
+    }
+
+    fn backlash_settings(&self) -> BacklashSettings {
+        self.backlash_settings
+    }
+
+    fn set_backlash(&mut self, value: f32) {
+        self.backlash_settings.set_distance(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::Backlash, self.axis), value);
+    }
+
+    fn set_backlash_velocity(&mut self, value: f32) {
+        self.backlash_settings.set_velocity(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::BacklashVelocity, self.axis),
+            value,
+        );
+    }
+
+    fn unit_scaling(&self) -> UnitScaling {
+        self.unit_scaling
+    }
+
+    fn set_units_per_revolution(&mut self, value: f32) {
+        self.unit_scaling.set_units_per_revolution(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::UnitsPerRevolution, self.axis),
+            value,
+        );
+    }
+
+    fn set_scaling_numerator(&mut self, value: i32) {
+        self.unit_scaling.set_numerator(value);
+        self.storage.lock().borrow_mut().save_i32(
+            Key::key_for_axis(AxisKey::ScalingNumerator, self.axis),
+            value,
+        );
+    }
+
+    fn set_scaling_denominator(&mut self, value: i32) {
+        self.unit_scaling.set_denominator(value);
+        self.storage.lock().borrow_mut().save_i32(
+            Key::key_for_axis(AxisKey::ScalingDenominator, self.axis),
+            value,
+        );
+    }
+
+    fn set_polarity_inverted(&mut self, inverted: bool) {
+        self.unit_scaling.set_inverted(inverted);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::PolarityInverted, self.axis),
+            inverted as u8,
+        );
+    }
+
+    fn velocity_estimator_settings(&self) -> VelocityEstimatorSettings {
+        self.velocity_estimator_settings
+    }
+
+    fn set_velocity_filter(&mut self, filter: VelocityFilter) {
+        self.velocity_estimator_settings.set_filter(filter);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::VelocityFilter, self.axis),
+            filter.into(),
+        );
+    }
+
+    fn set_velocity_filter_window(&mut self, window: u8) {
+        self.velocity_estimator_settings.set_window(window);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::VelocityFilterWindow, self.axis),
+            window,
+        );
+    }
+
+    fn set_velocity_filter_cutoff(&mut self, cutoff: f32) {
+        self.velocity_estimator_settings.set_cutoff(cutoff);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::VelocityFilterCutoff, self.axis),
+            cutoff,
+        );
+    }
+
+    fn step_loss_settings(&self) -> StepLossSettings {
+        self.step_loss_settings
+    }
+
+    fn set_step_loss_threshold(&mut self, value: f32) {
+        self.step_loss_settings.set_threshold(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::StepLossThreshold, self.axis),
+            value,
+        );
+    }
+
+    fn set_encoder_ratio(&mut self, value: f32) {
+        self.step_loss_settings.set_encoder_ratio(value);
+        self.storage
+            .lock()
+            .borrow_mut()
+            .save_f32(Key::key_for_axis(AxisKey::EncoderRatio, self.axis), value);
+    }
+
+    fn set_step_loss_reaction(&mut self, reaction: StepLossReaction) {
+        self.step_loss_settings.set_reaction(reaction);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::StepLossReaction, self.axis),
+            reaction.into(),
+        );
+    }
+
+    fn step_loss_deviation(&self) -> f32 {
+        self.step_loss_deviation
+    }
+
+    fn set_step_loss_deviation(&mut self, deviation: f32) {
+        self.step_loss_deviation = deviation;
+    }
+
+    fn microsteps(&self) -> u16 {
+        self.microsteps
+    }
+
+    fn set_microsteps(&mut self, microsteps: u16) {
+        self.microsteps = microsteps;
+        self.storage.lock().borrow_mut().save_i32(
+            Key::key_for_axis(AxisKey::Microsteps, self.axis),
+            microsteps as i32,
+        );
+    }
+
+    fn supports_microsteps(&self, microsteps: u16) -> bool {
+        is_supported_microstepping(microsteps)
+            && match self.supported_microsteps {
+                Some(supported) => supported.contains(&microsteps),
+                None => true,
+            }
+    }
+
+    fn restrict_microsteps(&mut self, microsteps: &'static [u16]) {
+        self.supported_microsteps = Some(microsteps);
+        if !self.supports_microsteps(self.microsteps) {
+            self.microsteps = if self.supports_microsteps(DEFAULT_MICROSTEPS) {
+                DEFAULT_MICROSTEPS
+            } else {
+                microsteps.first().copied().unwrap_or(DEFAULT_MICROSTEPS)
+            };
+        }
+    }
+
+    fn auto_tune_settings(&self) -> AutoTuneSettings {
+        self.auto_tune_settings
+    }
+
+    fn set_auto_tune_loop(&mut self, tuning_loop: TuningLoop) {
+        self.auto_tune_settings.set_tuning_loop(tuning_loop);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::AutoTuneLoop, self.axis),
+            tuning_loop.into(),
+        );
+    }
+
+    fn set_auto_tune_rule(&mut self, rule: TuningRule) {
+        self.auto_tune_settings.set_rule(rule);
+        self.storage.lock().borrow_mut().save_u8(
+            Key::key_for_axis(AxisKey::AutoTuneRule, self.axis),
+            rule.into(),
+        );
+    }
+
+    fn set_auto_tune_amplitude(&mut self, value: f32) {
+        self.auto_tune_settings.set_amplitude(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::AutoTuneAmplitude, self.axis),
+            value,
+        );
+    }
+
+    fn set_auto_tune_hysteresis(&mut self, value: f32) {
+        self.auto_tune_settings.set_hysteresis(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::AutoTuneHysteresis, self.axis),
+            value,
+        );
+    }
+
+    fn auto_tune_state(&self) -> TuningState {
+        self.auto_tune_state
+    }
+
+    fn set_auto_tune_state(&mut self, state: TuningState) {
+        self.auto_tune_state = state;
+    }
+
+    fn auto_tune_result(&self) -> TuningResult {
+        self.auto_tune_result
+    }
+
+    fn set_auto_tune_result(&mut self, result: TuningResult) {
+        self.auto_tune_result = result;
+    }
+
+    fn commit_auto_tune_result(&mut self) -> bool {
+        if self.auto_tune_state != TuningState::Finished {
+            return false;
+        }
+        let result = self.auto_tune_result;
+        match result.tuning_loop {
+            TuningLoop::Velocity => {
+                self.set_velocity_controller_p(result.proportional);
+                self.set_velocity_controller_s(result.integral);
+                self.set_velocity_controller_d(result.derivative);
+            }
+            TuningLoop::Position => {
+                self.set_position_controller_p(result.proportional);
+                self.set_position_controller_s(result.integral);
+                self.set_position_controller_d(result.derivative);
+            }
+        }
+        true
+    }
+
+    fn stall_settings(&self) -> StallSettings {
+        self.stall_settings
+    }
+
+    fn set_stall_threshold(&mut self, threshold: u16) {
+        self.stall_settings.set_threshold(threshold);
+        self.storage.lock().borrow_mut().save_i32(
+            Key::key_for_axis(AxisKey::StallThreshold, self.axis),
+            threshold as i32,
+        );
+    }
+
+    fn set_stall_min_velocity(&mut self, value: f32) {
+        self.stall_settings.set_min_velocity(value);
+        self.storage.lock().borrow_mut().save_f32(
+            Key::key_for_axis(AxisKey::StallMinVelocity, self.axis),
+            value,
+        );
+    }
+
+    fn stall_guard_result(&self) -> u16 {
+        self.stall_guard_result
+    }
+
+    fn set_stall_guard_result(&mut self, result: u16) {
+        self.stall_guard_result = result;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canopen::{AxisDictionary, ObjectDictionary};
+    use std::collections::HashMap;
+
+    const RESOLUTION: u32 = 51_200;
+
+    #[derive(Default)]
+    struct MockStorage {
+        values: HashMap<u16, [u8; 4]>,
+    }
+
+    impl ObjectDictionaryStorage for MockStorage {
+        fn save_f32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: f32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn save_bool<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: bool) {
+            self.values.insert(key.raw(), [value as u8, 0, 0, 0]);
+        }
+        fn save_u8<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: u8) {
+            self.values.insert(key.raw(), [value, 0, 0, 0]);
+        }
+        fn save_i32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: i32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn load_f32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<f32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| f32::from_le_bytes(*raw))
+        }
+        fn load_bool<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<bool> {
+            self.values.get(&key.raw()).map(|raw| raw[0] != 0)
+        }
+        fn load_u8<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<u8> {
+            self.values.get(&key.raw()).map(|raw| raw[0])
+        }
+        fn load_i32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<i32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| i32::from_le_bytes(*raw))
+        }
+    }
+
+    fn leak(storage: MockStorage) -> &'static Mutex<RefCell<MockStorage>> {
+        Box::leak(Box::new(Mutex::new(RefCell::new(storage))))
+    }
+
+    #[test]
+    fn unversioned_gains_rescaled_once() {
+        let mut storage = MockStorage::default();
+        storage.save_f32(Key::key_for_axis(AxisKey::VelocityP, Axis::Axis1), 2.0);
+        storage.save_f32(Key::key_for_axis(AxisKey::VelocityS, Axis::Axis1), 0.5);
+        storage.save_f32(Key::key_for_axis(AxisKey::PositionD, Axis::Axis2), 0.25);
+        let storage = leak(storage);
+
+        for _ in 0..2 {
+            let dictionary = PersistentStoreObjectDictionary::<_, RESOLUTION>::new(storage);
+            let velocity = dictionary.axis(Axis::Axis1).velocity_controller_settings();
+            assert_eq!(velocity.proportional(), 2.0);
+            assert!((velocity.integral() - 0.05).abs() < 1e-6);
+            let position = dictionary.axis(Axis::Axis2).position_controller_settings();
+            assert!((position.derivative() - 2.5).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn supply_thresholds_survive_reboot() {
+        let storage = leak(MockStorage::default());
+        let mut dictionary = PersistentStoreObjectDictionary::<_, RESOLUTION>::new(storage);
+        dictionary.set_max_temperature(70.0);
+        dictionary.set_min_battery_voltage(10.5);
+
+        let dictionary = PersistentStoreObjectDictionary::<_, RESOLUTION>::new(storage);
+        assert_eq!(dictionary.max_temperature(), 70.0);
+        assert_eq!(dictionary.min_battery_voltage(), 10.5);
+    }
+
+    #[test]
+    fn pdo_units_survive_reboot() {
+        let storage = leak(MockStorage::default());
+        let mut dictionary = PersistentStoreObjectDictionary::<_, RESOLUTION>::new(storage);
+        dictionary.set_pdo_units(PdoUnits::FixedPoint);
+
+        let dictionary = PersistentStoreObjectDictionary::<_, RESOLUTION>::new(storage);
+        assert!(dictionary.pdo_units() == PdoUnits::FixedPoint);
+    }
+
+    #[test]
+    fn microsteps_survive_reboot() {
+        let storage = leak(MockStorage::default());
+        let mut dictionary =
+            PersistentStoreAxisDictionary::<_, RESOLUTION>::new(Axis::Axis2, storage);
+        dictionary.set_microsteps(256);
+
+        let dictionary = PersistentStoreAxisDictionary::<_, RESOLUTION>::new(Axis::Axis2, storage);
+        assert_eq!(dictionary.microsteps(), 256);
+    }
+
+    #[test]
+    fn unsupported_stored_microsteps() {
+        for stored in &[0, 3, 12, 512, 65_552] {
+            let mut storage = MockStorage::default();
+            storage.save_i32(Key::key_for_axis(AxisKey::Microsteps, Axis::Axis1), *stored);
+            let dictionary =
+                PersistentStoreAxisDictionary::<_, RESOLUTION>::new(Axis::Axis1, leak(storage));
+            assert_eq!(dictionary.microsteps(), DEFAULT_MICROSTEPS);
+        }
+    }
+
+    #[test]
+    fn restricted_microsteps() {
+        let storage = leak(MockStorage::default());
+        let mut dictionary =
+            PersistentStoreAxisDictionary::<_, RESOLUTION>::new(Axis::Axis1, storage);
+        dictionary.set_microsteps(8);
+        dictionary.restrict_microsteps(&[4, 16]);
+        assert_eq!(dictionary.microsteps(), 16);
+        assert!(dictionary.supports_microsteps(4));
+        assert!(!dictionary.supports_microsteps(8));
+
+        // the replaced microstepping is not stored
+        let dictionary = PersistentStoreAxisDictionary::<_, RESOLUTION>::new(Axis::Axis1, storage);
+        assert_eq!(dictionary.microsteps(), 8);
+    }
 }
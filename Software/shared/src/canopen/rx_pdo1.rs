@@ -8,6 +8,10 @@ pub struct RxPDO1 {
     pub axis2_mode: AxisMode,
     pub axis1_enabled: bool,
     pub axis2_enabled: bool,
+    /// Resets the latched fault of the first axis.
+    pub axis1_fault_reset: bool,
+    /// Resets the latched fault of the second axis.
+    pub axis2_fault_reset: bool,
 }
 
 impl RxPDO1 {
@@ -27,6 +31,8 @@ impl TryFrom<&[u8]> for RxPDO1 {
             axis2_mode: AxisMode::from(value[0] >> 4),
             axis1_enabled: value[1] & 0x01 > 0,
             axis2_enabled: value[1] & 0x02 > 0,
+            axis1_fault_reset: value[1] & 0x04 > 0,
+            axis2_fault_reset: value[1] & 0x08 > 0,
         })
     }
 }
@@ -43,7 +49,10 @@ impl SerializePDO for RxPDO1 {
         }
 
         raw[0] = u8::from(self.axis1_mode) | u8::from(self.axis2_mode) << 4;
-        raw[1] = if self.axis1_enabled { 1 } else { 0 } | if self.axis2_enabled { 2 } else { 0 };
+        raw[1] = if self.axis1_enabled { 1 } else { 0 }
+            | if self.axis2_enabled { 2 } else { 0 }
+            | if self.axis1_fault_reset { 4 } else { 0 }
+            | if self.axis2_fault_reset { 8 } else { 0 };
 
         Ok(raw)
     }
@@ -61,4 +70,18 @@ mod tests {
         let invalid_raw_data = [0u8; 4];
         assert!(RxPDO1::try_from(invalid_raw_data.as_ref()).is_err());
     }
+
+    #[test]
+    fn fault_reset() {
+        let pdo = RxPDO1 {
+            axis2_enabled: true,
+            axis2_fault_reset: true,
+            ..Default::default()
+        };
+        let raw = pdo.to_raw().unwrap();
+        let pdo = RxPDO1::try_from(&raw[..RxPDO1::len()]).unwrap();
+        assert!(!pdo.axis1_fault_reset);
+        assert!(pdo.axis2_fault_reset);
+        assert!(pdo.axis2_enabled);
+    }
 }
@@ -1,19 +1,28 @@
 use crate::canopen::{PDODeserializationError, PDOSerializationError, SerializePDO};
+use crate::models::ErrorCode;
 use core::convert::{TryFrom, TryInto};
 
 /// `TxPDO1` represents the first Process Data Object sent by the device to the master.
 /// The PDO is reserved for general status information only.
-/// As of now it contains the information about the motor supply voltage and die temperature.
+/// As of now it contains the information about the motor supply voltage, die temperature, the following errors and the faults of the axes.
 #[derive(Copy, Clone, Default)]
 pub struct TxPDO1 {
     /// The motor supply temperature. In millivolts.
     pub battery_voltage: u16,
     /// The STM32F4 die temperature. In 0.1 deg C.
     pub temperature: u16,
+    /// The latched following error of the first axis.
+    pub axis1_following_error: bool,
+    /// The latched following error of the second axis.
+    pub axis2_following_error: bool,
+    /// The latched fault of the first axis.
+    pub axis1_error_code: ErrorCode,
+    /// The latched fault of the second axis.
+    pub axis2_error_code: ErrorCode,
 }
 
 impl TxPDO1 {
-    const SIZE: usize = 4;
+    const SIZE: usize = 7;
 }
 
 impl SerializePDO for TxPDO1 {
@@ -29,6 +38,10 @@ impl SerializePDO for TxPDO1 {
 
         raw[..2].clone_from_slice(&self.battery_voltage.to_le_bytes());
         raw[2..4].clone_from_slice(&self.temperature.to_le_bytes());
+        raw[4] = if self.axis1_following_error { 1 } else { 0 }
+            | if self.axis2_following_error { 2 } else { 0 };
+        raw[5] = self.axis1_error_code.into();
+        raw[6] = self.axis2_error_code.into();
 
         Ok(raw)
     }
@@ -45,6 +58,36 @@ impl TryFrom<&[u8]> for TxPDO1 {
         Ok(TxPDO1 {
             battery_voltage: u16::from_le_bytes(value[..2].try_into().unwrap()),
             temperature: u16::from_le_bytes(value[2..4].try_into().unwrap()),
+            axis1_following_error: value[4] & 0x01 > 0,
+            axis2_following_error: value[4] & 0x02 > 0,
+            // the codes unknown to this side are reported as no fault
+            axis1_error_code: ErrorCode::try_from(value[5]).unwrap_or_default(),
+            axis2_error_code: ErrorCode::try_from(value[6]).unwrap_or_default(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialization() {
+        let pdo = TxPDO1 {
+            battery_voltage: 12000,
+            temperature: 250,
+            axis1_following_error: false,
+            axis2_following_error: true,
+            axis1_error_code: ErrorCode::Undervoltage,
+            axis2_error_code: ErrorCode::None,
+        };
+        let raw = pdo.to_raw().unwrap();
+        let pdo = TxPDO1::try_from(&raw[..TxPDO1::len()]).unwrap();
+        assert_eq!(pdo.battery_voltage, 12000);
+        assert_eq!(pdo.temperature, 250);
+        assert!(!pdo.axis1_following_error);
+        assert!(pdo.axis2_following_error);
+        assert_eq!(pdo.axis1_error_code, ErrorCode::Undervoltage);
+        assert_eq!(pdo.axis2_error_code, ErrorCode::None);
+    }
+}
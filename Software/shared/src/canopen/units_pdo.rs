@@ -0,0 +1,82 @@
+use crate::canopen::{PDODeserializationError, PDOSerializationError};
+use crate::units::{from_fixed_point, to_fixed_point};
+use core::convert::TryInto;
+
+/// The `UnitsPDO` contains two values in user units.
+/// It replaces the velocity and position PDOs when the user units are selected for the PDOs,
+/// the values are serialized either as `f32` or as fixed-point `i32`.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct UnitsPDO {
+    pub first: f32,
+    pub second: f32,
+}
+
+impl UnitsPDO {
+    const SIZE: usize = 8;
+
+    pub fn len() -> usize {
+        Self::SIZE
+    }
+
+    /// Deserializes the PDO, the values are fixed-point when `fixed_point` is set.
+    pub fn parse(value: &[u8], fixed_point: bool) -> Result<Self, PDODeserializationError> {
+        if value.len() != Self::SIZE {
+            return Err(PDODeserializationError::IncorrectDataSize);
+        }
+
+        let parse = |raw: &[u8]| {
+            let raw: [u8; 4] = raw.try_into().unwrap();
+            if fixed_point {
+                from_fixed_point(i32::from_le_bytes(raw))
+            } else {
+                f32::from_le_bytes(raw)
+            }
+        };
+        Ok(Self {
+            first: parse(&value[..4]),
+            second: parse(&value[4..]),
+        })
+    }
+
+    /// Serializes the PDO, the values are fixed-point when `fixed_point` is set.
+    pub fn to_raw(&self, fixed_point: bool) -> Result<[u8; 8], PDOSerializationError> {
+        let mut raw = [0u8; 8];
+        if raw.len() < Self::SIZE {
+            return Err(PDOSerializationError::BufferTooSmall);
+        }
+
+        let serialize = |value: f32| {
+            if fixed_point {
+                to_fixed_point(value).to_le_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        raw[..4].clone_from_slice(&serialize(self.first));
+        raw[4..].clone_from_slice(&serialize(self.second));
+
+        Ok(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialization() {
+        let pdo = UnitsPDO {
+            first: 12.5,
+            second: -0.25,
+        };
+        let raw = pdo.to_raw(true).unwrap();
+        assert_eq!(i32::from_le_bytes(raw[..4].try_into().unwrap()), 12500);
+        let parsed = UnitsPDO::parse(&raw, true).unwrap();
+        assert_eq!(parsed.first, 12.5);
+        assert_eq!(parsed.second, -0.25);
+
+        let parsed = UnitsPDO::parse(&pdo.to_raw(false).unwrap(), false).unwrap();
+        assert_eq!(parsed.first, 12.5);
+        assert!(UnitsPDO::parse(&raw[..4], false).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Coordinated motion of both axes of the driver.
+//!
+//! The coordinated move is planned as a single point-to-point move of a path parameter going from zero to one.
+//! The position of each axis is a linear function of the path parameter, so both axes start and finish
+//! at the same time and the move is a straight line in joint space.
+//! The limits of the path parameter are derived from the limits of the axes scaled by their travelled distances,
+//! so the axis with the longest move (relative to its limits) is the one limiting the move.
+use crate::canopen::ObjectDictionary;
+use crate::models::{Axis, AxisMode, ErrorCode};
+use crate::motion_controller::{actual_state, profile_limits};
+use crate::planner::{ProfileLimits, TrajectoryPlanner, TrajectoryPoint};
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+const AXES: [Axis; 2] = [Axis::Axis1, Axis::Axis2];
+
+/// Planner of straight line moves of both axes.
+pub struct CoordinatedMotion {
+    planner: TrajectoryPlanner,
+    start: [f32; 2],
+    distance: [f32; 2],
+    /// The targets (in revolutions) of the move that is being executed, `None` when no move is executed.
+    planned_target: Option<[f32; 2]>,
+}
+
+impl CoordinatedMotion {
+    pub fn new(control_period: Microseconds) -> Self {
+        Self {
+            planner: TrajectoryPlanner::new(control_period),
+            start: [0.0; 2],
+            distance: [0.0; 2],
+            planned_target: None,
+        }
+    }
+
+    /// Plans a new coordinated move.
+    /// When the axes are moving, the velocity along the new line is given by the projection
+    /// of their velocities to the direction of the move.
+    ///
+    /// # Arguments
+    /// * `start` - states of the axes the move starts from
+    /// * `target` - target positions of the axes in revolutions
+    /// * `limits` - limits of the individual axes
+    pub fn plan(
+        &mut self,
+        start: [TrajectoryPoint; 2],
+        target: [f32; 2],
+        limits: [ProfileLimits; 2],
+    ) {
+        self.start = [start[0].position, start[1].position];
+        self.distance = [target[0] - start[0].position, target[1] - start[1].position];
+
+        let squared_length: f32 = self.distance.iter().map(|d| d * d).sum();
+        let path_velocity = if squared_length > 0.0 {
+            (start[0].velocity * self.distance[0] + start[1].velocity * self.distance[1])
+                / squared_length
+        } else {
+            0.0
+        };
+
+        self.planner.plan(
+            TrajectoryPoint {
+                position: 0.0,
+                velocity: path_velocity,
+                acceleration: 0.0,
+            },
+            1.0,
+            &Self::path_limits(&self.distance, &limits),
+        );
+    }
+
+    /// Returns the limits of the path parameter, so that none of the axes exceeds its own limits.
+    fn path_limits(distance: &[f32; 2], limits: &[ProfileLimits; 2]) -> ProfileLimits {
+        let mut velocity = f32::MAX;
+        let mut acceleration = f32::MAX;
+        let mut deceleration = f32::MAX;
+        let mut jerk = Some(f32::MAX);
+        for (distance, limits) in distance.iter().zip(limits.iter()) {
+            let distance = distance.abs();
+            if distance <= 0.0 {
+                continue;
+            }
+            velocity = velocity.min(limits.velocity() / distance);
+            acceleration = acceleration.min(limits.acceleration() / distance);
+            deceleration = deceleration.min(limits.deceleration() / distance);
+            // the move is jerk limited only when all the moving axes are jerk limited
+            jerk = match (jerk, limits.jerk()) {
+                (Some(path), Some(axis)) => Some(path.min(axis / distance)),
+                _ => None,
+            };
+        }
+
+        match jerk {
+            Some(jerk) => ProfileLimits::s_curve(velocity, acceleration, deceleration, jerk),
+            None => ProfileLimits::trapezoidal(velocity, acceleration, deceleration),
+        }
+    }
+
+    /// Advances the move by a single period and returns the reference states of both axes.
+    pub fn sample(&mut self) -> [TrajectoryPoint; 2] {
+        let path = self.planner.sample();
+        self.axis_references(&path)
+    }
+
+    /// Returns the reference states of both axes at the current time of the move.
+    pub fn reference(&self) -> [TrajectoryPoint; 2] {
+        self.axis_references(&self.planner.reference())
+    }
+
+    fn axis_references(&self, path: &TrajectoryPoint) -> [TrajectoryPoint; 2] {
+        let axis = |index: usize| TrajectoryPoint {
+            position: self.start[index] + path.position * self.distance[index],
+            velocity: path.velocity * self.distance[index],
+            acceleration: path.acceleration * self.distance[index],
+        };
+        [axis(0), axis(1)]
+    }
+
+    /// Returns true when the planned move was completely executed.
+    pub fn is_finished(&self) -> bool {
+        self.planner.is_finished()
+    }
+
+    /// Returns true when the axes shall be controlled by the coordinated motion -
+    /// the coordinated motion is enabled in the dictionary and both axes are enabled in position mode without a fault.
+    pub fn is_active<const RESOLUTION: u32>(
+        global_disable: bool,
+        dictionary: &dyn ObjectDictionary<RESOLUTION>,
+    ) -> bool {
+        !global_disable
+            && dictionary.coordinated_motion_enabled()
+            && AXES.iter().all(|axis| {
+                let axis = dictionary.axis(*axis);
+                axis.enabled()
+                    && axis.mode() == AxisMode::Position
+                    && axis.fault() == ErrorCode::None
+            })
+    }
+
+    /// Returns the references of both axes for the current control step, replanning the move when the target
+    /// positions of the axes change. Returns `None` when the coordinated motion is not active.
+    ///
+    /// The targets are limited by the software position limits of the axes,
+    /// the violations are reported to the dictionary.
+    pub fn control<const RESOLUTION: u32>(
+        &mut self,
+        global_disable: bool,
+        dictionary: &mut dyn ObjectDictionary<RESOLUTION>,
+    ) -> Option<[TrajectoryPoint; 2]> {
+        if !Self::is_active(global_disable, dictionary) {
+            self.planned_target = None;
+            return None;
+        }
+
+        let mut target = [0.0; 2];
+        for (index, axis) in AXES.iter().enumerate() {
+            let axis = dictionary.axis_mut(*axis);
+            let (position, violation) = axis
+                .position_limits()
+                .limit_position(axis.target_position().get_relative_revolutions());
+            axis.set_limit_violation(violation);
+            target[index] = position;
+        }
+
+        if self.planned_target != Some(target) {
+            let start = match self.planned_target {
+                Some(_) => self.reference(),
+                None => [
+                    actual_state(dictionary.axis(Axis::Axis1)),
+                    actual_state(dictionary.axis(Axis::Axis2)),
+                ],
+            };
+            let limits = [
+                profile_limits(dictionary.axis(Axis::Axis1)),
+                profile_limits(dictionary.axis(Axis::Axis2)),
+            ];
+            self.plan(start, target, limits);
+            self.planned_target = Some(target);
+        }
+
+        Some(self.sample())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    fn at_rest(position: f32) -> TrajectoryPoint {
+        TrajectoryPoint {
+            position,
+            velocity: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    #[test]
+    fn straight_line() {
+        let mut motion = CoordinatedMotion::new(PERIOD);
+        let limits = [
+            ProfileLimits::trapezoidal(2.0, 10.0, 10.0),
+            ProfileLimits::trapezoidal(1.0, 10.0, 10.0),
+        ];
+        motion.plan([at_rest(0.0), at_rest(1.0)], [4.0, -1.0], limits);
+
+        let mut reference = motion.reference();
+        for _ in 0..100_000 {
+            reference = motion.sample();
+            // both axes stay on the line
+            assert!((reference[1].position - (1.0 - reference[0].position / 2.0)).abs() < 1e-3);
+            assert!(reference[0].velocity.abs() <= 2.0 + 1e-3);
+            assert!(reference[1].velocity.abs() <= 1.0 + 1e-3);
+            if motion.is_finished() {
+                break;
+            }
+        }
+
+        assert!((reference[0].position - 4.0).abs() < 1e-4);
+        assert!((reference[1].position + 1.0).abs() < 1e-4);
+        // both axes limit the velocity of the move equally, the acceleration is limited by the first one
+        assert!((motion.planner.duration() - (2.0 + 0.2)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn single_axis_move() {
+        let mut motion = CoordinatedMotion::new(PERIOD);
+        let limits = [
+            ProfileLimits::s_curve(2.0, 10.0, 10.0, 100.0),
+            ProfileLimits::trapezoidal(1.0, 10.0, 10.0),
+        ];
+        motion.plan([at_rest(0.0), at_rest(3.0)], [0.0, 3.5], limits);
+        while !motion.is_finished() {
+            let reference = motion.sample();
+            assert_eq!(reference[0].position, 0.0);
+        }
+        assert!((motion.reference()[1].position - 3.5).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,215 @@
+//! Policy of the motor current of an axis.
+//!
+//! The current is interpolated between the standstill current and the running current, which itself is interpolated
+//! between the constant velocity current and the accelerating current based on the commanded acceleration.
+//! The load of the axis (the difference between the commanded and the measured velocity) boosts the current.
+//! An axis standing still for longer than the idle delay drops to the idle current.
+//! The output current changes with a limited slew rate, so there are no current steps.
+use crate::canopen::CurrentSettings;
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+#[derive(Copy, Clone)]
+pub struct CurrentPolicySettings {
+    idle_current: f32,
+    idle_delay: f32,
+    full_current_velocity: f32,
+    load_boost: f32,
+    slew_rate: f32,
+}
+
+impl CurrentPolicySettings {
+    /// Creates new current policy settings.
+    ///
+    /// # Arguments
+    /// * `idle_current` - the current of an axis standing still for longer than the idle delay in amperes
+    /// * `idle_delay` - the time after which a standing axis drops to the idle current in seconds
+    /// * `full_current_velocity` - the velocity at which the running current is reached in revolutions per second
+    /// * `load_boost` - the current added per revolution per second of the velocity difference in amperes
+    /// * `slew_rate` - the maximal change of the current in amperes per second, zero for no limitation
+    pub fn new(
+        idle_current: f32,
+        idle_delay: f32,
+        full_current_velocity: f32,
+        load_boost: f32,
+        slew_rate: f32,
+    ) -> Self {
+        Self {
+            idle_current,
+            idle_delay,
+            full_current_velocity,
+            load_boost,
+            slew_rate,
+        }
+    }
+
+    pub fn idle_current(&self) -> f32 {
+        self.idle_current
+    }
+    pub fn idle_delay(&self) -> f32 {
+        self.idle_delay
+    }
+    pub fn full_current_velocity(&self) -> f32 {
+        self.full_current_velocity
+    }
+    pub fn load_boost(&self) -> f32 {
+        self.load_boost
+    }
+    pub fn slew_rate(&self) -> f32 {
+        self.slew_rate
+    }
+    pub fn set_idle_current(&mut self, idle_current: f32) {
+        self.idle_current = idle_current;
+    }
+    pub fn set_idle_delay(&mut self, idle_delay: f32) {
+        self.idle_delay = idle_delay;
+    }
+    pub fn set_full_current_velocity(&mut self, full_current_velocity: f32) {
+        self.full_current_velocity = full_current_velocity;
+    }
+    pub fn set_load_boost(&mut self, load_boost: f32) {
+        self.load_boost = load_boost;
+    }
+    pub fn set_slew_rate(&mut self, slew_rate: f32) {
+        self.slew_rate = slew_rate;
+    }
+}
+
+impl Default for CurrentPolicySettings {
+    fn default() -> Self {
+        Self::new(0.2, 2.0, 0.5, 0.0, 5.0)
+    }
+}
+
+/// Generator of the motor current of an axis, sampled with the ramping period.
+pub struct CurrentPolicy {
+    period: f32,
+    last_velocity: f32,
+    /// Time the axis has been standing still in seconds.
+    standstill_time: f32,
+    current: f32,
+}
+
+impl CurrentPolicy {
+    pub fn new(ramping_period: Microseconds) -> Self {
+        Self {
+            period: ramping_period.0 as f32 / 1_000_000.0,
+            last_velocity: 0.0,
+            standstill_time: 0.0,
+            current: 0.0,
+        }
+    }
+
+    /// Returns the current for the next period.
+    ///
+    /// # Arguments
+    /// * `velocity` - the commanded velocity in revolutions per second
+    /// * `load` - the difference between the commanded and the measured velocity in revolutions per second
+    /// * `acceleration_limit` - the acceleration the commanded velocity is ramped with
+    /// * `current` - the current levels of the axis
+    /// * `settings` - the current policy settings of the axis
+    pub fn sample(
+        &mut self,
+        velocity: f32,
+        load: f32,
+        acceleration_limit: f32,
+        current: &CurrentSettings,
+        settings: &CurrentPolicySettings,
+    ) -> f32 {
+        let acceleration = (velocity - self.last_velocity) / self.period;
+        self.last_velocity = velocity;
+
+        let acceleration_ratio = Self::ratio(acceleration.abs(), acceleration_limit.abs());
+        let velocity_ratio = Self::ratio(velocity.abs(), settings.full_current_velocity);
+
+        let standing = acceleration_ratio == 0.0 && velocity_ratio == 0.0;
+        self.standstill_time = if standing {
+            self.standstill_time + self.period
+        } else {
+            0.0
+        };
+
+        let base = if standing && self.standstill_time >= settings.idle_delay {
+            settings.idle_current
+        } else {
+            let running = current.constant_velocity_current()
+                + (current.accelerating_current() - current.constant_velocity_current())
+                    * acceleration_ratio;
+            current.standstill_current()
+                + (running - current.standstill_current()) * velocity_ratio.max(acceleration_ratio)
+        };
+        let target = (base + settings.load_boost * load.abs()).max(0.0);
+
+        let step = settings.slew_rate * self.period;
+        self.current = if settings.slew_rate > 0.0 {
+            self.current + (target - self.current).max(-step).min(step)
+        } else {
+            target
+        };
+        self.current
+    }
+
+    /// Returns the ratio of the `value` to the `full` value limited to the range from zero to one.
+    fn ratio(value: f32, full: f32) -> f32 {
+        if full > 0.0 {
+            (value / full).min(1.0)
+        } else if value > 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(10_000);
+    const EPSILON: f32 = 1e-4;
+
+    fn levels() -> CurrentSettings {
+        CurrentSettings::new(0.4, 1.0, 0.6)
+    }
+
+    #[test]
+    fn interpolation() {
+        let settings = CurrentPolicySettings::new(0.2, 1.0, 1.0, 0.0, 0.0);
+        let mut policy = CurrentPolicy::new(PERIOD);
+        let mut sample = |velocity, load, acceleration, settings: &CurrentPolicySettings| {
+            policy.sample(velocity, load, acceleration, &levels(), settings)
+        };
+        assert!((sample(0.0, 0.0, 1.0, &settings) - 0.4).abs() < EPSILON);
+        // accelerating with the full acceleration
+        assert!((sample(0.01, 0.0, 1.0, &settings) - 1.0).abs() < EPSILON);
+        // accelerating with the half of the acceleration
+        assert!((sample(0.015, 0.0, 1.0, &settings) - 0.6).abs() < EPSILON);
+        // constant velocity at the half of the full current velocity
+        sample(0.5, 0.0, 1.0, &settings);
+        assert!((sample(0.5, 0.0, 1.0, &settings) - 0.5).abs() < EPSILON);
+
+        let settings = CurrentPolicySettings::new(0.2, 1.0, 1.0, 0.5, 0.0);
+        assert!((sample(0.5, 0.2, 1.0, &settings) - 0.6).abs() < EPSILON);
+    }
+
+    #[test]
+    fn idle_and_slew_rate() {
+        let settings = CurrentPolicySettings::new(0.2, 0.5, 1.0, 0.0, 1.0);
+        let mut policy = CurrentPolicy::new(PERIOD);
+        let mut sample = || policy.sample(0.0, 0.0, 1.0, &levels(), &settings);
+        // the current rises slowly from zero
+        assert!((sample() - 0.01).abs() < EPSILON);
+        let mut current = 0.0;
+        for _ in 0..49 {
+            current = sample();
+        }
+        assert!((current - 0.4).abs() < EPSILON);
+        // the idle delay has elapsed, the current drops slowly
+        assert!((sample() - 0.39).abs() < 0.011);
+        for _ in 0..100 {
+            current = sample();
+        }
+        assert!((current - 0.2).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,201 @@
+//! Kinematics of a differential drive (tank) robot driven by both axes of the driver.
+//!
+//! The left wheel is driven by the first axis and the right wheel by the second axis.
+//! Lengths are in the units of the wheel radius and the track width (generally meters), angles in radians.
+use crate::models::{Position, Velocity};
+use core::f32::consts::PI;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// Position and heading of the robot.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    /// Heading in radians in the range from -pi to pi, zero is the direction of the x axis.
+    pub heading: f32,
+}
+
+pub struct DifferentialDrive<const RESOLUTION: u32> {
+    wheel_radius: f32,
+    track_width: f32,
+    left_inverted: bool,
+    right_inverted: bool,
+    pose: Pose,
+    /// Wheel positions used in the last odometry update, `None` before the first update.
+    last_positions: Option<(Position<RESOLUTION>, Position<RESOLUTION>)>,
+}
+
+impl<const RESOLUTION: u32> DifferentialDrive<RESOLUTION> {
+    /// Creates a new differential drive.
+    ///
+    /// # Arguments
+    /// * `wheel_radius` - radius of the wheels
+    /// * `track_width` - distance between the wheels
+    pub fn new(wheel_radius: f32, track_width: f32) -> Self {
+        Self {
+            wheel_radius,
+            track_width,
+            left_inverted: false,
+            right_inverted: false,
+            pose: Pose::default(),
+            last_positions: None,
+        }
+    }
+
+    pub fn wheel_radius(&self) -> f32 {
+        self.wheel_radius
+    }
+    pub fn track_width(&self) -> f32 {
+        self.track_width
+    }
+    pub fn left_inverted(&self) -> bool {
+        self.left_inverted
+    }
+    pub fn right_inverted(&self) -> bool {
+        self.right_inverted
+    }
+    /// Inverts the direction of the left wheel, used when the motor turns backwards when moving forward.
+    pub fn set_left_inverted(&mut self, left_inverted: bool) {
+        self.left_inverted = left_inverted;
+    }
+    /// Inverts the direction of the right wheel, used when the motor turns backwards when moving forward.
+    pub fn set_right_inverted(&mut self, right_inverted: bool) {
+        self.right_inverted = right_inverted;
+    }
+
+    /// Converts the velocity command of the robot to the velocities of the axes (left, right).
+    ///
+    /// # Arguments
+    /// * `linear` - forward velocity of the robot
+    /// * `angular` - angular velocity of the robot in radians per second, positive values turn the robot left
+    ///
+    /// # Example
+    /// ```
+    /// use sm4_shared::prelude::DifferentialDrive;
+    ///
+    /// let mut drive = DifferentialDrive::<{ 3200 }>::new(0.5 / core::f32::consts::PI, 1.0);
+    /// drive.set_left_inverted(true);
+    /// let (left, right) = drive.wheel_velocities(1.0, 0.0);
+    /// assert_eq!(left.get_rps(), -1.0);
+    /// assert_eq!(right.get_rps(), 1.0);
+    /// ```
+    pub fn wheel_velocities(&self, linear: f32, angular: f32) -> (Velocity, Velocity) {
+        let difference = angular * self.track_width / 2.0;
+        (
+            Velocity::new(self.to_revolutions(linear - difference, self.left_inverted)),
+            Velocity::new(self.to_revolutions(linear + difference, self.right_inverted)),
+        )
+    }
+
+    /// Integrates the odometry using the positions of the axes (left, right) and returns the updated pose.
+    /// The first update only stores the positions.
+    pub fn update_odometry(
+        &mut self,
+        left: Position<RESOLUTION>,
+        right: Position<RESOLUTION>,
+    ) -> Pose {
+        if let Some((last_left, last_right)) = self.last_positions {
+            let left_distance = self.to_distance(left - &last_left, self.left_inverted);
+            let right_distance = self.to_distance(right - &last_right, self.right_inverted);
+
+            let distance = (left_distance + right_distance) / 2.0;
+            let rotation = (right_distance - left_distance) / self.track_width;
+            // the movement is approximated by a straight line in the mean direction
+            let direction = self.pose.heading + rotation / 2.0;
+            self.pose.x += distance * direction.cos();
+            self.pose.y += distance * direction.sin();
+            let heading = self.pose.heading + rotation;
+            self.pose.heading = heading.sin().atan2(heading.cos());
+        }
+        self.last_positions = Some((left, right));
+        self.pose
+    }
+
+    pub fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    /// Sets the current pose of the robot, the odometry continues from the pose.
+    pub fn reset_odometry(&mut self, pose: Pose) {
+        self.pose = pose;
+    }
+
+    fn to_revolutions(&self, velocity: f32, inverted: bool) -> f32 {
+        let rps = velocity / (2.0 * PI * self.wheel_radius);
+        if inverted {
+            -rps
+        } else {
+            rps
+        }
+    }
+
+    fn to_distance(&self, difference: Position<RESOLUTION>, inverted: bool) -> f32 {
+        let distance = difference.get_relative_revolutions() * 2.0 * PI * self.wheel_radius;
+        if inverted {
+            -distance
+        } else {
+            distance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOLUTION: u32 = 4;
+    const EPSILON: f32 = 1e-4;
+
+    /// Drive with the wheel circumference of 1 and the track width of 1.
+    fn drive() -> DifferentialDrive<RESOLUTION> {
+        DifferentialDrive::new(0.5 / PI, 1.0)
+    }
+
+    #[test]
+    fn wheel_velocities() {
+        let mut drive = drive();
+        let (left, right) = drive.wheel_velocities(0.0, 2.0);
+        assert!((left.get_rps() + 1.0).abs() < EPSILON);
+        assert!((right.get_rps() - 1.0).abs() < EPSILON);
+
+        drive.set_right_inverted(true);
+        let (left, right) = drive.wheel_velocities(2.0, 0.0);
+        assert!((left.get_rps() - 2.0).abs() < EPSILON);
+        assert!((right.get_rps() + 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn straight_odometry() {
+        let mut drive = drive();
+        drive.update_odometry(Position::new(1, 0), Position::new(1, 0));
+        let pose = drive.update_odometry(Position::new(3, 2), Position::new(3, 2));
+        assert!((pose.x - 2.5).abs() < EPSILON);
+        assert!(pose.y.abs() < EPSILON);
+        assert!(pose.heading.abs() < EPSILON);
+    }
+
+    #[test]
+    fn rotation_odometry() {
+        let mut drive = drive();
+        drive.set_left_inverted(true);
+        let mut position = Position::zero();
+        drive.update_odometry(position, position);
+        // both axes turn forward, the inverted left wheel moves backwards and the robot spins in place
+        for _ in 0..4 {
+            position += 1;
+            drive.update_odometry(position, position);
+        }
+        let pose = drive.pose();
+        assert!(pose.x.abs() < EPSILON);
+        assert!(pose.y.abs() < EPSILON);
+        assert!((pose.heading - 2.0).abs() < EPSILON);
+
+        // the heading wraps around
+        for _ in 0..4 {
+            position += 1;
+            drive.update_odometry(position, position);
+        }
+        assert!((drive.pose().heading - (4.0 - 2.0 * PI)).abs() < EPSILON);
+    }
+}
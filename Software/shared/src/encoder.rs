@@ -15,6 +15,7 @@
 //! * The total position shall be resettable.
 //! * For non-quadrature encoders a method that indicates change of motor rotation shall be implemented.
 use crate::models::{Position, Velocity};
+use crate::velocity_estimator::VelocityEstimatorSettings;
 
 /// `Direction` enum represents the direction where the motor is turning when looking at the shaft.
 #[derive(Copy, Clone, PartialEq)]
@@ -83,16 +84,29 @@ pub trait Encoder<const RESOLUTION: u32> {
     /// # Arguments
     /// * `direction` - indicates whether the shaft is now turning in the clockwise or counterclockwise direction.
     fn notify_direction_changed(&mut self, direction: Direction);
+
+    /// Configures the estimation of the velocity.
+    /// Encoders estimating the velocity, e.g. the [crate::prelude::FilteredEncoder], apply the `settings`,
+    /// the other encoders ignore them.
+    fn set_velocity_estimator(&mut self, _settings: &VelocityEstimatorSettings) {}
+
+    /// This method shall be called with the encoders counting the generated steps,
+    /// whenever the number of steps per revolution of the motor changes.
+    /// The other encoders ignore it.
+    /// # Arguments
+    /// * `steps_per_revolution` - the number of (micro)steps generated per a single revolution of the motor
+    fn set_step_resolution(&mut self, _steps_per_revolution: u32) {}
 }
 
+/// Encoder turning by a single increment in the notified direction on every sample, for the tests of the other modules.
 #[cfg(test)]
-mod tests {
+pub(crate) mod mock {
     use super::*;
     use embedded_time::duration::Microseconds;
 
-    const ENCODER_RESOLUTION: u32 = 4;
+    pub(crate) const ENCODER_RESOLUTION: u32 = 4;
 
-    struct MockEncoder {
+    pub(crate) struct MockEncoder {
         current_position: Position<ENCODER_RESOLUTION>,
         current_velocity: Velocity,
         direction: Direction,
@@ -100,7 +114,7 @@ mod tests {
     }
 
     impl MockEncoder {
-        fn new() -> Self {
+        pub(crate) fn new() -> Self {
             Self {
                 current_position: Position::zero(),
                 current_velocity: Velocity::zero(),
@@ -142,6 +156,12 @@ mod tests {
             self.direction = direction;
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockEncoder;
+    use super::*;
 
     #[test]
     fn mock_encoder_test() {
@@ -0,0 +1,103 @@
+//! Faults of the axes.
+//!
+//! A fault is latched in the object dictionary of the axis when it occurs and blocks the motion of the axis
+//! until it is reset. Only the first fault is latched, so the reported error code is the cause of the stop.
+//! When the fault is reset while its cause persists, the fault is latched again by the next check.
+use crate::canopen::ObjectDictionary;
+use crate::models::{Axis, ErrorCode};
+
+/// The latched fault of a single axis.
+#[derive(Copy, Clone, Default)]
+pub struct FaultLatch {
+    code: ErrorCode,
+}
+
+impl FaultLatch {
+    /// Latches the fault with the `code` unless another fault is latched already.
+    /// Returns true when the fault has been latched.
+    pub fn raise(&mut self, code: ErrorCode) -> bool {
+        if self.is_active() || code == ErrorCode::None {
+            return false;
+        }
+        self.code = code;
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.code = ErrorCode::None;
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.code != ErrorCode::None
+    }
+}
+
+/// Latches the fault with the `code` on both axes of the driver.
+pub fn raise_fault<const RESOLUTION: u32>(
+    dictionary: &mut dyn ObjectDictionary<RESOLUTION>,
+    code: ErrorCode,
+) {
+    dictionary.axis_mut(Axis::Axis1).raise_fault(code);
+    dictionary.axis_mut(Axis::Axis2).raise_fault(code);
+}
+
+/// Checks the motor supply voltage and the temperature of the driver stored in the `dictionary`
+/// and latches the fault on both axes when they are out of their limits.
+pub fn monitor_supply<const RESOLUTION: u32>(dictionary: &mut dyn ObjectDictionary<RESOLUTION>) {
+    let code = supply_fault(
+        dictionary.battery_voltage(),
+        dictionary.temperature(),
+        dictionary.min_battery_voltage(),
+        dictionary.max_temperature(),
+    );
+    raise_fault(dictionary, code);
+}
+
+/// Returns the fault caused by the motor supply `voltage` or the `temperature` of the driver.
+fn supply_fault(
+    voltage: f32,
+    temperature: f32,
+    min_voltage: f32,
+    max_temperature: f32,
+) -> ErrorCode {
+    if temperature > max_temperature {
+        ErrorCode::OverTemperature
+    } else if voltage < min_voltage {
+        ErrorCode::Undervoltage
+    } else {
+        ErrorCode::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latch() {
+        let mut latch = FaultLatch::default();
+        assert!(!latch.raise(ErrorCode::None));
+        assert!(!latch.is_active());
+        assert!(latch.raise(ErrorCode::LimitHit));
+        // the first fault stays latched
+        assert!(!latch.raise(ErrorCode::Undervoltage));
+        assert_eq!(latch.code(), ErrorCode::LimitHit);
+        latch.reset();
+        assert!(!latch.is_active());
+        assert!(latch.raise(ErrorCode::Undervoltage));
+    }
+
+    #[test]
+    fn supply() {
+        assert_eq!(supply_fault(12.0, 40.0, 6.0, 85.0), ErrorCode::None);
+        assert_eq!(supply_fault(5.0, 40.0, 6.0, 85.0), ErrorCode::Undervoltage);
+        assert_eq!(
+            supply_fault(5.0, 90.0, 6.0, 85.0),
+            ErrorCode::OverTemperature
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Monitoring of the deviation of an axis from its setpoint.
+use crate::models::FaultReaction;
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+#[derive(Copy, Clone, Default)]
+pub struct FollowingErrorSettings {
+    position_window: f32,
+    position_time: f32,
+    velocity_window: f32,
+    velocity_time: f32,
+    reaction: FaultReaction,
+}
+
+impl FollowingErrorSettings {
+    /// Creates new following error settings.
+    ///
+    /// # Arguments
+    /// * `position_window` - the allowed position error in revolutions, zero disables the position monitoring
+    /// * `position_time` - the time the position error may stay outside of the window in seconds
+    /// * `velocity_window` - the allowed velocity error in revolutions per second, zero disables the velocity monitoring
+    /// * `velocity_time` - the time the velocity error may stay outside of the window in seconds
+    /// * `reaction` - the reaction to the following error
+    pub fn new(
+        position_window: f32,
+        position_time: f32,
+        velocity_window: f32,
+        velocity_time: f32,
+        reaction: FaultReaction,
+    ) -> Self {
+        Self {
+            position_window,
+            position_time,
+            velocity_window,
+            velocity_time,
+            reaction,
+        }
+    }
+
+    pub fn position_window(&self) -> f32 {
+        self.position_window
+    }
+    pub fn position_time(&self) -> f32 {
+        self.position_time
+    }
+    pub fn velocity_window(&self) -> f32 {
+        self.velocity_window
+    }
+    pub fn velocity_time(&self) -> f32 {
+        self.velocity_time
+    }
+    pub fn reaction(&self) -> FaultReaction {
+        self.reaction
+    }
+    pub fn set_position_window(&mut self, position_window: f32) {
+        self.position_window = position_window;
+    }
+    pub fn set_position_time(&mut self, position_time: f32) {
+        self.position_time = position_time;
+    }
+    pub fn set_velocity_window(&mut self, velocity_window: f32) {
+        self.velocity_window = velocity_window;
+    }
+    pub fn set_velocity_time(&mut self, velocity_time: f32) {
+        self.velocity_time = velocity_time;
+    }
+    pub fn set_reaction(&mut self, reaction: FaultReaction) {
+        self.reaction = reaction;
+    }
+}
+
+/// Detector of the following error of a single axis.
+/// The error trips when the position or the velocity error stays outside of its window for longer than its time.
+pub struct FollowingErrorMonitor {
+    period: f32,
+    /// Time the position error has been outside of the window in seconds.
+    position_time: f32,
+    /// Time the velocity error has been outside of the window in seconds.
+    velocity_time: f32,
+}
+
+impl FollowingErrorMonitor {
+    pub fn new(control_period: Microseconds) -> Self {
+        Self {
+            period: control_period.0 as f32 / 1_000_000.0,
+            position_time: 0.0,
+            velocity_time: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position_time = 0.0;
+        self.velocity_time = 0.0;
+    }
+
+    /// Returns true when the following error has tripped.
+    ///
+    /// # Arguments
+    /// * `position_error` - the position error in revolutions, `None` when the axis is not position controlled
+    /// * `velocity_error` - the velocity error in revolutions per second
+    /// * `settings` - the following error settings of the axis
+    pub fn sample(
+        &mut self,
+        position_error: Option<f32>,
+        velocity_error: f32,
+        settings: &FollowingErrorSettings,
+    ) -> bool {
+        self.position_time = match position_error {
+            Some(error) if Self::outside(error, settings.position_window) => {
+                self.position_time + self.period
+            }
+            _ => 0.0,
+        };
+        self.velocity_time = if Self::outside(velocity_error, settings.velocity_window) {
+            self.velocity_time + self.period
+        } else {
+            0.0
+        };
+
+        (self.position_time > 0.0 && self.position_time >= settings.position_time)
+            || (self.velocity_time > 0.0 && self.velocity_time >= settings.velocity_time)
+    }
+
+    fn outside(error: f32, window: f32) -> bool {
+        window > 0.0 && error.abs() > window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(10_000);
+
+    #[test]
+    fn position_error() {
+        let settings = FollowingErrorSettings::new(0.5, 0.045, 0.0, 0.0, FaultReaction::QuickStop);
+        let mut monitor = FollowingErrorMonitor::new(PERIOD);
+        assert!(!monitor.sample(Some(0.4), 100.0, &settings));
+        for _ in 0..4 {
+            assert!(!monitor.sample(Some(-0.6), 0.0, &settings));
+        }
+        // the error returned to the window, the time starts again
+        assert!(!monitor.sample(None, 0.0, &settings));
+        for _ in 0..4 {
+            assert!(!monitor.sample(Some(0.6), 0.0, &settings));
+        }
+        assert!(monitor.sample(Some(0.6), 0.0, &settings));
+    }
+
+    #[test]
+    fn velocity_error() {
+        let settings = FollowingErrorSettings::new(0.0, 0.0, 1.0, 0.0, FaultReaction::WarnOnly);
+        let mut monitor = FollowingErrorMonitor::new(PERIOD);
+        assert!(!monitor.sample(Some(10.0), 0.5, &settings));
+        assert!(monitor.sample(None, -1.5, &settings));
+    }
+}
@@ -0,0 +1,200 @@
+//! Electronic gearing of an axis to the other axis of the driver.
+//!
+//! The geared position of the slave axis is `numerator / denominator * master + offset` (in revolutions).
+//! When the gearing is engaged, the slave is usually away from the geared position and moves with a different velocity,
+//! so the deviation from the geared position is driven to zero by a move planned with the profile limits of the slave.
+//! The same happens when the gear ratio or the offset change, so the slave never jumps.
+use crate::models::GearingState;
+use crate::planner::{ProfileLimits, TrajectoryPlanner, TrajectoryPoint};
+use embedded_time::duration::Microseconds;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct GearingSettings {
+    numerator: i32,
+    denominator: i32,
+    offset: f32,
+}
+
+impl GearingSettings {
+    /// Creates new gearing settings.
+    ///
+    /// # Arguments
+    /// * `numerator` - the numerator of the gear ratio
+    /// * `denominator` - the denominator of the gear ratio, shall not be zero
+    /// * `offset` - the position of the slave axis when the master axis is in the zero position in revolutions
+    pub fn new(numerator: i32, denominator: i32, offset: f32) -> Self {
+        Self {
+            numerator,
+            denominator,
+            offset,
+        }
+    }
+
+    pub fn numerator(&self) -> i32 {
+        self.numerator
+    }
+    pub fn denominator(&self) -> i32 {
+        self.denominator
+    }
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+    pub fn set_numerator(&mut self, numerator: i32) {
+        self.numerator = numerator;
+    }
+    pub fn set_denominator(&mut self, denominator: i32) {
+        self.denominator = denominator;
+    }
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = offset;
+    }
+
+    /// Returns the gear ratio, zero when the denominator is zero.
+    pub fn ratio(&self) -> f32 {
+        if self.denominator == 0 {
+            0.0
+        } else {
+            self.numerator as f32 / self.denominator as f32
+        }
+    }
+}
+
+impl Default for GearingSettings {
+    fn default() -> Self {
+        Self::new(1, 1, 0.0)
+    }
+}
+
+/// Generator of the reference of a slave axis geared to a master axis.
+pub struct ElectronicGearing {
+    /// Planner of the deviation of the slave from the geared position.
+    planner: TrajectoryPlanner,
+    /// The settings the current engagement was planned for, `None` when disengaged.
+    settings: Option<GearingSettings>,
+    /// The last generated reference.
+    reference: TrajectoryPoint,
+}
+
+impl ElectronicGearing {
+    pub fn new(control_period: Microseconds) -> Self {
+        Self {
+            planner: TrajectoryPlanner::new(control_period),
+            settings: None,
+            reference: TrajectoryPoint::default(),
+        }
+    }
+
+    /// Disengages the gearing, the next sample starts a new engagement.
+    pub fn disengage(&mut self) {
+        self.settings = None;
+    }
+
+    /// Advances the gearing by a single period and returns the reference state of the slave axis.
+    ///
+    /// # Arguments
+    /// * `slave` - the actual state of the slave axis, used when the gearing is being engaged
+    /// * `master` - the actual state of the master axis
+    /// * `settings` - the gearing settings of the slave axis
+    /// * `limits` - the limits of the engagement moves
+    pub fn sample(
+        &mut self,
+        slave: TrajectoryPoint,
+        master: TrajectoryPoint,
+        settings: &GearingSettings,
+        limits: &ProfileLimits,
+    ) -> TrajectoryPoint {
+        let geared = Self::geared(&master, settings);
+        if self.settings != Some(*settings) {
+            // continue from the last reference when only the settings changed
+            let start = match self.settings {
+                Some(_) => self.reference,
+                None => slave,
+            };
+            self.planner.plan(
+                TrajectoryPoint {
+                    position: start.position - geared.position,
+                    velocity: start.velocity - geared.velocity,
+                    acceleration: 0.0,
+                },
+                0.0,
+                limits,
+            );
+            self.settings = Some(*settings);
+        }
+
+        let deviation = self.planner.sample();
+        self.reference = TrajectoryPoint {
+            position: geared.position + deviation.position,
+            velocity: geared.velocity + deviation.velocity,
+            acceleration: geared.acceleration + deviation.acceleration,
+        };
+        self.reference
+    }
+
+    pub fn state(&self) -> GearingState {
+        match self.settings {
+            None => GearingState::Disengaged,
+            Some(_) if self.planner.is_finished() => GearingState::Engaged,
+            Some(_) => GearingState::Engaging,
+        }
+    }
+
+    fn geared(master: &TrajectoryPoint, settings: &GearingSettings) -> TrajectoryPoint {
+        let ratio = settings.ratio();
+        TrajectoryPoint {
+            position: ratio * master.position + settings.offset,
+            velocity: ratio * master.velocity,
+            acceleration: ratio * master.acceleration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    fn state(position: f32, velocity: f32) -> TrajectoryPoint {
+        TrajectoryPoint {
+            position,
+            velocity,
+            acceleration: 0.0,
+        }
+    }
+
+    #[test]
+    fn ratio() {
+        assert_eq!(GearingSettings::new(3, 2, 0.0).ratio(), 1.5);
+        assert_eq!(GearingSettings::new(-1, 4, 0.0).ratio(), -0.25);
+        assert_eq!(GearingSettings::new(1, 0, 0.0).ratio(), 0.0);
+    }
+
+    #[test]
+    fn engagement() {
+        let settings = GearingSettings::new(1, 2, 1.0);
+        let limits = ProfileLimits::trapezoidal(2.0, 10.0, 10.0);
+        let mut gearing = ElectronicGearing::new(PERIOD);
+        assert_eq!(gearing.state(), GearingState::Disengaged);
+
+        let mut master = state(0.0, 1.0);
+        let mut reference = gearing.sample(state(0.0, 0.0), master, &settings, &limits);
+        assert_eq!(gearing.state(), GearingState::Engaging);
+        for _ in 0..5000 {
+            master.position += master.velocity * 0.001;
+            let next = gearing.sample(reference, master, &settings, &limits);
+            // the engagement is smooth
+            assert!((next.velocity - reference.velocity).abs() <= 10.0 * 0.001 + 1e-3);
+            reference = next;
+        }
+        assert_eq!(gearing.state(), GearingState::Engaged);
+        assert!((reference.position - (master.position / 2.0 + 1.0)).abs() < 1e-3);
+        assert!((reference.velocity - 0.5).abs() < 1e-3);
+
+        // the change of the ratio is ramped as well
+        let settings = GearingSettings::new(1, 1, 1.0);
+        let next = gearing.sample(reference, master, &settings, &limits);
+        assert_eq!(gearing.state(), GearingState::Engaging);
+        assert!((next.position - reference.position).abs() < 0.01);
+    }
+}
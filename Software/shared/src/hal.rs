@@ -8,12 +8,29 @@ pub trait StepGenerator {
     /// # Arguments
     /// * `frequency` - frequency of the output square wave signal
     fn set_step_frequency(&mut self, frequency: Hertz);
+
+    /// Returns the frequency that is actually generated in Hz,
+    /// which differs from the set frequency by the resolution of the generator.
+    fn step_frequency(&self) -> f32;
 }
 
 pub trait DACChannel {
     fn set_output_voltage(&mut self, voltage: u16);
 }
 
+/// This trait is an abstraction over a configuration pin of a driver, which is evaluated with the tristate detection.
+/// It is generally a GPIO switched between the push-pull output and the floating input.
+pub trait TristatePin {
+    /// Drives the pin to the ground.
+    fn set_low(&mut self);
+
+    /// Drives the pin to the supply of the IO.
+    fn set_high(&mut self);
+
+    /// Leaves the pin floating, so the driver detects it as open.
+    fn set_open(&mut self);
+}
+
 /// This trait is an abstraction over stepper drivers.
 /// Generally the drivers have two functions - generate steps and set output current.
 pub trait StepperDriver {
@@ -24,9 +41,94 @@ pub trait StepperDriver {
     /// * `frequency` - frequency of the output motor shaft in revolutions per second
     fn set_output_frequency(&mut self, frequency: f32);
 
+    /// Returns the frequency of the output motor shaft in revolutions per second, that is actually generated.
+    fn output_frequency(&self) -> f32;
+
+    /// Sets the number of microsteps per a full step, the driver has been configured with.
+    fn set_microsteps(&mut self, microsteps: u16);
+
+    /// Returns the number of microsteps per a single revolution of the motor.
+    fn microsteps_per_revolution(&self) -> u32;
+
     /// Sets the target current the driver shall drive the stepper motor with.
     ///
     /// # Arguments
     /// * `current` - the desired current in Amps
     fn set_current(&mut self, current: f32);
+
+    /// Returns the load of the motor measured by the driver, the StallGuard result of the Trinamic drivers.
+    /// Lower values mean a higher load, approaching zero when the motor stalls.
+    /// Returns `None` when the driver does not measure the load or the reading failed.
+    fn stall_guard_result(&mut self) -> Option<u16> {
+        None
+    }
+
+    /// Enables the output stage of the driver, so the motor is powered.
+    fn enable(&mut self) {}
+
+    /// Disables the output stage of the driver, the motor is not powered and turns freely.
+    fn disable(&mut self) {}
+
+    /// Returns true when the driver reports a fault, e.g. the overtemperature or a short circuit of the outputs.
+    /// The driver shuts down on the fault until it is disabled and enabled again.
+    fn is_faulted(&mut self) -> bool {
+        false
+    }
+}
+
+/// This trait is an abstraction over a counter of the pulses of a quadrature encoder,
+/// generally a timer in the encoder mode.
+pub trait QuadratureCounter {
+    /// Returns the current value of the counter, which counts every edge of both channels
+    /// and wraps around in both directions.
+    fn count(&self) -> u16;
+
+    /// Returns the value of the counter captured on the last index pulse,
+    /// `None` when there was no index pulse since the last call.
+    fn take_index(&mut self) -> Option<u16>;
+}
+
+/// This trait is an abstraction over the access to the registers of an encoder, generally over SPI.
+pub trait EncoderRegisters {
+    type Error;
+
+    /// Sends the `frame` to the encoder and returns the frame received during the same transfer.
+    fn transfer(&mut self, frame: u16) -> Result<u16, Self::Error>;
+}
+
+/// This trait is an abstraction over the single wire UART of the Trinamic drivers, generally a half-duplex USART.
+pub trait TrinamicUart {
+    type Error;
+
+    /// Sends the `datagram` to the driver. The echo of the datagram received on the single wire shall be discarded.
+    fn write(&mut self, datagram: &[u8]) -> Result<(), Self::Error>;
+
+    /// Receives the reply of the driver, the whole `buffer` is filled.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// This trait is an abstraction over the input used as the reference during homing,
+/// generally a limit switch or a home switch.
+pub trait HomeSwitch {
+    /// Returns true when the switch is triggered.
+    fn is_active(&self) -> bool;
+
+    /// Returns false when there is no switch connected to the axis,
+    /// in which case only the homing methods that do not require a switch can be performed.
+    fn is_present(&self) -> bool {
+        true
+    }
+}
+
+/// Placeholder for axes without a home switch.
+pub struct NoHomeSwitch;
+
+impl HomeSwitch for NoHomeSwitch {
+    fn is_active(&self) -> bool {
+        false
+    }
+
+    fn is_present(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,328 @@
+use crate::models::{HomingMethod, HomingState};
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+#[derive(Copy, Clone, Default)]
+pub struct HomingSettings {
+    method: HomingMethod,
+    search_velocity: f32,
+    latch_velocity: f32,
+    backoff: f32,
+    offset: f32,
+}
+
+impl HomingSettings {
+    /// Creates new homing settings.
+    ///
+    /// # Arguments
+    /// * `method` - the homing sequence
+    /// * `search_velocity` - the velocity used to find the home switch or the mechanical stop in revolutions per second
+    /// * `latch_velocity` - the slow velocity used to latch the edge of the home switch in revolutions per second
+    /// * `backoff` - the distance the axis moves away from the switch before the latching in revolutions
+    /// * `offset` - the distance of the zero position from the home position in revolutions
+    pub fn new(
+        method: HomingMethod,
+        search_velocity: f32,
+        latch_velocity: f32,
+        backoff: f32,
+        offset: f32,
+    ) -> Self {
+        Self {
+            method,
+            search_velocity,
+            latch_velocity,
+            backoff,
+            offset,
+        }
+    }
+
+    pub fn method(&self) -> HomingMethod {
+        self.method
+    }
+    pub fn search_velocity(&self) -> f32 {
+        self.search_velocity
+    }
+    pub fn latch_velocity(&self) -> f32 {
+        self.latch_velocity
+    }
+    pub fn backoff(&self) -> f32 {
+        self.backoff
+    }
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+    pub fn set_method(&mut self, method: HomingMethod) {
+        self.method = method;
+    }
+    pub fn set_search_velocity(&mut self, search_velocity: f32) {
+        self.search_velocity = search_velocity;
+    }
+    pub fn set_latch_velocity(&mut self, latch_velocity: f32) {
+        self.latch_velocity = latch_velocity;
+    }
+    pub fn set_backoff(&mut self, backoff: f32) {
+        self.backoff = backoff;
+    }
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = offset;
+    }
+}
+
+/// The action requested by the homing sequence from the motion controller.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HomingCommand {
+    /// Move with the velocity in revolutions per second.
+    Velocity(f32),
+    /// Move to the position in revolutions.
+    Position(f32),
+    /// The axis is in the zero position, the encoder position shall be reset.
+    SetHome,
+    /// Stand still.
+    Hold,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Phase {
+    Idle,
+    /// Moving towards the switch or the mechanical stop with the search velocity.
+    Search,
+    /// Moving away from the switch, `released_at` is the position where the switch was released.
+    Backoff {
+        released_at: Option<f32>,
+    },
+    /// Slowly approaching the switch to find its edge.
+    Latch,
+    MoveToZero {
+        target: f32,
+    },
+    Attained,
+    Error,
+}
+
+/// The homing sequence of a single axis.
+///
+/// The switch based methods search for the switch with the search velocity, back off from it by the backoff distance
+/// and then latch the edge of the switch with the latch velocity. The edge is the home position.
+/// The sensorless methods search for the mechanical stop with the search velocity, the position where the motor
+/// stalls is the home position. The stall is not detected at the low velocities, so there is no latching.
+/// Finally the axis moves by the home offset and the position there is used as the new zero.
+pub struct Homing {
+    phase: Phase,
+}
+
+impl Homing {
+    pub fn new() -> Self {
+        Self { phase: Phase::Idle }
+    }
+
+    /// Starts the homing sequence from the `position` (in revolutions).
+    /// The `switch_present` is false when the home trigger of the method is not available,
+    /// i.e. the home switch for the switch based methods and the stall detection for the sensorless methods.
+    pub fn start(&mut self, switch_present: bool, position: f32, settings: &HomingSettings) {
+        self.phase = match settings.method {
+            HomingMethod::CurrentPosition => Phase::MoveToZero {
+                target: position + settings.offset,
+            },
+            _ if !switch_present => Phase::Error,
+            _ => Phase::Search,
+        };
+    }
+
+    /// Stops the sequence. When the sequence was not finished, it ends with an error.
+    pub fn abort(&mut self) {
+        if self.state() == HomingState::InProgress {
+            self.phase = Phase::Error;
+        }
+    }
+
+    /// Advances the homing sequence.
+    ///
+    /// # Arguments
+    /// * `switch_active` - the state of the home trigger, the home switch or the stall of the motor
+    /// * `position` - the actual position of the axis in revolutions
+    /// * `move_finished` - true when the axis has finished the last requested [HomingCommand::Position] move
+    /// * `settings` - the homing settings of the axis
+    pub fn sample(
+        &mut self,
+        switch_active: bool,
+        position: f32,
+        move_finished: bool,
+        settings: &HomingSettings,
+    ) -> HomingCommand {
+        let direction = match settings.method {
+            HomingMethod::NegativeLimitSwitch | HomingMethod::NegativeStall => -1.0,
+            _ => 1.0,
+        };
+        let search_velocity = direction * settings.search_velocity.abs();
+        let latch_velocity = direction * settings.latch_velocity.abs();
+
+        if self.phase == Phase::Search && switch_active {
+            self.phase = if settings.method.is_sensorless() {
+                Phase::MoveToZero {
+                    target: position + settings.offset,
+                }
+            } else {
+                Phase::Backoff { released_at: None }
+            };
+        }
+        if let Phase::Backoff { released_at } = self.phase {
+            match released_at {
+                None if !switch_active => {
+                    self.phase = Phase::Backoff {
+                        released_at: Some(position),
+                    }
+                }
+                Some(released_at) if (position - released_at).abs() >= settings.backoff.abs() => {
+                    self.phase = Phase::Latch
+                }
+                _ => {}
+            }
+        }
+        if self.phase == Phase::Latch && switch_active {
+            self.phase = Phase::MoveToZero {
+                target: position + settings.offset,
+            };
+        }
+        if let Phase::MoveToZero { .. } = self.phase {
+            if move_finished {
+                self.phase = Phase::Attained;
+                return HomingCommand::SetHome;
+            }
+        }
+
+        match self.phase {
+            Phase::Search => HomingCommand::Velocity(search_velocity),
+            Phase::Backoff { .. } => HomingCommand::Velocity(-latch_velocity),
+            Phase::Latch => HomingCommand::Velocity(latch_velocity),
+            Phase::MoveToZero { target } => HomingCommand::Position(target),
+            Phase::Idle | Phase::Attained | Phase::Error => HomingCommand::Hold,
+        }
+    }
+
+    pub fn state(&self) -> HomingState {
+        match self.phase {
+            Phase::Idle => HomingState::Idle,
+            Phase::Attained => HomingState::Attained,
+            Phase::Error => HomingState::Error,
+            _ => HomingState::InProgress,
+        }
+    }
+}
+
+impl Default for Homing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(method: HomingMethod) -> HomingSettings {
+        HomingSettings::new(method, 1.0, 0.1, 0.5, 2.0)
+    }
+
+    #[test]
+    fn switch_sequence() {
+        let settings = settings(HomingMethod::NegativeLimitSwitch);
+        let mut homing = Homing::new();
+        homing.start(true, 0.0, &settings);
+        assert_eq!(homing.state(), HomingState::InProgress);
+
+        assert_eq!(
+            homing.sample(false, 0.0, false, &settings),
+            HomingCommand::Velocity(-1.0)
+        );
+        // switch found, backing off until it is released and the backoff distance is travelled
+        assert_eq!(
+            homing.sample(true, -3.0, false, &settings),
+            HomingCommand::Velocity(0.1)
+        );
+        assert_eq!(
+            homing.sample(false, -2.9, false, &settings),
+            HomingCommand::Velocity(0.1)
+        );
+        assert_eq!(
+            homing.sample(false, -2.3, false, &settings),
+            HomingCommand::Velocity(-0.1)
+        );
+        // latched the edge of the switch
+        assert_eq!(
+            homing.sample(true, -3.0, false, &settings),
+            HomingCommand::Position(-1.0)
+        );
+        assert_eq!(
+            homing.sample(false, -1.5, false, &settings),
+            HomingCommand::Position(-1.0)
+        );
+        assert_eq!(
+            homing.sample(false, -1.0, true, &settings),
+            HomingCommand::SetHome
+        );
+        assert_eq!(homing.state(), HomingState::Attained);
+        assert_eq!(
+            homing.sample(false, 0.0, true, &settings),
+            HomingCommand::Hold
+        );
+    }
+
+    #[test]
+    fn current_position() {
+        let settings = settings(HomingMethod::CurrentPosition);
+        let mut homing = Homing::new();
+        homing.start(false, 1.0, &settings);
+        assert_eq!(
+            homing.sample(false, 1.0, false, &settings),
+            HomingCommand::Position(3.0)
+        );
+        assert_eq!(
+            homing.sample(false, 3.0, true, &settings),
+            HomingCommand::SetHome
+        );
+    }
+
+    #[test]
+    fn sensorless() {
+        let settings = settings(HomingMethod::NegativeStall);
+        let mut homing = Homing::new();
+        homing.start(false, 0.0, &settings);
+        assert_eq!(homing.state(), HomingState::Error);
+
+        homing.start(true, 0.0, &settings);
+        assert_eq!(
+            homing.sample(false, 0.0, false, &settings),
+            HomingCommand::Velocity(-1.0)
+        );
+        // the motor stalled against the mechanical stop
+        assert_eq!(
+            homing.sample(true, -4.0, false, &settings),
+            HomingCommand::Position(-2.0)
+        );
+        assert_eq!(
+            homing.sample(false, -2.0, true, &settings),
+            HomingCommand::SetHome
+        );
+        assert_eq!(homing.state(), HomingState::Attained);
+    }
+
+    #[test]
+    fn missing_switch_and_abort() {
+        let settings = settings(HomingMethod::PositiveLimitSwitch);
+        let mut homing = Homing::new();
+        homing.start(false, 0.0, &settings);
+        assert_eq!(homing.state(), HomingState::Error);
+
+        homing.start(true, 0.0, &settings);
+        assert_eq!(
+            homing.sample(false, 0.0, false, &settings),
+            HomingCommand::Velocity(1.0)
+        );
+        homing.abort();
+        assert_eq!(homing.state(), HomingState::Error);
+        assert_eq!(
+            homing.sample(true, 0.0, false, &settings),
+            HomingCommand::Hold
+        );
+    }
+}
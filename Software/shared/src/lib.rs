@@ -2,29 +2,86 @@
 //!
 //! This shared library contains the abstractions for motor control and common data structures
 //! for interfacing with the control software.
+//!
+//! The `std` feature enables the simulated stepper motor in the `sim` module,
+//! which allows testing the motion control on the host.
 
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
+mod as5047;
+mod autotune;
+mod backlash;
 mod canopen;
+mod coordinated;
+mod current;
+mod differential_drive;
 mod encoder;
+mod fault;
+mod following_error;
+mod gearing;
 mod hal;
+mod homing;
+mod limits;
 mod models;
 mod motion_controller;
+mod planner;
 mod psd;
+mod pvt;
+mod quadrature;
 mod ramp;
+#[cfg(any(test, feature = "std"))]
+mod sim;
+mod stall;
+mod step_loss;
 mod tmc2100;
+mod tmc2209;
+mod trinamic;
+mod units;
 mod usb_protocol;
+mod velocity_estimator;
 
 pub mod prelude {
+    pub use crate::as5047::{As5047Encoder, As5047Error, As5047Register, AS5047_RESOLUTION};
+    pub use crate::autotune::{AutoTuneSettings, RelayAutoTuner, TuningResult};
+    pub use crate::backlash::BacklashCompensation;
     pub use crate::canopen::*;
+    pub use crate::coordinated::CoordinatedMotion;
+    pub use crate::differential_drive::{DifferentialDrive, Pose};
     pub use crate::encoder::*;
+    pub use crate::fault::{monitor_supply, raise_fault};
+    pub use crate::gearing::ElectronicGearing;
     pub use crate::hal::*;
-    pub use crate::models::{Axis, AxisMode, Position, Velocity};
+    pub use crate::homing::Homing;
+    pub use crate::limits::PositionLimits;
+    pub use crate::models::{
+        Axis, AxisMode, ErrorCode, FaultReaction, GearingState, HomingMethod, HomingState,
+        LimitViolation, PdoUnits, Position, PvtState, RampProfile, StepLossReaction, TuningLoop,
+        TuningRule, TuningState, Velocity, VelocityFilter,
+    };
     pub use crate::motion_controller::AxisMotionController;
+    pub use crate::planner::{ProfileLimits, TrajectoryPlanner, TrajectoryPoint};
     pub use crate::psd::PSDController;
-    pub use crate::ramp::TrapRampGen;
-    pub use crate::tmc2100::TMC2100;
+    pub use crate::pvt::{PvtInterpolator, PvtPoint, PvtQueue, PVT_QUEUE_CAPACITY};
+    pub use crate::quadrature::QuadratureEncoder;
+    pub use crate::ramp::{SCurveRampGen, TrapRampGen};
+    #[cfg(any(test, feature = "std"))]
+    pub use crate::sim::{
+        LoadParameters, MotorParameters, SimulatedDriver, SimulatedEncoder, StepperSimulation,
+    };
+    pub use crate::stall::StallDetector;
+    pub use crate::step_loss::StepLossMonitor;
+    pub use crate::tmc2100::{TMC2100, TMC2100_MICROSTEPS};
+    pub use crate::tmc2209::TMC2209;
+    pub use crate::trinamic::{
+        crc8, parse_reply, read_request, write_datagram, ChopperConfig, DriverStatus, GlobalConfig,
+        GlobalStatus, HoldRunCurrent, InvalidMicrosteps, TrinamicError, TrinamicRegister,
+        DATAGRAM_LENGTH, READ_REQUEST_LENGTH,
+    };
+    pub use crate::units::{from_fixed_point, to_fixed_point, UnitScaling, FIXED_POINT_SCALE};
     pub use crate::usb_protocol::*;
+    pub use crate::velocity_estimator::{
+        FilteredEncoder, VelocityEstimator, VelocityEstimatorSettings, MAX_VELOCITY_WINDOW,
+    };
     pub use crate::OnError;
 }
 
@@ -0,0 +1,186 @@
+use crate::models::LimitViolation;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// Software limits of the position of an axis.
+#[derive(Copy, Clone, Default)]
+pub struct PositionLimits {
+    enabled: bool,
+    min: f32,
+    max: f32,
+    tolerance: f32,
+}
+
+impl PositionLimits {
+    /// Creates new position limits.
+    ///
+    /// # Arguments
+    /// * `enabled` - the limits are enforced only when enabled
+    /// * `min` - the minimal allowed position in revolutions
+    /// * `max` - the maximal allowed position in revolutions
+    /// * `tolerance` - the distance in revolutions the axis may get beyond the limits before they are violated
+    pub fn new(enabled: bool, min: f32, max: f32, tolerance: f32) -> Self {
+        Self {
+            enabled,
+            min,
+            max,
+            tolerance,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    pub fn set_min(&mut self, min: f32) {
+        self.min = min;
+    }
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max;
+    }
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Returns the limit the actual `position` is beyond by more than the tolerance.
+    /// The axis stopping at the limit may get slightly past it, which is not a violation.
+    pub fn check_position(&self, position: f32) -> LimitViolation {
+        if !self.enabled {
+            LimitViolation::None
+        } else if position < self.min - self.tolerance {
+            LimitViolation::Min
+        } else if position > self.max + self.tolerance {
+            LimitViolation::Max
+        } else {
+            LimitViolation::None
+        }
+    }
+
+    /// Limits the target position to the allowed range.
+    /// Returns the limited position and the limit that was violated by the original target.
+    pub fn limit_position(&self, target: f32) -> (f32, LimitViolation) {
+        if !self.enabled {
+            (target, LimitViolation::None)
+        } else if target < self.min {
+            (self.min, LimitViolation::Min)
+        } else if target > self.max {
+            (self.max, LimitViolation::Max)
+        } else {
+            (target, LimitViolation::None)
+        }
+    }
+
+    /// Limits the target velocity, so that the axis decelerating with the `deceleration`
+    /// stops at the limit in the direction of the movement.
+    /// The axis keeps its velocity for the `lag` before it starts to decelerate,
+    /// so it brakes early by the distance covered meanwhile.
+    /// Returns the limited velocity and the limit that caused the limitation.
+    ///
+    /// # Arguments
+    /// * `target` - the target velocity in revolutions per second
+    /// * `position` - the actual position in revolutions
+    /// * `velocity` - the velocity the axis moves with in revolutions per second
+    /// * `deceleration` - the deceleration of the axis in revolutions per second squared
+    /// * `lag` - the time in seconds before the axis starts to decelerate
+    pub fn limit_velocity(
+        &self,
+        target: f32,
+        position: f32,
+        velocity: f32,
+        deceleration: f32,
+        lag: f32,
+    ) -> (f32, LimitViolation) {
+        if !self.enabled {
+            return (target, LimitViolation::None);
+        }
+        let (distance, violation) = if target > 0.0 {
+            (self.max - position, LimitViolation::Max)
+        } else {
+            (position - self.min, LimitViolation::Min)
+        };
+        // both the actual and the allowed velocity are kept for the lag, the allowed velocity v
+        // satisfies v * lag + v^2 / (2 * deceleration) = distance - velocity * lag
+        let deceleration = deceleration.abs();
+        let lag = lag.max(0.0);
+        let braking_distance = (distance - velocity.abs() * lag).max(0.0);
+        let lag_velocity = deceleration * lag;
+        let allowed = (lag_velocity * lag_velocity + 2.0 * deceleration * braking_distance).sqrt()
+            - lag_velocity;
+        if target.abs() > allowed {
+            (target.signum() * allowed, violation)
+        } else {
+            (target, LimitViolation::None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_limits() {
+        let limits = PositionLimits::new(true, -1.0, 2.0, 0.0);
+        assert_eq!(limits.limit_position(1.0), (1.0, LimitViolation::None));
+        assert_eq!(limits.limit_position(-3.0), (-1.0, LimitViolation::Min));
+        assert_eq!(limits.limit_position(5.0), (2.0, LimitViolation::Max));
+
+        let limits = PositionLimits::new(false, -1.0, 2.0, 0.0);
+        assert_eq!(limits.limit_position(5.0), (5.0, LimitViolation::None));
+    }
+
+    #[test]
+    fn position_tolerance() {
+        let limits = PositionLimits::new(true, -1.0, 2.0, 0.1);
+        assert_eq!(limits.check_position(2.05), LimitViolation::None);
+        assert_eq!(limits.check_position(-1.05), LimitViolation::None);
+        assert_eq!(limits.check_position(2.2), LimitViolation::Max);
+        assert_eq!(limits.check_position(-1.2), LimitViolation::Min);
+
+        let limits = PositionLimits::new(false, -1.0, 2.0, 0.1);
+        assert_eq!(limits.check_position(5.0), LimitViolation::None);
+    }
+
+    #[test]
+    fn velocity_limits() {
+        let limits = PositionLimits::new(true, -1.0, 2.0, 0.0);
+        // far from the limit
+        assert_eq!(
+            limits.limit_velocity(1.0, 0.0, 0.0, 2.0, 0.0),
+            (1.0, LimitViolation::None)
+        );
+        // decelerating towards the max limit
+        assert_eq!(
+            limits.limit_velocity(3.0, 1.0, 0.0, 2.0, 0.0),
+            (2.0, LimitViolation::Max)
+        );
+        // at the limit only the movement away from it is allowed
+        assert_eq!(
+            limits.limit_velocity(1.0, 2.5, 0.0, 2.0, 0.0),
+            (0.0, LimitViolation::Max)
+        );
+        assert_eq!(
+            limits.limit_velocity(-1.0, 2.5, 0.0, 2.0, 0.0),
+            (-1.0, LimitViolation::None)
+        );
+        assert_eq!(
+            limits.limit_velocity(-1.0, -1.0, 0.0, 2.0, 0.0),
+            (0.0, LimitViolation::Min)
+        );
+        // braking early for the lag
+        let (velocity, violation) = limits.limit_velocity(3.0, 1.0, 2.0, 2.0, 0.1);
+        assert!((velocity - 1.6).abs() < 1e-5);
+        assert_eq!(violation, LimitViolation::Max);
+    }
+}
@@ -6,6 +6,8 @@ mod velocity;
 pub use position::Position;
 pub use velocity::Velocity;
 
+use core::convert::TryFrom;
+
 #[derive(Copy, Clone)]
 pub enum Axis {
     Axis1,
@@ -21,13 +23,25 @@ impl Axis {
     }
 }
 
-/// `AxisMode` enum represents the control mode of an axis - either velocity control or position control
+/// `AxisMode` enum represents the control mode of an axis - velocity control, position control, homing,
+/// interpolated position control, electronic gearing or auto-tuning.
 /// In raw data, the [Self::Velocity] variant is represented as a zero and the [Self::Position] variant is represented as 1.
+/// The third lowest bit (0x04) selects the extended modes, which are distinguished by the two lowest bits -
+/// [Self::Homing] is represented as 0x04, [Self::Interpolated] as 0x05, [Self::Gearing] as 0x06
+/// and [Self::AutoTune] as 0x07.
 /// The variant [Self::Velocity] is the default.
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
 pub enum AxisMode {
     Velocity,
     Position,
+    /// The axis searches for its reference position using the configured homing method.
+    Homing,
+    /// The axis follows the position-velocity-time points pushed to its queue.
+    Interpolated,
+    /// The axis follows the actual position of the other axis with the configured gear ratio.
+    Gearing,
+    /// The axis performs the relay feedback experiment to tune the gains of one of its controllers.
+    AutoTune,
 }
 
 /// By default, the driver's axis mode shall be [Self::Velocity].
@@ -37,9 +51,17 @@ impl Default for AxisMode {
     }
 }
 
-/// Used to implement `AxisMode` deserialization from a single bit (the lowest bit in a byte).
+/// Used to implement `AxisMode` deserialization from the lowest three bits in a byte.
 impl From<u8> for AxisMode {
     fn from(raw: u8) -> Self {
+        if raw & 0x04 > 0 {
+            return match raw & 0x03 {
+                0x01 => AxisMode::Interpolated,
+                0x02 => AxisMode::Gearing,
+                0x03 => AxisMode::AutoTune,
+                _ => AxisMode::Homing,
+            };
+        }
         match raw & 0x01 {
             1 => AxisMode::Position,
             _ => AxisMode::Velocity,
@@ -47,19 +69,505 @@ impl From<u8> for AxisMode {
     }
 }
 
-/// Used for serialization as a single bit (the lowest bit in a byte).
+/// Used for serialization into the lowest three bits of a byte.
 impl From<AxisMode> for u8 {
     fn from(raw: AxisMode) -> Self {
         match raw {
             AxisMode::Velocity => 0x00,
             AxisMode::Position => 0x01,
+            AxisMode::Homing => 0x04,
+            AxisMode::Interpolated => 0x05,
+            AxisMode::Gearing => 0x06,
+            AxisMode::AutoTune => 0x07,
+        }
+    }
+}
+
+/// `RampProfile` enum represents the shape of the velocity ramps generated for an axis.
+/// In raw data, the [Self::Trapezoidal] variant is represented as a zero and the [Self::SCurve] variant is represented as 1.
+/// The variant [Self::Trapezoidal] is the default.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum RampProfile {
+    /// Only the acceleration is limited.
+    #[default]
+    Trapezoidal,
+    /// Both the acceleration and the jerk are limited.
+    SCurve,
+}
+
+/// Used to implement `RampProfile` deserialization from a single bit (the lowest bit in a byte).
+impl From<u8> for RampProfile {
+    fn from(raw: u8) -> Self {
+        match raw & 0x01 {
+            1 => RampProfile::SCurve,
+            _ => RampProfile::Trapezoidal,
+        }
+    }
+}
+
+/// Used for serialization as a single bit (the lowest bit in a byte).
+impl From<RampProfile> for u8 {
+    fn from(raw: RampProfile) -> Self {
+        match raw {
+            RampProfile::Trapezoidal => 0x00,
+            RampProfile::SCurve => 0x01,
+        }
+    }
+}
+
+/// `HomingMethod` enum represents the sequence used to find the reference position of an axis.
+/// The raw values correspond to the homing method numbers defined in CiA 402,
+/// the sensorless methods use the manufacturer specific negative numbers in the two's complement.
+/// By default, the current position is used as the home position, as it does not require a home switch.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum HomingMethod {
+    /// Homing on the home switch in the negative direction (method 17).
+    NegativeLimitSwitch,
+    /// Homing on the home switch in the positive direction (method 18).
+    PositiveLimitSwitch,
+    /// The current position is used as the home position (method 37).
+    #[default]
+    CurrentPosition,
+    /// Homing on the stall of the motor against the mechanical stop in the negative direction (method -1).
+    NegativeStall,
+    /// Homing on the stall of the motor against the mechanical stop in the positive direction (method -2).
+    PositiveStall,
+}
+
+impl HomingMethod {
+    /// Returns true when the stall of the motor is used as the home trigger instead of the home switch.
+    pub fn is_sensorless(&self) -> bool {
+        matches!(
+            self,
+            HomingMethod::NegativeStall | HomingMethod::PositiveStall
+        )
+    }
+}
+
+impl TryFrom<u8> for HomingMethod {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            17 => Ok(HomingMethod::NegativeLimitSwitch),
+            18 => Ok(HomingMethod::PositiveLimitSwitch),
+            37 => Ok(HomingMethod::CurrentPosition),
+            0xff => Ok(HomingMethod::NegativeStall),
+            0xfe => Ok(HomingMethod::PositiveStall),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<HomingMethod> for u8 {
+    fn from(raw: HomingMethod) -> Self {
+        match raw {
+            HomingMethod::NegativeLimitSwitch => 17,
+            HomingMethod::PositiveLimitSwitch => 18,
+            HomingMethod::CurrentPosition => 37,
+            HomingMethod::NegativeStall => 0xff,
+            HomingMethod::PositiveStall => 0xfe,
+        }
+    }
+}
+
+/// `HomingState` enum represents the progress of the homing of an axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum HomingState {
+    /// The axis has not been homed yet.
+    #[default]
+    Idle,
+    InProgress,
+    /// The home position was found and the position of the axis is referenced to it.
+    Attained,
+    /// The homing was aborted or could not be performed.
+    Error,
+}
+
+impl From<HomingState> for u8 {
+    fn from(raw: HomingState) -> Self {
+        match raw {
+            HomingState::Idle => 0x00,
+            HomingState::InProgress => 0x01,
+            HomingState::Attained => 0x02,
+            HomingState::Error => 0x03,
+        }
+    }
+}
+
+/// `LimitViolation` enum represents the software position limit that restricted the last command of an axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum LimitViolation {
+    #[default]
+    None,
+    /// The axis was commanded below the minimal position.
+    Min,
+    /// The axis was commanded above the maximal position.
+    Max,
+}
+
+impl From<LimitViolation> for u8 {
+    fn from(raw: LimitViolation) -> Self {
+        match raw {
+            LimitViolation::None => 0x00,
+            LimitViolation::Min => 0x01,
+            LimitViolation::Max => 0x02,
+        }
+    }
+}
+
+/// `PvtState` enum represents the state of the interpolated motion of an axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum PvtState {
+    /// No points were executed since the interpolated mode was entered.
+    #[default]
+    Idle,
+    /// The axis is moving between the points.
+    Running,
+    /// All the points were executed and the axis stopped in the last one.
+    Empty,
+    /// The queue ran out of points while the axis was moving, the axis stopped in the last point.
+    Underrun,
+}
+
+impl From<PvtState> for u8 {
+    fn from(raw: PvtState) -> Self {
+        match raw {
+            PvtState::Idle => 0x00,
+            PvtState::Running => 0x01,
+            PvtState::Empty => 0x02,
+            PvtState::Underrun => 0x03,
+        }
+    }
+}
+
+/// `GearingState` enum represents the coupling of an axis in the gearing mode to its master axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum GearingState {
+    #[default]
+    Disengaged,
+    /// The axis is approaching the geared position.
+    Engaging,
+    /// The axis follows the master axis with the gear ratio.
+    Engaged,
+}
+
+impl From<GearingState> for u8 {
+    fn from(raw: GearingState) -> Self {
+        match raw {
+            GearingState::Disengaged => 0x00,
+            GearingState::Engaging => 0x01,
+            GearingState::Engaged => 0x02,
+        }
+    }
+}
+
+/// `FaultReaction` enum represents the reaction of an axis to a fault.
+/// By default, the axis stops when a fault occurs.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum FaultReaction {
+    /// The fault is only reported.
+    WarnOnly,
+    /// The axis decelerates to a stop and holds until the fault is cleared.
+    #[default]
+    QuickStop,
+    /// The axis is disabled.
+    Disable,
+}
+
+impl TryFrom<u8> for FaultReaction {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(FaultReaction::WarnOnly),
+            0x01 => Ok(FaultReaction::QuickStop),
+            0x02 => Ok(FaultReaction::Disable),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<FaultReaction> for u8 {
+    fn from(raw: FaultReaction) -> Self {
+        match raw {
+            FaultReaction::WarnOnly => 0x00,
+            FaultReaction::QuickStop => 0x01,
+            FaultReaction::Disable => 0x02,
+        }
+    }
+}
+
+/// `ErrorCode` enum represents the cause of a fault of an axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum ErrorCode {
+    /// There is no fault.
+    #[default]
+    None,
+    /// The error output of the stepper motor driver is active.
+    DriverError,
+    /// The following error has tripped.
+    FollowingError,
+    /// The temperature of the driver exceeded the allowed maximum.
+    OverTemperature,
+    /// The motor supply voltage dropped below the allowed minimum.
+    Undervoltage,
+    /// The axis left the range of the software position limits.
+    LimitHit,
+    /// The commands from the master stopped coming.
+    CommunicationLoss,
+    /// The motor deviated from the commanded position, see [crate::prelude::StepLossMonitor].
+    StepLoss,
+    /// The motor stalled, see [crate::prelude::StallDetector].
+    Stall,
+}
+
+impl TryFrom<u8> for ErrorCode {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(ErrorCode::None),
+            0x01 => Ok(ErrorCode::DriverError),
+            0x02 => Ok(ErrorCode::FollowingError),
+            0x03 => Ok(ErrorCode::OverTemperature),
+            0x04 => Ok(ErrorCode::Undervoltage),
+            0x05 => Ok(ErrorCode::LimitHit),
+            0x06 => Ok(ErrorCode::CommunicationLoss),
+            0x07 => Ok(ErrorCode::StepLoss),
+            0x08 => Ok(ErrorCode::Stall),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(raw: ErrorCode) -> Self {
+        match raw {
+            ErrorCode::None => 0x00,
+            ErrorCode::DriverError => 0x01,
+            ErrorCode::FollowingError => 0x02,
+            ErrorCode::OverTemperature => 0x03,
+            ErrorCode::Undervoltage => 0x04,
+            ErrorCode::LimitHit => 0x05,
+            ErrorCode::CommunicationLoss => 0x06,
+            ErrorCode::StepLoss => 0x07,
+            ErrorCode::Stall => 0x08,
+        }
+    }
+}
+
+/// `PdoUnits` enum represents the units of the velocity and position PDOs.
+/// By default, the PDOs use the native units.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum PdoUnits {
+    /// The velocities in revolutions per second and the positions as revolutions and angle.
+    #[default]
+    Native,
+    /// The velocities and positions in user units as `f32`.
+    Float,
+    /// The velocities and positions in thousandths of the user units as `i32`.
+    FixedPoint,
+}
+
+impl TryFrom<u8> for PdoUnits {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(PdoUnits::Native),
+            0x01 => Ok(PdoUnits::Float),
+            0x02 => Ok(PdoUnits::FixedPoint),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<PdoUnits> for u8 {
+    fn from(raw: PdoUnits) -> Self {
+        match raw {
+            PdoUnits::Native => 0x00,
+            PdoUnits::Float => 0x01,
+            PdoUnits::FixedPoint => 0x02,
+        }
+    }
+}
+
+/// `VelocityFilter` enum represents the filter applied to the velocity measured by the encoder.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum VelocityFilter {
+    /// The difference of two consecutive positions.
+    #[default]
+    Difference,
+    /// The moving average of the differences.
+    MovingAverage,
+    /// The first-order low-pass filter of the differences.
+    LowPass,
+    /// The tracking observer of the position and the velocity.
+    Observer,
+}
+
+impl TryFrom<u8> for VelocityFilter {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(VelocityFilter::Difference),
+            0x01 => Ok(VelocityFilter::MovingAverage),
+            0x02 => Ok(VelocityFilter::LowPass),
+            0x03 => Ok(VelocityFilter::Observer),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<VelocityFilter> for u8 {
+    fn from(raw: VelocityFilter) -> Self {
+        match raw {
+            VelocityFilter::Difference => 0x00,
+            VelocityFilter::MovingAverage => 0x01,
+            VelocityFilter::LowPass => 0x02,
+            VelocityFilter::Observer => 0x03,
+        }
+    }
+}
+
+/// `StepLossReaction` enum represents the reaction to the steps lost by the motor.
+/// By default, the lost steps stop the axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum StepLossReaction {
+    /// The deviation is reported only.
+    WarnOnly,
+    /// The commanded position is corrected to the measured position and the axis continues.
+    Resynchronize,
+    /// The [ErrorCode::StepLoss] fault is latched.
+    #[default]
+    Fault,
+}
+
+impl TryFrom<u8> for StepLossReaction {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(StepLossReaction::WarnOnly),
+            0x01 => Ok(StepLossReaction::Resynchronize),
+            0x02 => Ok(StepLossReaction::Fault),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<StepLossReaction> for u8 {
+    fn from(raw: StepLossReaction) -> Self {
+        match raw {
+            StepLossReaction::WarnOnly => 0x00,
+            StepLossReaction::Resynchronize => 0x01,
+            StepLossReaction::Fault => 0x02,
+        }
+    }
+}
+
+/// `TuningLoop` enum represents the controller of an axis that is tuned by the auto-tuning.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum TuningLoop {
+    /// The relay drives the velocity action and the actual velocity is measured.
+    #[default]
+    Velocity,
+    /// The relay drives the target velocity and the actual position is measured.
+    Position,
+}
+
+impl TryFrom<u8> for TuningLoop {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(TuningLoop::Velocity),
+            0x01 => Ok(TuningLoop::Position),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<TuningLoop> for u8 {
+    fn from(raw: TuningLoop) -> Self {
+        match raw {
+            TuningLoop::Velocity => 0x00,
+            TuningLoop::Position => 0x01,
+        }
+    }
+}
+
+/// `TuningRule` enum represents the rule calculating the controller gains from the ultimate gain and period.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum TuningRule {
+    /// The classic Ziegler-Nichols PID rule, aggressive with a large overshoot.
+    #[default]
+    ZieglerNichols,
+    /// The Ziegler-Nichols PI rule, the derivative gain is zero.
+    ZieglerNicholsPI,
+    /// The Tyreus-Luyben PID rule, more robust than the Ziegler-Nichols rule.
+    TyreusLuyben,
+    /// The PID rule with little or no overshoot.
+    NoOvershoot,
+}
+
+impl TryFrom<u8> for TuningRule {
+    type Error = ();
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            0x00 => Ok(TuningRule::ZieglerNichols),
+            0x01 => Ok(TuningRule::ZieglerNicholsPI),
+            0x02 => Ok(TuningRule::TyreusLuyben),
+            0x03 => Ok(TuningRule::NoOvershoot),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<TuningRule> for u8 {
+    fn from(raw: TuningRule) -> Self {
+        match raw {
+            TuningRule::ZieglerNichols => 0x00,
+            TuningRule::ZieglerNicholsPI => 0x01,
+            TuningRule::TyreusLuyben => 0x02,
+            TuningRule::NoOvershoot => 0x03,
+        }
+    }
+}
+
+/// `TuningState` enum represents the progress of the auto-tuning of an axis.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+pub enum TuningState {
+    #[default]
+    Idle,
+    InProgress,
+    /// The experiment has finished and the proposed gains are ready to be committed.
+    Finished,
+    /// The experiment was aborted or the axis did not oscillate.
+    Error,
+}
+
+impl From<TuningState> for u8 {
+    fn from(raw: TuningState) -> Self {
+        match raw {
+            TuningState::Idle => 0x00,
+            TuningState::InProgress => 0x01,
+            TuningState::Finished => 0x02,
+            TuningState::Error => 0x03,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::models::AxisMode;
+    use crate::models::{
+        AxisMode, ErrorCode, FaultReaction, HomingMethod, PdoUnits, RampProfile, StepLossReaction,
+        TuningLoop, TuningRule, TuningState, VelocityFilter,
+    };
+    use core::convert::TryFrom;
 
     #[test]
     fn axis_mode_deserialize() {
@@ -74,5 +582,91 @@ mod tests {
     fn axis_mode_serialize() {
         assert_eq!(u8::from(AxisMode::Velocity), 0u8);
         assert_eq!(u8::from(AxisMode::Position), 1u8);
+        assert_eq!(u8::from(AxisMode::Homing), 4u8);
+    }
+
+    #[test]
+    fn homing_mode_deserialize() {
+        assert_eq!(AxisMode::from(4u8), AxisMode::Homing);
+        assert_eq!(AxisMode::from(5u8), AxisMode::Interpolated);
+        assert_eq!(AxisMode::from(0x14u8), AxisMode::Homing);
+        assert_eq!(u8::from(AxisMode::Interpolated), 5u8);
+        assert_eq!(AxisMode::from(6u8), AxisMode::Gearing);
+        assert_eq!(u8::from(AxisMode::Gearing), 6u8);
+        assert_eq!(AxisMode::from(7u8), AxisMode::AutoTune);
+        assert_eq!(u8::from(AxisMode::AutoTune), 7u8);
+    }
+
+    #[test]
+    fn homing_method_serialization() {
+        assert_eq!(
+            HomingMethod::try_from(17u8),
+            Ok(HomingMethod::NegativeLimitSwitch)
+        );
+        assert_eq!(u8::from(HomingMethod::CurrentPosition), 37u8);
+        assert_eq!(
+            HomingMethod::try_from(-1i8 as u8),
+            Ok(HomingMethod::NegativeStall)
+        );
+        assert_eq!(u8::from(HomingMethod::PositiveStall) as i8, -2i8);
+        assert!(HomingMethod::try_from(1u8).is_err());
+    }
+
+    #[test]
+    fn ramp_profile_serialization() {
+        assert_eq!(RampProfile::from(0u8), RampProfile::Trapezoidal);
+        assert_eq!(RampProfile::from(1u8), RampProfile::SCurve);
+        assert_eq!(u8::from(RampProfile::SCurve), 1u8);
+    }
+
+    #[test]
+    fn fault_reaction_serialization() {
+        assert_eq!(FaultReaction::try_from(0u8), Ok(FaultReaction::WarnOnly));
+        assert_eq!(u8::from(FaultReaction::Disable), 2u8);
+        assert!(FaultReaction::try_from(3u8).is_err());
+    }
+
+    #[test]
+    fn error_code_serialization() {
+        assert_eq!(ErrorCode::try_from(0u8), Ok(ErrorCode::None));
+        assert_eq!(ErrorCode::try_from(6u8), Ok(ErrorCode::CommunicationLoss));
+        assert_eq!(u8::from(ErrorCode::LimitHit), 5u8);
+        assert_eq!(ErrorCode::try_from(7u8), Ok(ErrorCode::StepLoss));
+        assert_eq!(u8::from(ErrorCode::Stall), 8u8);
+        assert!(ErrorCode::try_from(9u8).is_err());
+    }
+
+    #[test]
+    fn pdo_units_serialization() {
+        assert_eq!(PdoUnits::try_from(2u8), Ok(PdoUnits::FixedPoint));
+        assert_eq!(u8::from(PdoUnits::Float), 1u8);
+        assert!(PdoUnits::try_from(3u8).is_err());
+    }
+
+    #[test]
+    fn velocity_filter_serialization() {
+        assert_eq!(VelocityFilter::try_from(3u8), Ok(VelocityFilter::Observer));
+        assert_eq!(u8::from(VelocityFilter::MovingAverage), 1u8);
+        assert!(VelocityFilter::try_from(4u8).is_err());
+    }
+
+    #[test]
+    fn step_loss_reaction_serialization() {
+        assert_eq!(
+            StepLossReaction::try_from(1u8),
+            Ok(StepLossReaction::Resynchronize)
+        );
+        assert_eq!(u8::from(StepLossReaction::Fault), 2u8);
+        assert!(StepLossReaction::try_from(3u8).is_err());
+    }
+
+    #[test]
+    fn tuning_serialization() {
+        assert_eq!(TuningLoop::try_from(1u8), Ok(TuningLoop::Position));
+        assert!(TuningLoop::try_from(2u8).is_err());
+        assert_eq!(TuningRule::try_from(2u8), Ok(TuningRule::TyreusLuyben));
+        assert_eq!(u8::from(TuningRule::NoOvershoot), 3u8);
+        assert!(TuningRule::try_from(4u8).is_err());
+        assert_eq!(u8::from(TuningState::Finished), 2u8);
     }
 }
@@ -6,7 +6,7 @@ use core::ops::{Add, AddAssign, Sub, SubAssign};
 /// When the number of revolutions is negative,
 /// there is always one more revolution added (-2.5 revolutions in reality -> -3 revolutions in `Position`) and
 /// the resulting position is calculated by adding the positive angle to it.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Position<const RESOLUTION: u32> {
     revolutions: i32,
     angle: u32,
@@ -112,6 +112,62 @@ impl<const RESOLUTION: u32> Position<RESOLUTION> {
         self.revolutions as f32 + self.angle as f32 / RESOLUTION as f32
     }
 
+    /// Constructs the position from the `revolutions` and the `angle` in increments of another `resolution`,
+    /// which is generally known only at runtime. The angle is rounded to the nearest increment of this position.
+    ///
+    /// # Example
+    /// ```
+    /// use sm4_shared::prelude::Position;
+    ///
+    /// let position = Position::<{3200}>::from_resolution(1, 10, 16);
+    /// assert_eq!(position.get_revolutions(), 1);
+    /// assert_eq!(position.get_angle(), 2000);
+    /// ```
+    pub fn from_resolution(revolutions: i32, angle: u32, resolution: u32) -> Self {
+        if resolution == 0 {
+            return Self::new(revolutions, 0);
+        }
+        let revolutions = revolutions + (angle / resolution) as i32;
+        let angle = angle % resolution;
+        let angle = (angle as u64 * RESOLUTION as u64 + resolution as u64 / 2) / resolution as u64;
+        Self::new(revolutions, angle as u32)
+    }
+
+    /// Constructs the position from the number of `increments` of another `resolution`.
+    ///
+    /// # Example
+    /// ```
+    /// use sm4_shared::prelude::Position;
+    ///
+    /// let position = Position::<{4}>::from_increments(-6, 8);
+    /// assert_eq!(position.get_revolutions(), -1);
+    /// assert_eq!(position.get_angle(), 1);
+    /// ```
+    pub fn from_increments(increments: i32, resolution: u32) -> Self {
+        if resolution == 0 {
+            return Self::zero();
+        }
+        Self::from_resolution(
+            increments.div_euclid(resolution as i32),
+            increments.rem_euclid(resolution as i32) as u32,
+            resolution,
+        )
+    }
+
+    /// Converts the position to the `TARGET` resolution.
+    ///
+    /// # Example
+    /// ```
+    /// use sm4_shared::prelude::Position;
+    ///
+    /// let position = Position::<{3200}>::new(-2, 1600).convert::<{4}>();
+    /// assert_eq!(position.get_revolutions(), -2);
+    /// assert_eq!(position.get_angle(), 2);
+    /// ```
+    pub fn convert<const TARGET: u32>(&self) -> Position<TARGET> {
+        Position::from_resolution(self.revolutions, self.angle, RESOLUTION)
+    }
+
     fn from_raw(mut revolutions: i32, mut angle: i32) -> Position<RESOLUTION> {
         if angle.abs() as i32 >= RESOLUTION as i32 {
             revolutions += angle.signum() * angle / RESOLUTION as i32;
@@ -224,6 +280,21 @@ mod tests {
 
     const ENCODER_RESOLUTION: u32 = 4;
 
+    #[test]
+    fn resolution_conversion() {
+        // the angle rounded up to the full revolution
+        let position = Position::<{ ENCODER_RESOLUTION }>::from_resolution(0, 15, 16);
+        assert_eq!(position.revolutions, 1);
+        assert_eq!(position.angle, 0);
+
+        let position = Position::<{ ENCODER_RESOLUTION }>::from_increments(-3, 2);
+        assert_eq!(position.get_increments(), -6);
+        let position = position
+            .convert::<51200>()
+            .convert::<{ ENCODER_RESOLUTION }>();
+        assert_eq!(position.get_increments(), -6);
+    }
+
     #[test]
     fn position_manipulation() {
         let mut position = Position::<{ ENCODER_RESOLUTION }>::zero();
@@ -1,34 +1,111 @@
+use crate::backlash::BacklashCompensation;
+use crate::current::CurrentPolicy;
+use crate::following_error::FollowingErrorMonitor;
+use crate::homing::HomingCommand;
 use crate::prelude::*;
+use crate::stall::StallDetector;
+use crate::step_loss::StepLossMonitor;
+#[cfg(not(any(test, feature = "std")))]
 use num_traits::Float;
 
 use embedded_time::duration::Microseconds;
 
 /// Motion controller of an arbitrary axis.
 /// This motion controller expects that the target driver is controlled either in velocity or position mode.
-/// Trapezoidal ramp generator is utilized.
-pub struct AxisMotionController<D: StepperDriver, E: Encoder<RESOLUTION>, const RESOLUTION: u32> {
+/// Either trapezoidal or S-curve ramp generator is utilized, depending on the axis configuration.
+/// A latched fault of the axis stops it until the fault is reset.
+pub struct AxisMotionController<
+    D: StepperDriver,
+    E: Encoder<RESOLUTION>,
+    H: HomeSwitch,
+    const RESOLUTION: u32,
+> {
     /// The target stepper motor driver, that will be controlled by this motion controller.
     driver: D,
     /// The encoder, that will be used to provide feedback for closed loop control
     encoder: E,
+    /// The switch used as the reference for homing.
+    home_switch: H,
+    homing: Homing,
+    auto_tuner: RelayAutoTuner,
     velocity_controller: PSDController,
     position_controller: PSDController,
     ramp_generator: TrapRampGen,
+    s_curve_generator: SCurveRampGen,
+    trajectory_planner: TrajectoryPlanner,
+    pvt_interpolator: PvtInterpolator,
+    gearing: ElectronicGearing,
+    current_policy: CurrentPolicy,
+    following_error_monitor: FollowingErrorMonitor,
+    backlash: BacklashCompensation,
+    step_loss_monitor: StepLossMonitor,
+    stall_detector: StallDetector,
+    /// True when the driver reported the StallGuard result in the last control step.
+    stall_guard_available: bool,
+    /// True when the motor was stalled in the last control step.
+    stalled: bool,
+    /// False when the driver has been disabled because of its fault.
+    driver_enabled: bool,
+    /// The position error (in revolutions) of the last control step, `None` when the position was not controlled.
+    position_error: Option<f32>,
+    /// True when the actual position was beyond the software position limits in the last control step.
+    beyond_limits: bool,
+    /// The target position (in revolutions) of the trajectory that is being executed, `None` when no trajectory is followed.
+    planned_target: Option<f32>,
     /// Variable used to store the calculated velocity action for ramp generator.
     axis_velocity_action: f32,
+    /// The last output frequency generated by the ramp generator.
+    output_frequency: f32,
+    /// The mode in which the axis was controlled in the last control step, `None` when it was disabled.
+    active_mode: Option<AxisMode>,
+    control_period: f32, // seconds
 }
 
-impl<D: StepperDriver, E: Encoder<RESOLUTION>, const RESOLUTION: u32>
-    AxisMotionController<D, E, RESOLUTION>
+impl<D: StepperDriver, E: Encoder<RESOLUTION>, H: HomeSwitch, const RESOLUTION: u32>
+    AxisMotionController<D, E, H, RESOLUTION>
 {
-    pub fn new(driver: D, encoder: E, sampling_period: Microseconds) -> Self {
+    /// Creates a new motion controller.
+    ///
+    /// # Arguments
+    /// * `control_period` - period in which the [Self::control()] method is called
+    /// * `ramping_period` - period in which the [Self::ramp()] method is called
+    pub fn new(
+        driver: D,
+        encoder: E,
+        home_switch: H,
+        control_period: Microseconds,
+        ramping_period: Microseconds,
+    ) -> Self {
+        let mut driver = driver;
+        driver.enable();
         Self {
             driver,
             encoder,
-            velocity_controller: PSDController::new(sampling_period),
-            position_controller: PSDController::new(sampling_period),
-            ramp_generator: TrapRampGen::new(sampling_period),
+            home_switch,
+            homing: Homing::new(),
+            auto_tuner: RelayAutoTuner::new(control_period),
+            velocity_controller: PSDController::new(control_period),
+            position_controller: PSDController::new(control_period),
+            ramp_generator: TrapRampGen::new(ramping_period),
+            s_curve_generator: SCurveRampGen::new(ramping_period),
+            trajectory_planner: TrajectoryPlanner::new(control_period),
+            pvt_interpolator: PvtInterpolator::new(control_period),
+            gearing: ElectronicGearing::new(control_period),
+            current_policy: CurrentPolicy::new(ramping_period),
+            following_error_monitor: FollowingErrorMonitor::new(control_period),
+            backlash: BacklashCompensation::new(control_period),
+            step_loss_monitor: StepLossMonitor::new(ramping_period),
+            stall_detector: StallDetector::new(),
+            stall_guard_available: false,
+            stalled: false,
+            driver_enabled: true,
+            position_error: None,
+            beyond_limits: false,
+            planned_target: None,
             axis_velocity_action: 0.0,
+            output_frequency: 0.0,
+            active_mode: None,
+            control_period: control_period.0 as f32 / 1_000_000.0,
         }
     }
 
@@ -37,9 +114,32 @@ impl<D: StepperDriver, E: Encoder<RESOLUTION>, const RESOLUTION: u32>
             self.axis_velocity_action = 0.0;
         }
 
-        let output_frequency = self
-            .ramp_generator
-            .generate(self.axis_velocity_action, dictionary.acceleration());
+        let decelerating = self.axis_velocity_action * self.output_frequency < 0.0
+            || self.axis_velocity_action.abs() < self.output_frequency.abs();
+        let acceleration = if decelerating {
+            dictionary.deceleration()
+        } else {
+            dictionary.acceleration()
+        };
+
+        let output_frequency = match dictionary.ramp_profile() {
+            RampProfile::Trapezoidal => {
+                let output_frequency = self
+                    .ramp_generator
+                    .generate(self.axis_velocity_action, acceleration);
+                self.s_curve_generator.reset(output_frequency);
+                output_frequency
+            }
+            RampProfile::SCurve => {
+                let output_frequency = self.s_curve_generator.generate(
+                    self.axis_velocity_action,
+                    acceleration,
+                    dictionary.jerk(),
+                );
+                self.ramp_generator.reset(output_frequency);
+                output_frequency
+            }
+        };
 
         let axis_new_direction = Direction::from(output_frequency);
         if Direction::from(dictionary.actual_velocity().get_rps()) != axis_new_direction {
@@ -52,50 +152,599 @@ impl<D: StepperDriver, E: Encoder<RESOLUTION>, const RESOLUTION: u32>
             Velocity::new(output_frequency)
         });
 
+        self.output_frequency = output_frequency;
         self.driver.set_output_frequency(output_frequency);
-        let current = if output_frequency.abs() < 0.1 {
-            dictionary.current().standstill_current()
-        } else if (output_frequency - self.axis_velocity_action).abs() < f32::EPSILON {
-            dictionary.current().constant_velocity_current()
+        self.step_loss_monitor
+            .command(self.driver.output_frequency());
+        // without the feedback, the measured velocity is not known and the load cannot be estimated
+        let load = if dictionary.velocity_feedback_control_enabled() {
+            output_frequency - dictionary.actual_velocity().get_rps()
         } else {
-            dictionary.current().accelerating_current()
+            0.0
         };
+        let current = self.current_policy.sample(
+            output_frequency,
+            load,
+            acceleration,
+            &dictionary.current(),
+            &dictionary.current_policy(),
+        );
         self.driver.set_current(current);
     }
 
+    /// Controls the axis described by the `dictionary`.
     pub fn control(
         &mut self,
         global_disable: bool,
         dictionary: &mut dyn AxisDictionary<RESOLUTION>,
     ) {
+        self.control_with_reference(global_disable, dictionary, None, None);
+    }
+
+    /// Controls the `axis` of the driver described by the `dictionary`.
+    /// Unlike [Self::control()], the state of the other axis is available,
+    /// so the axis in the gearing mode follows the actual position of the other axis.
+    pub fn control_axis(
+        &mut self,
+        global_disable: bool,
+        dictionary: &mut dyn ObjectDictionary<RESOLUTION>,
+        axis: Axis,
+    ) {
+        let master = match axis {
+            Axis::Axis1 => Axis::Axis2,
+            Axis::Axis2 => Axis::Axis1,
+        };
+        let master = actual_state(dictionary.axis(master));
+        self.control_with_reference(
+            global_disable,
+            dictionary.axis_mut(axis),
+            None,
+            Some(master),
+        );
+    }
+
+    /// Controls the axis along the `reference` planned outside of this motion controller,
+    /// which is used for the coordinated motion of multiple axes.
+    /// The reference is followed only in position mode, it is ignored in the other modes.
+    pub fn follow(
+        &mut self,
+        global_disable: bool,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+        reference: TrajectoryPoint,
+    ) {
+        self.control_with_reference(global_disable, dictionary, Some(reference), None);
+    }
+
+    /// Controls the axis, the `reference` is the external reference for the position mode
+    /// and the `master` is the state of the master axis for the gearing mode.
+    /// The velocity estimator settings and the microstepping are passed to the encoder and the driver
+    /// on every step, the positions are always expressed in the `RESOLUTION` of the motion controller.
+    /// Targets beyond the software position limits are clamped in position mode and the axis decelerates
+    /// to stop at the limits in velocity mode, leaving the limits latches the [ErrorCode::LimitHit] fault.
+    fn control_with_reference(
+        &mut self,
+        global_disable: bool,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+        reference: Option<TrajectoryPoint>,
+        master: Option<TrajectoryPoint>,
+    ) {
+        self.encoder
+            .set_velocity_estimator(&dictionary.velocity_estimator_settings());
+        self.driver.set_microsteps(dictionary.microsteps());
+        self.encoder
+            .set_step_resolution(self.driver.microsteps_per_revolution());
         self.encoder.sample();
-        dictionary.set_actual_position(self.encoder.get_position());
+        dictionary.set_actual_position(self.backlash.load_position(self.encoder.get_position()));
+
+        self.position_error = None;
+        self.monitor_step_loss(dictionary);
+        self.monitor_stall(dictionary);
+        self.monitor_driver(dictionary);
+
+        // the fault is latched only when the axis leaves the limits, so it may return after the reset,
+        // the limits are not enforced while the reference position is being searched for
+        let violation = dictionary
+            .position_limits()
+            .check_position(dictionary.actual_position().get_relative_revolutions());
+        let beyond_limits =
+            violation != LimitViolation::None && dictionary.mode() != AxisMode::Homing;
+        if beyond_limits && !self.beyond_limits {
+            dictionary.raise_fault(ErrorCode::LimitHit);
+        }
+        self.beyond_limits = beyond_limits;
+
+        let faulted = dictionary.fault() != ErrorCode::None;
+        let active_mode = if dictionary.enabled() && !global_disable && !faulted {
+            Some(dictionary.mode())
+        } else {
+            None
+        };
+        let mode_changed = active_mode != self.active_mode;
+        if mode_changed {
+            self.position_controller.reset();
+            self.planned_target = None;
+            if self.active_mode == Some(AxisMode::Homing) {
+                self.homing.abort();
+                dictionary.set_homing_state(self.homing.state());
+            }
+            if self.active_mode == Some(AxisMode::Interpolated) {
+                // the remaining points are not executed after leaving the interpolated mode
+                dictionary.pvt_queue_mut().clear();
+                dictionary.set_pvt_state(PvtState::Idle);
+            }
+            if self.active_mode == Some(AxisMode::AutoTune) {
+                self.auto_tuner.abort();
+                dictionary.set_auto_tune_state(self.auto_tuner.state());
+            }
+            if self.active_mode == Some(AxisMode::Gearing) {
+                // the other modes continue from the actual state of the axis, so the disengagement is ramped by them
+                self.gearing.disengage();
+                dictionary.set_gearing_state(self.gearing.state());
+            }
+        }
 
-        let target_velocity = if dictionary.enabled() && !global_disable {
+        // the part of the velocity action that is derived directly from the planned profile
+        let mut feedforward = 0.0;
+        let limits = dictionary.position_limits();
+        let mut limit_violation = LimitViolation::None;
+        // the velocity with which the axis still stops at the limit, when it brakes for the limit
+        let mut braking_velocity = None;
+        let target_velocity = if active_mode.is_some() {
             match dictionary.mode() {
-                AxisMode::Velocity => dictionary.target_velocity(),
-                AxisMode::Position => Velocity::new(self.position_controller.sample(
-                    &dictionary.target_position().get_relative_revolutions(),
-                    &dictionary.actual_position().get_relative_revolutions(),
-                    &dictionary.position_controller_settings(),
-                )),
+                AxisMode::Velocity => {
+                    let (velocity, violation) = limits.limit_velocity(
+                        dictionary.target_velocity().get_rps(),
+                        dictionary.actual_position().get_relative_revolutions(),
+                        self.output_frequency,
+                        dictionary.deceleration(),
+                        self.braking_lag(dictionary),
+                    );
+                    limit_violation = violation;
+                    if violation != LimitViolation::None {
+                        braking_velocity = Some(velocity);
+                    }
+                    Velocity::new(velocity)
+                }
+                AxisMode::Position => match reference {
+                    Some(reference) => {
+                        // the external reference is already limited by its planner
+                        limit_violation = dictionary.limit_violation();
+                        self.planned_target = None;
+                        self.track(reference, &mut feedforward, dictionary)
+                    }
+                    None => {
+                        let (target, violation) = limits.limit_position(
+                            dictionary.target_position().get_relative_revolutions(),
+                        );
+                        limit_violation = violation;
+                        self.follow_trajectory(target, &mut feedforward, dictionary)
+                    }
+                },
+                // the limits are not enforced while the reference position is being searched for
+                AxisMode::Homing => self.home(mode_changed, &mut feedforward, dictionary),
+                AxisMode::Interpolated => {
+                    let (velocity, violation) =
+                        self.interpolate(mode_changed, &mut feedforward, dictionary);
+                    limit_violation = violation;
+                    velocity
+                }
+                AxisMode::Gearing => match master {
+                    Some(master) => {
+                        let (velocity, violation) = self.gear(master, &mut feedforward, dictionary);
+                        limit_violation = violation;
+                        velocity
+                    }
+                    // the master axis is not known, the axis stands still
+                    None => {
+                        self.planned_target = None;
+                        Velocity::zero()
+                    }
+                },
+                AxisMode::AutoTune => self.auto_tune(mode_changed, dictionary),
             }
         } else {
+            self.planned_target = None;
             Velocity::zero()
         };
+        dictionary.set_limit_violation(limit_violation);
+
+        if mode_changed {
+            // continue from the current action when switching modes, start from scratch otherwise
+            if active_mode.is_some() && self.active_mode.is_some() {
+                self.velocity_controller.preload(
+                    self.axis_velocity_action - feedforward,
+                    dictionary.actual_velocity().get_rps(),
+                    &dictionary.velocity_controller_settings(),
+                );
+            } else {
+                self.velocity_controller.reset();
+            }
+            self.active_mode = active_mode;
+        }
+
+        // the relay replaces the velocity controller while the velocity loop is tuned
+        let velocity_tuned = active_mode == Some(AxisMode::AutoTune)
+            && dictionary.auto_tune_settings().tuning_loop() == TuningLoop::Velocity;
+        self.axis_velocity_action = feedforward
+            + if dictionary.velocity_feedback_control_enabled() && !velocity_tuned {
+                self.velocity_controller.sample(
+                    &target_velocity.get_rps(),
+                    &dictionary.actual_velocity().get_rps(),
+                    &dictionary.velocity_controller_settings(),
+                )
+            } else {
+                target_velocity.get_rps()
+            };
+        // the velocity controller may overshoot the limited target, which would carry the axis past the limit
+        if let Some(velocity) = braking_velocity {
+            self.axis_velocity_action = match limit_violation {
+                LimitViolation::Min => self.axis_velocity_action.max(velocity),
+                LimitViolation::Max => self.axis_velocity_action.min(velocity),
+                LimitViolation::None => self.axis_velocity_action,
+            };
+        }
+
+        self.monitor_following_error(dictionary);
+    }
+
+    /// Returns the time in seconds before the axis starts to decelerate after its target velocity is lowered.
+    /// The target is held for the control period and the S-curve ramp builds up the deceleration gradually,
+    /// which delays the braking by a half of the time the deceleration takes to build up.
+    fn braking_lag(&self, dictionary: &dyn AxisDictionary<RESOLUTION>) -> f32 {
+        let jerk = dictionary.jerk();
+        match dictionary.ramp_profile() {
+            RampProfile::SCurve if jerk > 0.0 => {
+                self.control_period + 0.5 * dictionary.deceleration().abs() / jerk
+            }
+            _ => self.control_period,
+        }
+    }
+
+    /// Checks the following error of the axis in the last control step and reacts when it trips.
+    /// The error stays latched and the axis reacts as configured until the error is cleared.
+    fn monitor_following_error(&mut self, dictionary: &mut dyn AxisDictionary<RESOLUTION>) {
+        // without the feedback, the actual state of the axis is not measured
+        if self.active_mode.is_none() || !dictionary.velocity_feedback_control_enabled() {
+            self.following_error_monitor.reset();
+            return;
+        }
+
+        let settings = dictionary.following_error_settings();
+        let velocity_error = self.output_frequency - dictionary.actual_velocity().get_rps();
+        if self
+            .following_error_monitor
+            .sample(self.position_error, velocity_error, &settings)
+        {
+            self.following_error_monitor.reset();
+            dictionary.set_following_error(true);
+            match settings.reaction() {
+                FaultReaction::WarnOnly => {}
+                FaultReaction::QuickStop => dictionary.raise_fault(ErrorCode::FollowingError),
+                FaultReaction::Disable => {
+                    // the driver is enabled again once the fault is reset, see Self::monitor_driver()
+                    self.driver.disable();
+                    self.driver_enabled = false;
+                    dictionary.raise_fault(ErrorCode::FollowingError);
+                    dictionary.set_enabled(false);
+                }
+            }
+        }
+    }
+
+    /// Compares the position commanded to the motor with the position measured by the encoder
+    /// and reacts when the motor lost steps.
+    fn monitor_step_loss(&mut self, dictionary: &mut dyn AxisDictionary<RESOLUTION>) {
+        let settings = dictionary.step_loss_settings();
+        let lost = self
+            .step_loss_monitor
+            .check(&self.encoder.get_position(), &settings);
+        dictionary.set_step_loss_deviation(self.step_loss_monitor.deviation());
+        // the motor may be moved by hand while it is disabled
+        if self.active_mode.is_none() {
+            self.step_loss_monitor.resynchronize();
+            return;
+        }
+
+        if lost {
+            match settings.reaction() {
+                StepLossReaction::WarnOnly => {}
+                StepLossReaction::Resynchronize => self.step_loss_monitor.resynchronize(),
+                StepLossReaction::Fault => {
+                    self.step_loss_monitor.resynchronize();
+                    dictionary.raise_fault(ErrorCode::StepLoss);
+                }
+            }
+        }
+    }
+
+    /// Reads the StallGuard result of the driver and latches the fault when the motor stalled.
+    /// The stall is not a fault in the sensorless homing, where it triggers the home.
+    fn monitor_stall(&mut self, dictionary: &mut dyn AxisDictionary<RESOLUTION>) {
+        let result = self.driver.stall_guard_result();
+        self.stall_guard_available = result.is_some();
+        if let Some(result) = result {
+            dictionary.set_stall_guard_result(result);
+        }
+        // the motor does not stall unless it is driven
+        if self.active_mode.is_none() {
+            self.stall_detector.reset();
+            self.stalled = false;
+            return;
+        }
+
+        self.stalled = self.stall_detector.sample(
+            result,
+            self.driver.output_frequency(),
+            &dictionary.stall_settings(),
+        );
+        let home_trigger = dictionary.mode() == AxisMode::Homing
+            && dictionary.homing_settings().method().is_sensorless();
+        if self.stalled && !home_trigger {
+            dictionary.raise_fault(ErrorCode::Stall);
+        }
+    }
 
-        self.axis_velocity_action = if dictionary.velocity_feedback_control_enabled() {
-            self.velocity_controller.sample(
-                &target_velocity.get_rps(),
-                &dictionary.actual_velocity().get_rps(),
-                &dictionary.velocity_controller_settings(),
+    /// Disables the driver when it reports a fault. The driver is enabled again once the fault of the axis is reset,
+    /// which also recovers the driver from the shutdown.
+    fn monitor_driver(&mut self, dictionary: &mut dyn AxisDictionary<RESOLUTION>) {
+        if !self.driver_enabled {
+            if dictionary.fault() == ErrorCode::None {
+                self.driver.enable();
+                self.driver_enabled = true;
+            }
+            return;
+        }
+
+        if self.driver.is_faulted() {
+            self.driver.disable();
+            self.driver_enabled = false;
+            dictionary.raise_fault(ErrorCode::DriverError);
+        }
+    }
+
+    /// Returns the target velocity of the axis following the trajectory to the `target` position (in revolutions)
+    /// and stores the velocity feedforward to the `feedforward`.
+    /// The position controller only corrects the deviation of the axis from the planned trajectory.
+    fn follow_trajectory(
+        &mut self,
+        target: f32,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> Velocity {
+        let reference = self.position_reference(target, dictionary);
+        self.track(reference, feedforward, dictionary)
+    }
+
+    /// Returns the target velocity of the axis tracking the `reference`
+    /// and stores the velocity feedforward to the `feedforward`.
+    /// The planned velocity and acceleration are fed forward scaled by the configured gains and the backlash
+    /// is compensated whenever the direction reverses. The reported actual position is the position of the load.
+    fn track(
+        &mut self,
+        reference: TrajectoryPoint,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> Velocity {
+        self.position_error =
+            Some(reference.position - dictionary.actual_position().get_relative_revolutions());
+        // the position of the motor is controlled, so the compensation is a part of the reference
+        let reference = self
+            .backlash
+            .compensate(reference, &dictionary.backlash_settings());
+        let correction = self.position_controller.sample(
+            &reference.position,
+            &self.encoder.get_position().get_relative_revolutions(),
+            &dictionary.position_controller_settings(),
+        );
+        *feedforward = dictionary.velocity_feedforward() * reference.velocity
+            + dictionary.acceleration_feedforward() * reference.acceleration;
+        if dictionary.velocity_feedback_control_enabled() {
+            Velocity::new(reference.velocity + correction)
+        } else {
+            Velocity::new(correction)
+        }
+    }
+
+    /// Performs a step of the homing sequence and returns the target velocity of the axis.
+    /// The encoder position is reset once the home is found.
+    fn home(
+        &mut self,
+        started: bool,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> Velocity {
+        let position = dictionary.actual_position().get_relative_revolutions();
+        let settings = dictionary.homing_settings();
+        // the stall of the motor replaces the home switch in the sensorless methods
+        let (trigger_present, trigger_active) = if settings.method().is_sensorless() {
+            (
+                self.stall_guard_available && dictionary.stall_settings().threshold() > 0,
+                self.stalled,
             )
         } else {
-            target_velocity.get_rps()
+            (self.home_switch.is_present(), self.home_switch.is_active())
         };
+        if started {
+            self.homing.start(trigger_present, position, &settings);
+        }
+
+        let move_finished = self.planned_target.is_some() && self.trajectory_planner.is_finished();
+        let command = self
+            .homing
+            .sample(trigger_active, position, move_finished, &settings);
+        dictionary.set_homing_state(self.homing.state());
+
+        match command {
+            HomingCommand::Position(target) => {
+                return self.follow_trajectory(target, feedforward, dictionary)
+            }
+            HomingCommand::Velocity(velocity) => {
+                self.planned_target = None;
+                return Velocity::new(velocity);
+            }
+            HomingCommand::SetHome => {
+                self.encoder.reset_position();
+                self.backlash.reset();
+                self.step_loss_monitor.reset();
+                self.stall_detector.reset();
+                dictionary.set_actual_position(self.encoder.get_position());
+                dictionary.set_target_position(Position::zero());
+                self.position_controller.reset();
+            }
+            HomingCommand::Hold => {}
+        }
+        self.planned_target = None;
+        Velocity::zero()
+    }
+
+    /// Performs a step of the relay feedback experiment and returns the target velocity of the axis.
+    /// The velocity loop oscillates around the target velocity, the position loop around the position
+    /// where the experiment started. The proposed gains are stored once the experiment finishes.
+    fn auto_tune(
+        &mut self,
+        started: bool,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> Velocity {
+        self.planned_target = None;
+        let settings = dictionary.auto_tune_settings();
+        let measured = match settings.tuning_loop() {
+            TuningLoop::Velocity => dictionary.actual_velocity().get_rps(),
+            TuningLoop::Position => dictionary.actual_position().get_relative_revolutions(),
+        };
+        if started {
+            self.auto_tuner.start(match settings.tuning_loop() {
+                TuningLoop::Velocity => dictionary.target_velocity().get_rps(),
+                TuningLoop::Position => measured,
+            });
+        }
+
+        let output = self.auto_tuner.sample(measured, &settings);
+        let state = self.auto_tuner.state();
+        if state == TuningState::Finished && dictionary.auto_tune_state() != state {
+            dictionary.set_auto_tune_result(self.auto_tuner.result());
+        }
+        dictionary.set_auto_tune_state(state);
+
+        match (state, settings.tuning_loop()) {
+            (TuningState::InProgress, TuningLoop::Velocity) => {
+                Velocity::new(self.auto_tuner.setpoint() + output)
+            }
+            (TuningState::InProgress, TuningLoop::Position) => Velocity::new(output),
+            _ => Velocity::zero(),
+        }
+    }
+
+    /// Performs a step of the cubic Hermite interpolation between the PVT points and returns the target velocity
+    /// of the axis together with the violation of the position limits by the interpolated reference.
+    fn interpolate(
+        &mut self,
+        started: bool,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> (Velocity, LimitViolation) {
+        if started {
+            self.pvt_interpolator
+                .reset(dictionary.actual_position().get_relative_revolutions());
+        }
+        self.planned_target = None;
+
+        let reference = self.pvt_interpolator.sample(dictionary.pvt_queue_mut());
+        dictionary.set_pvt_state(self.pvt_interpolator.state());
+        self.track_limited(reference, feedforward, dictionary)
+    }
+
+    /// Performs a step of the electronic gearing to the `master` axis and returns the target velocity of the axis
+    /// together with the violation of the position limits by the geared reference.
+    fn gear(
+        &mut self,
+        master: TrajectoryPoint,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> (Velocity, LimitViolation) {
+        self.planned_target = None;
+        let reference = self.gearing.sample(
+            actual_state(dictionary),
+            master,
+            &dictionary.gearing_settings(),
+            &profile_limits(dictionary),
+        );
+        dictionary.set_gearing_state(self.gearing.state());
+        self.track_limited(reference, feedforward, dictionary)
+    }
+
+    /// Tracks the `reference` limited by the software position limits of the axis.
+    /// When the reference is beyond the limits, the axis stops at the limit.
+    fn track_limited(
+        &mut self,
+        mut reference: TrajectoryPoint,
+        feedforward: &mut f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> (Velocity, LimitViolation) {
+        let (position, violation) = dictionary
+            .position_limits()
+            .limit_position(reference.position);
+        if violation != LimitViolation::None {
+            reference = TrajectoryPoint {
+                position,
+                velocity: 0.0,
+                acceleration: 0.0,
+            };
+        }
+        (self.track(reference, feedforward, dictionary), violation)
+    }
+
+    /// Returns the reference state of the axis on the planned trajectory.
+    /// The trajectory is replanned whenever the `target` position (in revolutions) changes.
+    fn position_reference(
+        &mut self,
+        target: f32,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+    ) -> TrajectoryPoint {
+        if self.planned_target != Some(target) {
+            let start = if self.planned_target.is_some() {
+                self.trajectory_planner.reference()
+            } else {
+                actual_state(dictionary)
+            };
+            self.trajectory_planner
+                .plan(start, target, &profile_limits(dictionary));
+            self.planned_target = Some(target);
+        }
+
+        self.trajectory_planner.sample()
     }
 
     pub fn decompose(self) -> (D, E) {
         (self.driver, self.encoder)
     }
 }
+
+/// Returns the limits of the moves of the axis configured in the `dictionary`.
+pub(crate) fn profile_limits<const RESOLUTION: u32>(
+    dictionary: &dyn AxisDictionary<RESOLUTION>,
+) -> ProfileLimits {
+    match dictionary.ramp_profile() {
+        RampProfile::Trapezoidal => ProfileLimits::trapezoidal(
+            dictionary.max_velocity(),
+            dictionary.acceleration(),
+            dictionary.deceleration(),
+        ),
+        RampProfile::SCurve => ProfileLimits::s_curve(
+            dictionary.max_velocity(),
+            dictionary.acceleration(),
+            dictionary.deceleration(),
+            dictionary.jerk(),
+        ),
+    }
+}
+
+/// Returns the actual state of the axis described by the `dictionary`.
+pub(crate) fn actual_state<const RESOLUTION: u32>(
+    dictionary: &dyn AxisDictionary<RESOLUTION>,
+) -> TrajectoryPoint {
+    TrajectoryPoint {
+        position: dictionary.actual_position().get_relative_revolutions(),
+        velocity: dictionary.actual_velocity().get_rps(),
+        acceleration: 0.0,
+    }
+}
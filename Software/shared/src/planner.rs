@@ -0,0 +1,537 @@
+//! Point-to-point trajectory planning.
+//!
+//! The planner computes a time optimal move from the current state of an axis to the target position.
+//! The move respects the velocity, acceleration and deceleration limits and optionally the jerk limit,
+//! which results in an S-curve profile instead of the trapezoidal one.
+//! The move is internally represented as a sequence of segments with constant jerk,
+//! trapezoidal moves being a special case with zero jerk in every segment.
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// Maximal number of segments of a single move - a stop of the ongoing movement (3 segments)
+/// followed by a full S-curve move (7 segments).
+const MAX_SEGMENTS: usize = 10;
+/// Number of iterations used to find the peak velocity of moves that are too short to reach the maximal velocity.
+const PEAK_VELOCITY_ITERATIONS: usize = 32;
+
+/// Limits that shall be respected by the planned move.
+/// All the values are in revolutions and seconds.
+#[derive(Copy, Clone, Default)]
+pub struct ProfileLimits {
+    velocity: f32,
+    acceleration: f32,
+    deceleration: f32,
+    jerk: Option<f32>,
+}
+
+impl ProfileLimits {
+    /// Creates limits of a trapezoidal profile.
+    pub fn trapezoidal(velocity: f32, acceleration: f32, deceleration: f32) -> Self {
+        Self {
+            velocity: velocity.abs(),
+            acceleration: acceleration.abs(),
+            deceleration: deceleration.abs(),
+            jerk: None,
+        }
+    }
+
+    /// Creates limits of an S-curve profile.
+    /// Non-positive jerk results in a trapezoidal profile.
+    pub fn s_curve(velocity: f32, acceleration: f32, deceleration: f32, jerk: f32) -> Self {
+        Self {
+            jerk: if jerk > 0.0 { Some(jerk) } else { None },
+            ..Self::trapezoidal(velocity, acceleration, deceleration)
+        }
+    }
+
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+    pub fn acceleration(&self) -> f32 {
+        self.acceleration
+    }
+    pub fn deceleration(&self) -> f32 {
+        self.deceleration
+    }
+    pub fn jerk(&self) -> Option<f32> {
+        self.jerk
+    }
+
+    fn is_valid(&self) -> bool {
+        self.velocity > 0.0 && self.acceleration > 0.0 && self.deceleration > 0.0
+    }
+}
+
+/// A single sample of the planned trajectory.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct TrajectoryPoint {
+    /// Position in revolutions.
+    pub position: f32,
+    /// Velocity in revolutions per second.
+    pub velocity: f32,
+    /// Acceleration in revolutions per second squared.
+    pub acceleration: f32,
+}
+
+/// Part of the move with constant jerk.
+#[derive(Copy, Clone, Default)]
+struct Segment {
+    duration: f32,
+    start: TrajectoryPoint,
+    jerk: f32,
+}
+
+impl Segment {
+    fn sample(&self, time: f32) -> TrajectoryPoint {
+        let TrajectoryPoint {
+            position,
+            velocity,
+            acceleration,
+        } = self.start;
+        TrajectoryPoint {
+            position: position
+                + velocity * time
+                + acceleration * time * time / 2.0
+                + self.jerk * time * time * time / 6.0,
+            velocity: velocity + acceleration * time + self.jerk * time * time / 2.0,
+            acceleration: acceleration + self.jerk * time,
+        }
+    }
+}
+
+/// Sequence of segments forming a move.
+#[derive(Copy, Clone, Default)]
+struct Move {
+    segments: [Segment; MAX_SEGMENTS],
+    count: usize,
+    end: TrajectoryPoint,
+}
+
+impl Move {
+    fn starting_at(start: TrajectoryPoint) -> Self {
+        Self {
+            end: start,
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, duration: f32, acceleration: f32, jerk: f32) {
+        if duration <= 0.0 || self.count == MAX_SEGMENTS {
+            return;
+        }
+        let segment = Segment {
+            duration,
+            start: TrajectoryPoint {
+                acceleration,
+                ..self.end
+            },
+            jerk,
+        };
+        self.segments[self.count] = segment;
+        self.count += 1;
+        self.end = segment.sample(duration);
+        self.end.acceleration = 0.0;
+    }
+
+    fn duration(&self) -> f32 {
+        self.segments[..self.count]
+            .iter()
+            .map(|segment| segment.duration)
+            .sum()
+    }
+
+    /// Appends segments changing the velocity from the current one to `velocity`.
+    fn change_velocity(&mut self, velocity: f32, acceleration: f32, jerk: Option<f32>) {
+        let difference = velocity - self.end.velocity;
+        let direction = difference.signum();
+        let difference = difference.abs();
+
+        match jerk {
+            None => self.push(difference / acceleration, direction * acceleration, 0.0),
+            Some(jerk) => {
+                let (jerk_time, constant_time, peak) =
+                    velocity_change_timing(difference, acceleration, jerk);
+                self.push(jerk_time, 0.0, direction * jerk);
+                self.push(constant_time, direction * peak, 0.0);
+                self.push(jerk_time, direction * peak, -direction * jerk);
+            }
+        }
+        // prevent accumulation of numerical errors
+        self.end.velocity = velocity;
+    }
+
+    fn cruise(&mut self, duration: f32) {
+        self.push(duration, 0.0, 0.0);
+    }
+}
+
+/// Returns the duration of the jerk phase, the duration of the constant acceleration phase and
+/// the peak acceleration of a jerk limited velocity change.
+fn velocity_change_timing(difference: f32, acceleration: f32, jerk: f32) -> (f32, f32, f32) {
+    if difference >= acceleration * acceleration / jerk {
+        let jerk_time = acceleration / jerk;
+        (
+            jerk_time,
+            difference / acceleration - jerk_time,
+            acceleration,
+        )
+    } else {
+        let jerk_time = (difference / jerk).sqrt();
+        (jerk_time, 0.0, jerk * jerk_time)
+    }
+}
+
+/// Returns the distance travelled while the velocity changes from `from` to `to`.
+/// Both velocities shall have the same sign.
+fn velocity_change_distance(from: f32, to: f32, acceleration: f32, jerk: Option<f32>) -> f32 {
+    let difference = (to - from).abs();
+    let duration = match jerk {
+        None => difference / acceleration,
+        Some(jerk) => {
+            let (jerk_time, constant_time, _) =
+                velocity_change_timing(difference, acceleration, jerk);
+            2.0 * jerk_time + constant_time
+        }
+    };
+    // the velocity profile is point symmetric, so the mean velocity is the average of the boundaries
+    (from + to) / 2.0 * duration
+}
+
+/// Point-to-point trajectory planner.
+/// The planner is periodically sampled and provides the reference position, velocity and acceleration
+/// of the axis.
+pub struct TrajectoryPlanner {
+    planned: Move,
+    elapsed: f32,
+    target: f32,
+    period: f32, // seconds
+}
+
+impl TrajectoryPlanner {
+    pub fn new(period: Microseconds) -> Self {
+        Self {
+            planned: Move::default(),
+            elapsed: 0.0,
+            target: 0.0,
+            period: period.0 as f32 / 1_000_000.0,
+        }
+    }
+
+    /// Plans a new move from the `start` state to the `target` position, where the axis shall stop.
+    /// When the axis is moving away from the target or it can't stop before it,
+    /// the planned move first stops the axis and returns back to the target.
+    /// With the jerk limit, the acceleration of the `start` is ramped to zero first, so the acceleration
+    /// of a replanned move is continuous.
+    ///
+    /// # Arguments
+    /// * `start` - state of the axis the move starts from, the acceleration is ignored without the jerk limit
+    /// * `target` - target position in revolutions
+    /// * `limits` - limits of the move
+    pub fn plan(&mut self, start: TrajectoryPoint, target: f32, limits: &ProfileLimits) {
+        self.planned = Move::starting_at(TrajectoryPoint {
+            acceleration: 0.0,
+            ..start
+        });
+        self.elapsed = 0.0;
+        self.target = target;
+
+        if !limits.is_valid() {
+            return;
+        }
+
+        if let Some(jerk) = limits.jerk {
+            let acceleration = start.acceleration;
+            self.planned.push(
+                acceleration.abs() / jerk,
+                acceleration,
+                -acceleration.signum() * jerk,
+            );
+        }
+        let start = self.planned.end;
+
+        let direction = (target - start.position).signum();
+        let distance = (target - start.position).abs();
+        let velocity = start.velocity * direction;
+
+        if velocity < 0.0
+            || velocity_change_distance(velocity, 0.0, limits.deceleration, limits.jerk) > distance
+        {
+            // the target can't be reached without stopping first
+            self.planned
+                .change_velocity(0.0, limits.deceleration, limits.jerk);
+            let stop = self.planned.end;
+            let direction = (target - stop.position).signum();
+            let distance = (target - stop.position).abs();
+            self.plan_stopping_move(direction, distance, 0.0, limits);
+        } else {
+            self.plan_stopping_move(direction, distance, velocity, limits);
+        }
+    }
+
+    /// Plans the move to the distance, where the velocity is in the direction of the move.
+    fn plan_stopping_move(
+        &mut self,
+        direction: f32,
+        distance: f32,
+        velocity: f32,
+        limits: &ProfileLimits,
+    ) {
+        let total_distance = |peak: f32| {
+            let rate = if peak >= velocity {
+                limits.acceleration
+            } else {
+                limits.deceleration
+            };
+            velocity_change_distance(velocity, peak, rate, limits.jerk)
+                + velocity_change_distance(peak, 0.0, limits.deceleration, limits.jerk)
+        };
+
+        let (peak, cruise) = if total_distance(limits.velocity) <= distance {
+            let peak = limits.velocity;
+            (peak, (distance - total_distance(peak)) / peak)
+        } else {
+            // the peak at the lower bound always fits in the distance, an axis faster than the limit
+            // may have to slow down below the limit to stop in time
+            let mut low = if velocity > limits.velocity {
+                0.0
+            } else {
+                velocity
+            };
+            let mut high = limits.velocity;
+            for _ in 0..PEAK_VELOCITY_ITERATIONS {
+                let middle = (low + high) / 2.0;
+                if total_distance(middle) > distance {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            (low, 0.0)
+        };
+
+        let rate = if peak >= velocity {
+            limits.acceleration
+        } else {
+            limits.deceleration
+        };
+        self.planned
+            .change_velocity(direction * peak, rate, limits.jerk);
+        self.planned.cruise(cruise);
+        self.planned
+            .change_velocity(0.0, limits.deceleration, limits.jerk);
+    }
+
+    /// Advances the time of the move by a single period and returns the reference state of the axis.
+    pub fn sample(&mut self) -> TrajectoryPoint {
+        self.elapsed += self.period;
+        self.reference()
+    }
+
+    /// Returns the reference state of the axis at the current time of the move.
+    pub fn reference(&self) -> TrajectoryPoint {
+        let mut time = self.elapsed;
+        for segment in &self.planned.segments[..self.planned.count] {
+            if time < segment.duration {
+                return segment.sample(time);
+            }
+            time -= segment.duration;
+        }
+
+        TrajectoryPoint {
+            position: if self.planned.count > 0 {
+                self.target
+            } else {
+                self.planned.end.position
+            },
+            velocity: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    /// Returns the total duration of the planned move in seconds.
+    pub fn duration(&self) -> f32 {
+        self.planned.duration()
+    }
+
+    /// Returns true when the planned move was completely executed.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration()
+    }
+
+    /// Returns the target position of the planned move.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+    const EPSILON: f32 = 1e-3;
+
+    fn at_rest(position: f32) -> TrajectoryPoint {
+        TrajectoryPoint {
+            position,
+            velocity: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    /// Executes the move and checks that the limits are respected.
+    fn execute(planner: &mut TrajectoryPlanner, limits: &ProfileLimits) -> TrajectoryPoint {
+        let mut previous = planner.reference();
+        let mut point = previous;
+        for _ in 0..100_000 {
+            point = planner.sample();
+            assert!(point.velocity.abs() <= limits.velocity() + EPSILON);
+            let acceleration = (point.velocity - previous.velocity) / 0.001;
+            let max = limits.acceleration().max(limits.deceleration());
+            assert!(acceleration.abs() <= max + EPSILON * 1000.0);
+            if let Some(jerk) = limits.jerk() {
+                assert!(
+                    (point.acceleration - previous.acceleration).abs() <= jerk * 0.001 + EPSILON
+                );
+            }
+            previous = point;
+            if planner.is_finished() {
+                break;
+            }
+        }
+        point
+    }
+
+    #[test]
+    fn trapezoidal_move() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let limits = ProfileLimits::trapezoidal(2.0, 10.0, 5.0);
+        planner.plan(at_rest(1.0), 11.0, &limits);
+
+        // 0.2 s acceleration, 0.4 s deceleration, the rest is cruising at 2 rps
+        let cruise = (10.0 - 0.2 - 0.4) / 2.0;
+        assert!((planner.duration() - (0.6 + cruise)).abs() < EPSILON);
+
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, 11.0);
+        assert_eq!(end.velocity, 0.0);
+    }
+
+    #[test]
+    fn triangular_move() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let limits = ProfileLimits::trapezoidal(10.0, 10.0, 10.0);
+        planner.plan(at_rest(0.0), -1.0, &limits);
+
+        // peak velocity sqrt(10) rps is reached in the middle of the move
+        assert!((planner.duration() - 2.0 * 0.1f32.sqrt()).abs() < EPSILON);
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, -1.0);
+    }
+
+    #[test]
+    fn s_curve_move() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let limits = ProfileLimits::s_curve(2.0, 10.0, 10.0, 100.0);
+        planner.plan(at_rest(0.0), 5.0, &limits);
+
+        // 0.3 s to change velocity, the rest is cruising
+        let cruise = (5.0 - 2.0 * 0.3) / 2.0;
+        assert!((planner.duration() - (0.6 + cruise)).abs() < EPSILON);
+
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, 5.0);
+
+        // short S-curve move, that reaches neither the maximal acceleration nor the velocity
+        planner.plan(at_rest(5.0), 5.01, &limits);
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, 5.01);
+    }
+
+    #[test]
+    fn replanning_while_moving() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let limits = ProfileLimits::trapezoidal(2.0, 10.0, 10.0);
+        let moving = TrajectoryPoint {
+            position: 0.0,
+            velocity: 2.0,
+            acceleration: 0.0,
+        };
+
+        // target in the direction of the movement, no need to stop
+        planner.plan(moving, 1.0, &limits);
+        assert!((planner.duration() - (0.2 + 0.4)).abs() < EPSILON);
+        assert_eq!(execute(&mut planner, &limits).position, 1.0);
+
+        // target behind the axis, it has to stop and return
+        planner.plan(moving, -1.0, &limits);
+        let mut minimal = 0.0f32;
+        let mut maximal = 0.0f32;
+        while !planner.is_finished() {
+            let point = planner.sample();
+            minimal = minimal.min(point.position);
+            maximal = maximal.max(point.position);
+        }
+        assert!((maximal - 0.2).abs() < 0.01);
+        assert!((minimal + 1.0).abs() < EPSILON);
+
+        // target too close to stop in time
+        planner.plan(moving, 0.1, &limits);
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, 0.1);
+    }
+
+    #[test]
+    fn stopping_from_above_maximal_velocity() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let fast = TrajectoryPoint {
+            position: 0.0,
+            velocity: 3.0,
+            acceleration: 0.0,
+        };
+
+        // the target is close, slowing down to the maximal velocity first must not overshoot it
+        for (limits, target) in [
+            (ProfileLimits::trapezoidal(2.0, 10.0, 10.0), 0.46),
+            (ProfileLimits::s_curve(2.0, 10.0, 10.0, 100.0), 0.7),
+        ] {
+            planner.plan(fast, target, &limits);
+            let mut maximal = 0.0f32;
+            while !planner.is_finished() {
+                let point = planner.sample();
+                assert!(point.velocity >= -EPSILON);
+                maximal = maximal.max(point.position);
+            }
+            assert!(maximal <= target + EPSILON);
+            assert_eq!(planner.reference().position, target);
+        }
+    }
+
+    #[test]
+    fn s_curve_replanning() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        let limits = ProfileLimits::s_curve(2.0, 10.0, 10.0, 100.0);
+        planner.plan(at_rest(0.0), 5.0, &limits);
+        for _ in 0..50 {
+            planner.sample();
+        }
+        let start = planner.reference();
+        assert!(start.acceleration > 1.0);
+
+        // the new move continues with the acceleration of the interrupted one
+        planner.plan(start, 0.5, &limits);
+        let point = planner.sample();
+        assert!((point.acceleration - start.acceleration).abs() <= 100.0 * 0.001 + EPSILON);
+        let end = execute(&mut planner, &limits);
+        assert_eq!(end.position, 0.5);
+    }
+
+    #[test]
+    fn invalid_limits() {
+        let mut planner = TrajectoryPlanner::new(PERIOD);
+        planner.plan(at_rest(3.0), 5.0, &ProfileLimits::default());
+        assert!(planner.is_finished());
+        assert_eq!(planner.sample().position, 3.0);
+    }
+}
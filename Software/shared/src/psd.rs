@@ -1,4 +1,6 @@
 use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
 
 #[derive(Copy, Clone, Default)]
 pub struct ControllerSettings {
@@ -6,6 +8,10 @@ pub struct ControllerSettings {
     integral: f32,
     derivative: f32,
     max_output_amplitude: f32,
+    /// Maximal amplitude of the integral part of the action.
+    integral_limit: f32,
+    /// Time constant of the low-pass filter applied to the derivative part (seconds), zero disables the filter.
+    derivative_filter: f32,
 }
 
 impl ControllerSettings {
@@ -20,6 +26,8 @@ impl ControllerSettings {
             integral,
             derivative,
             max_output_amplitude,
+            integral_limit: max_output_amplitude,
+            derivative_filter: 0.0,
         }
     }
 
@@ -35,6 +43,12 @@ impl ControllerSettings {
     pub fn max_output_amplitude(&self) -> f32 {
         self.max_output_amplitude
     }
+    pub fn integral_limit(&self) -> f32 {
+        self.integral_limit
+    }
+    pub fn derivative_filter(&self) -> f32 {
+        self.derivative_filter
+    }
     pub fn set_proportional(&mut self, proportional: f32) {
         self.proportional = proportional;
     }
@@ -47,12 +61,27 @@ impl ControllerSettings {
     pub fn set_max_output_amplitude(&mut self, max_output_amplitude: f32) {
         self.max_output_amplitude = max_output_amplitude;
     }
+    pub fn set_integral_limit(&mut self, integral_limit: f32) {
+        self.integral_limit = integral_limit;
+    }
+    pub fn set_derivative_filter(&mut self, derivative_filter: f32) {
+        self.derivative_filter = derivative_filter;
+    }
 }
 
+/// Discrete PSD (PID) controller.
+///
+/// The integration is stopped while the action is saturated in the direction of the error (conditional integration)
+/// and the integral part is limited separately by [ControllerSettings::integral_limit()].
+/// The derivative part is calculated from the measured value instead of the error, so that steps of the desired value
+/// do not cause spikes in the action, and it is filtered by a first-order low-pass filter.
 #[derive(Copy, Clone)]
 pub struct PSDController {
     sum: f32,
-    previous: f32,
+    /// The measured value from the previous sample, `None` after reset.
+    previous_actual: Option<f32>,
+    /// The filtered derivative of the measured value.
+    derivative: f32,
     sampling_period: f32, // seconds
 }
 
@@ -60,7 +89,8 @@ impl PSDController {
     pub fn new(sampling_period: Microseconds) -> Self {
         Self {
             sum: 0.0,
-            previous: 0.0,
+            previous_actual: None,
+            derivative: 0.0,
             sampling_period: sampling_period.0 as f32 / 1_000_000.0,
         }
     }
@@ -68,20 +98,145 @@ impl PSDController {
     pub fn sample(&mut self, desired: &f32, actual: &f32, settings: &ControllerSettings) -> f32 {
         let error = desired - actual;
 
-        self.sum += error * self.sampling_period;
-        self.sum = self.sum.clamp(
-            -settings.max_output_amplitude,
-            settings.max_output_amplitude,
-        );
+        let raw_derivative = self
+            .previous_actual
+            .map_or(0.0, |previous| (actual - previous) / self.sampling_period);
+        self.previous_actual = Some(*actual);
+        let filter_gain =
+            self.sampling_period / (settings.derivative_filter.max(0.0) + self.sampling_period);
+        self.derivative += filter_gain * (raw_derivative - self.derivative);
 
-        let action = error * settings.proportional
-            + settings.derivative * (error - self.previous) / self.sampling_period
-            + settings.integral * self.sum;
-        self.previous = error;
+        let proportional_and_derivative =
+            error * settings.proportional - settings.derivative * self.derivative;
 
+        let sum = self.limit_sum(self.sum + error * self.sampling_period, settings);
+        let action = proportional_and_derivative + settings.integral * sum;
+        let saturated = action.abs() > settings.max_output_amplitude;
+        // conditional integration - the integral part must not grow while the action is saturated
+        if !saturated || action.signum() != error.signum() {
+            self.sum = sum;
+        }
+
+        let action = proportional_and_derivative + settings.integral * self.sum;
         action.clamp(
             -settings.max_output_amplitude,
             settings.max_output_amplitude,
         )
     }
+
+    /// Clears the internal state of the controller.
+    /// Should be called when the controller starts to be used after a period of inactivity.
+    pub fn reset(&mut self) {
+        self.sum = 0.0;
+        self.previous_actual = None;
+        self.derivative = 0.0;
+    }
+
+    /// Sets the internal state of the controller so that the next action continues from the `output`
+    /// without a step, given the `actual` measured value and no error (bumpless transfer).
+    pub fn preload(&mut self, output: f32, actual: f32, settings: &ControllerSettings) {
+        self.reset();
+        self.previous_actual = Some(actual);
+        if settings.integral != 0.0 {
+            self.sum = self.limit_sum(output / settings.integral, settings);
+        }
+    }
+
+    /// Limits the sum, so the integral part does not exceed the integral limit.
+    /// Without the integral gain, the sum is limited by the maximal amplitude of the output,
+    /// so it does not wind up until the gain is set.
+    fn limit_sum(&self, sum: f32, settings: &ControllerSettings) -> f32 {
+        let limit = if settings.integral != 0.0 {
+            (settings.integral_limit / settings.integral).abs()
+        } else {
+            settings.max_output_amplitude.abs()
+        };
+        sum.clamp(-limit, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(10_000);
+
+    #[test]
+    fn integral_limit() {
+        let mut settings = ControllerSettings::new(0.0, 1.0, 0.0, 10.0);
+        settings.set_integral_limit(2.0);
+        let mut controller = PSDController::new(PERIOD);
+        let mut action = 0.0;
+        for _ in 0..1000 {
+            action = controller.sample(&1.0, &0.0, &settings);
+        }
+        assert!((action - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn no_windup_without_integral_gain() {
+        let mut settings = ControllerSettings::new(1.0, 0.0, 0.0, 10.0);
+        let mut controller = PSDController::new(PERIOD);
+        for _ in 0..10_000 {
+            controller.sample(&1.0, &0.0, &settings);
+        }
+        assert_eq!(controller.sum, 10.0);
+
+        settings.set_integral(0.1);
+        settings.set_integral_limit(0.5);
+        let action = controller.sample(&1.0, &0.0, &settings);
+        assert!((action - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn no_windup_while_saturated() {
+        let settings = ControllerSettings::new(1.0, 10.0, 0.0, 1.0);
+        let mut controller = PSDController::new(PERIOD);
+        for _ in 0..1000 {
+            assert_eq!(controller.sample(&10.0, &0.0, &settings), 1.0);
+        }
+        // the integral did not accumulate, so the action follows the error immediately
+        let action = controller.sample(&0.0, &0.5, &settings);
+        assert!(action < 0.0);
+    }
+
+    #[test]
+    fn derivative_on_measurement() {
+        let settings = ControllerSettings::new(1.0, 0.0, 1.0, 100.0);
+        let mut controller = PSDController::new(PERIOD);
+        assert_eq!(controller.sample(&0.0, &0.0, &settings), 0.0);
+        // a step of the desired value does not cause a derivative kick
+        assert_eq!(controller.sample(&1.0, &0.0, &settings), 1.0);
+        // a change of the measured value does
+        let action = controller.sample(&1.0, &0.1, &settings);
+        assert!((action - (0.9 - 0.1 / 0.01)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn derivative_filter() {
+        let mut settings = ControllerSettings::new(0.0, 0.0, 1.0, 1000.0);
+        let mut controller = PSDController::new(PERIOD);
+        controller.sample(&0.0, &0.0, &settings);
+        let unfiltered = controller.sample(&0.0, &1.0, &settings);
+
+        settings.set_derivative_filter(0.09);
+        controller.reset();
+        controller.sample(&0.0, &0.0, &settings);
+        let filtered = controller.sample(&0.0, &1.0, &settings);
+        assert!((filtered - unfiltered / 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reset_and_preload() {
+        let settings = ControllerSettings::new(1.0, 2.0, 0.0, 10.0);
+        let mut controller = PSDController::new(PERIOD);
+        for _ in 0..100 {
+            controller.sample(&1.0, &0.0, &settings);
+        }
+        controller.reset();
+        assert!((controller.sample(&1.0, &0.0, &settings) - 1.02).abs() < 1e-4);
+
+        controller.preload(3.0, 5.0, &settings);
+        assert!((controller.sample(&5.0, &5.0, &settings) - 3.0).abs() < 1e-4);
+    }
 }
@@ -0,0 +1,281 @@
+//! Interpolated motion along a stream of position-velocity-time (PVT) points.
+//!
+//! The host pushes the points to a fixed-capacity queue of the axis and the axis follows
+//! cubic Hermite segments between them, so the host does not need to send the targets in real time.
+use crate::models::PvtState;
+use crate::planner::TrajectoryPoint;
+use embedded_time::duration::Microseconds;
+
+/// The number of PVT points that can be buffered for a single axis.
+pub const PVT_QUEUE_CAPACITY: usize = 32;
+
+/// A point of the interpolated trajectory.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PvtPoint {
+    /// Position in revolutions.
+    pub position: f32,
+    /// Velocity in revolutions per second.
+    pub velocity: f32,
+    /// Duration of the segment ending in this point in seconds.
+    pub time: f32,
+}
+
+/// Fixed-capacity FIFO queue of PVT points.
+#[derive(Copy, Clone)]
+pub struct PvtQueue<const CAPACITY: usize> {
+    points: [PvtPoint; CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl<const CAPACITY: usize> PvtQueue<CAPACITY> {
+    pub fn new() -> Self {
+        Self {
+            points: [PvtPoint::default(); CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Appends the point to the end of the queue. Returns the point back when the queue is full.
+    pub fn push(&mut self, point: PvtPoint) -> Result<(), PvtPoint> {
+        if self.is_full() {
+            return Err(point);
+        }
+        self.points[(self.head + self.len) % CAPACITY] = point;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the first point from the queue.
+    pub fn pop(&mut self) -> Option<PvtPoint> {
+        if self.is_empty() {
+            return None;
+        }
+        let point = self.points[self.head];
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        Some(point)
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == CAPACITY
+    }
+
+    pub fn capacity(&self) -> usize {
+        CAPACITY
+    }
+}
+
+impl<const CAPACITY: usize> Default for PvtQueue<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generates the reference of an axis by cubic Hermite interpolation between the points taken from a [PvtQueue].
+///
+/// When the queue runs empty, the axis holds the position of the last point.
+/// Running out of points while the last point had a non-zero velocity is reported as an underrun.
+pub struct PvtInterpolator {
+    period: f32,
+    /// The point the current segment starts from.
+    start: PvtPoint,
+    /// The point the current segment ends in, `None` when no segment is being executed.
+    end: Option<PvtPoint>,
+    /// Time since the start of the current segment in seconds.
+    elapsed: f32,
+    state: PvtState,
+}
+
+impl PvtInterpolator {
+    /// Creates a new interpolator sampled with the `control_period`.
+    pub fn new(control_period: Microseconds) -> Self {
+        Self {
+            period: control_period.0 as f32 / 1_000_000.0,
+            start: PvtPoint::default(),
+            end: None,
+            elapsed: 0.0,
+            state: PvtState::Idle,
+        }
+    }
+
+    /// Stops the interpolation, the next segment starts at rest from the `position` (in revolutions).
+    pub fn reset(&mut self, position: f32) {
+        self.start = PvtPoint {
+            position,
+            velocity: 0.0,
+            time: 0.0,
+        };
+        self.end = None;
+        self.elapsed = 0.0;
+        self.state = PvtState::Idle;
+    }
+
+    /// Advances the interpolation by a single period, taking new points from the `queue` when needed,
+    /// and returns the reference state of the axis.
+    pub fn sample<const CAPACITY: usize>(
+        &mut self,
+        queue: &mut PvtQueue<CAPACITY>,
+    ) -> TrajectoryPoint {
+        if self.end.is_some() {
+            self.elapsed += self.period;
+        }
+        loop {
+            if let Some(end) = self.end {
+                if self.elapsed < end.time {
+                    break;
+                }
+                self.elapsed -= end.time;
+                self.start = end;
+                self.end = None;
+            }
+            match queue.pop() {
+                Some(point) => {
+                    self.end = Some(point);
+                    self.state = PvtState::Running;
+                }
+                None => {
+                    if self.state == PvtState::Running {
+                        self.state = if self.start.velocity == 0.0 {
+                            PvtState::Empty
+                        } else {
+                            PvtState::Underrun
+                        };
+                        // the axis stops at the last point, the next segment starts from the rest
+                        self.start.velocity = 0.0;
+                    }
+                    self.elapsed = 0.0;
+                    break;
+                }
+            }
+        }
+
+        self.reference()
+    }
+
+    /// Returns the reference state of the axis at the current time of the interpolation.
+    pub fn reference(&self) -> TrajectoryPoint {
+        match self.end {
+            Some(end) => Self::interpolate(&self.start, &end, self.elapsed),
+            None => TrajectoryPoint {
+                position: self.start.position,
+                velocity: 0.0,
+                acceleration: 0.0,
+            },
+        }
+    }
+
+    pub fn state(&self) -> PvtState {
+        self.state
+    }
+
+    /// Evaluates the cubic Hermite polynomial going from the `start` to the `end` at the time `t`.
+    fn interpolate(start: &PvtPoint, end: &PvtPoint, t: f32) -> TrajectoryPoint {
+        let duration = end.time;
+        let a = start.position;
+        let b = start.velocity;
+        let c = (3.0 * (end.position - start.position) / duration
+            - 2.0 * start.velocity
+            - end.velocity)
+            / duration;
+        let d = (2.0 * (start.position - end.position) / duration + start.velocity + end.velocity)
+            / (duration * duration);
+        TrajectoryPoint {
+            position: a + t * (b + t * (c + t * d)),
+            velocity: b + t * (2.0 * c + 3.0 * t * d),
+            acceleration: 2.0 * c + 6.0 * t * d,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    fn point(position: f32, velocity: f32, time: f32) -> PvtPoint {
+        PvtPoint {
+            position,
+            velocity,
+            time,
+        }
+    }
+
+    #[test]
+    fn queue() {
+        let mut queue = PvtQueue::<2>::new();
+        assert!(queue.push(point(1.0, 0.0, 1.0)).is_ok());
+        assert!(queue.push(point(2.0, 0.0, 1.0)).is_ok());
+        assert!(queue.is_full());
+        assert_eq!(queue.push(point(3.0, 0.0, 1.0)), Err(point(3.0, 0.0, 1.0)));
+        assert_eq!(queue.pop(), Some(point(1.0, 0.0, 1.0)));
+        assert!(queue.push(point(3.0, 0.0, 1.0)).is_ok());
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some(point(2.0, 0.0, 1.0)));
+        assert_eq!(queue.pop(), Some(point(3.0, 0.0, 1.0)));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn interpolation() {
+        let mut queue = PvtQueue::<4>::new();
+        let mut interpolator = PvtInterpolator::new(PERIOD);
+        interpolator.reset(1.0);
+        assert_eq!(interpolator.sample(&mut queue).position, 1.0);
+        assert_eq!(interpolator.state(), PvtState::Idle);
+
+        queue.push(point(2.0, 1.0, 0.5)).unwrap();
+        queue.push(point(3.0, 0.0, 1.5)).unwrap();
+        let mut previous = interpolator.sample(&mut queue);
+        assert_eq!(interpolator.state(), PvtState::Running);
+        for step in 1..2100 {
+            let reference = interpolator.sample(&mut queue);
+            // the trajectory is continuous
+            assert!((reference.position - previous.position).abs() < 0.01);
+            assert!((reference.velocity - previous.velocity).abs() < 0.1);
+            if step == 500 {
+                // passing the first point
+                assert!((reference.position - 2.0).abs() < 1e-3);
+                assert!((reference.velocity - 1.0).abs() < 1e-2);
+            }
+            previous = reference;
+        }
+        assert_eq!(interpolator.state(), PvtState::Empty);
+        assert!((previous.position - 3.0).abs() < 1e-4);
+        assert_eq!(previous.velocity, 0.0);
+    }
+
+    #[test]
+    fn underrun() {
+        let mut queue = PvtQueue::<4>::new();
+        let mut interpolator = PvtInterpolator::new(PERIOD);
+        interpolator.reset(0.0);
+        queue.push(point(1.0, 2.0, 0.01)).unwrap();
+        for _ in 0..20 {
+            interpolator.sample(&mut queue);
+        }
+        assert_eq!(interpolator.state(), PvtState::Underrun);
+        let reference = interpolator.reference();
+        assert_eq!(reference.position, 1.0);
+        assert_eq!(reference.velocity, 0.0);
+
+        queue.push(point(2.0, 0.0, 0.01)).unwrap();
+        interpolator.sample(&mut queue);
+        assert_eq!(interpolator.state(), PvtState::Running);
+    }
+}
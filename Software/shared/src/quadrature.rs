@@ -0,0 +1,164 @@
+//! Incremental quadrature encoder.
+//!
+//! The pulses of both channels are counted by a [QuadratureCounter], which is sampled periodically.
+//! The counter wraps around, so the difference of two consecutive samples is the distance travelled
+//! as long as the encoder moves less than the half of the range of the counter between the samples.
+//!
+//! The encoder may have an index channel, which pulses once per revolution. The position of the last
+//! index pulse is stored, and when the encoder is armed, the next index pulse becomes the zero position.
+use crate::encoder::{Direction, Encoder};
+use crate::hal::QuadratureCounter;
+use crate::models::{Position, Velocity};
+use embedded_time::duration::Microseconds;
+
+/// Quadrature encoder, the `RESOLUTION` is the number of counted edges per revolution,
+/// which is four times the number of lines of the encoder.
+pub struct QuadratureEncoder<C: QuadratureCounter, const RESOLUTION: u32> {
+    counter: C,
+    past_count: u16,
+    past_position: Position<RESOLUTION>,
+    current_position: Position<RESOLUTION>,
+    current_velocity: Velocity,
+    sampling_period: Microseconds,
+    /// The position of the last index pulse, `None` when no index pulse has been seen yet.
+    index_position: Option<Position<RESOLUTION>>,
+    zero_at_index: bool,
+}
+
+impl<C: QuadratureCounter, const RESOLUTION: u32> QuadratureEncoder<C, RESOLUTION> {
+    pub fn new(counter: C, sampling_period: Microseconds) -> Self {
+        let past_count = counter.count();
+        Self {
+            counter,
+            past_count,
+            past_position: Position::zero(),
+            current_position: Position::zero(),
+            current_velocity: Velocity::zero(),
+            sampling_period,
+            index_position: None,
+            zero_at_index: false,
+        }
+    }
+
+    /// Returns the position of the last index pulse, `None` when no index pulse has been seen yet.
+    pub fn index_position(&self) -> Option<Position<RESOLUTION>> {
+        self.index_position
+    }
+
+    /// Arms the encoder, so the position of the next index pulse becomes the zero position.
+    pub fn arm_zero_at_index(&mut self) {
+        self.zero_at_index = true;
+    }
+
+    /// Returns true when the encoder waits for the index pulse to set the zero position.
+    pub fn is_armed(&self) -> bool {
+        self.zero_at_index
+    }
+}
+
+impl<C: QuadratureCounter, const RESOLUTION: u32> Encoder<RESOLUTION>
+    for QuadratureEncoder<C, RESOLUTION>
+{
+    fn get_velocity(&self) -> Velocity {
+        self.current_velocity
+    }
+
+    fn get_position(&self) -> Position<RESOLUTION> {
+        self.current_position
+    }
+
+    fn reset_position(&mut self) -> Position<RESOLUTION> {
+        let past = self.current_position;
+        self.current_position = Position::zero();
+        self.past_position = Position::zero();
+        self.current_velocity = Velocity::zero();
+        self.index_position = None;
+        past
+    }
+
+    fn sample(&mut self) {
+        let count = self.counter.count();
+        self.current_position += count.wrapping_sub(self.past_count) as i16 as i32;
+
+        if let Some(index) = self.counter.take_index() {
+            let mut position = self.current_position;
+            position -= count.wrapping_sub(index) as i16 as i32;
+            if self.zero_at_index {
+                self.current_position -= &position;
+                self.past_position -= &position;
+                position = Position::zero();
+                self.zero_at_index = false;
+            }
+            self.index_position = Some(position);
+        }
+        self.past_count = count;
+
+        self.current_velocity = Velocity::from_positions(
+            &self.current_position,
+            &self.past_position,
+            self.sampling_period,
+        );
+        self.past_position = self.current_position;
+    }
+
+    /// The quadrature encoder measures the direction by itself, the notification is ignored.
+    fn notify_direction_changed(&mut self, _direction: Direction) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOLUTION: u32 = 400;
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    struct MockCounter {
+        count: u16,
+        index: Option<u16>,
+    }
+
+    impl QuadratureCounter for MockCounter {
+        fn count(&self) -> u16 {
+            self.count
+        }
+
+        fn take_index(&mut self) -> Option<u16> {
+            self.index.take()
+        }
+    }
+
+    fn encoder(count: u16) -> QuadratureEncoder<MockCounter, RESOLUTION> {
+        QuadratureEncoder::new(MockCounter { count, index: None }, PERIOD)
+    }
+
+    #[test]
+    fn counter_wrapping() {
+        let mut encoder = encoder(u16::MAX - 10);
+        encoder.counter.count = 30;
+        encoder.sample();
+        assert_eq!(encoder.get_position().get_increments(), 41);
+        assert_eq!(encoder.get_velocity().get_rps(), 102.5);
+
+        encoder.counter.count = u16::MAX - 100;
+        encoder.sample();
+        assert_eq!(encoder.get_position().get_increments(), -90);
+    }
+
+    #[test]
+    fn index() {
+        let mut encoder = encoder(0);
+        encoder.counter.count = 500;
+        encoder.counter.index = Some(450);
+        encoder.sample();
+        assert_eq!(encoder.index_position().unwrap().get_increments(), 450);
+        assert_eq!(encoder.get_position().get_increments(), 500);
+
+        encoder.arm_zero_at_index();
+        encoder.counter.count = 900;
+        encoder.counter.index = Some(850);
+        encoder.sample();
+        assert!(!encoder.is_armed());
+        assert_eq!(encoder.index_position().unwrap().get_increments(), 0);
+        assert_eq!(encoder.get_position().get_increments(), 50);
+    }
+}
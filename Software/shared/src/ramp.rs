@@ -1,4 +1,5 @@
 use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
 use num_traits::Float;
 
 pub struct TrapRampGen {
@@ -23,4 +24,192 @@ impl TrapRampGen {
         }
         self.current_speed
     }
+
+    /// Restarts the generation from the provided speed.
+    /// Used when the generator takes over from a different ramp generator.
+    pub fn reset(&mut self, current_speed: f32) {
+        self.current_speed = current_speed;
+    }
+}
+
+/// Ramp generator that limits both the acceleration and its rate of change (jerk).
+/// The resulting velocity profile is S-shaped, which avoids steps in the acceleration
+/// at the beginning and at the end of every velocity change.
+pub struct SCurveRampGen {
+    current_speed: f32,
+    current_acceleration: f32,
+    period: f32, // seconds
+}
+
+impl SCurveRampGen {
+    pub fn new(period: Microseconds) -> Self {
+        Self {
+            current_speed: 0.0,
+            current_acceleration: 0.0,
+            period: period.0 as f32 / 1_000_000.0,
+        }
+    }
+
+    /// Generates the next speed sample on the way to the `target_speed`.
+    ///
+    /// # Arguments
+    /// * `target_speed` - the speed the generator shall converge to
+    /// * `max_acceleration` - the maximal allowed acceleration
+    /// * `jerk` - the maximal allowed change of acceleration per second,
+    ///   non-positive values disable the jerk limitation
+    pub fn generate(&mut self, target_speed: f32, max_acceleration: f32, jerk: f32) -> f32 {
+        let max_acceleration = max_acceleration.abs();
+        let jerk_step = if jerk > 0.0 {
+            jerk * self.period
+        } else {
+            max_acceleration
+        }
+        .min(max_acceleration);
+
+        let diff = target_speed - self.current_speed;
+        if diff.abs() <= max_acceleration * self.period
+            && self.current_acceleration.abs() <= jerk_step
+        {
+            self.current_speed = target_speed;
+            self.current_acceleration = 0.0;
+            return self.current_speed;
+        }
+
+        let direction = diff.signum();
+        let increased = (self.current_acceleration + direction * jerk_step)
+            .clamp(-max_acceleration, max_acceleration);
+        let held = self
+            .current_acceleration
+            .clamp(-max_acceleration, max_acceleration);
+
+        self.current_acceleration =
+            if self.can_stop_at(target_speed, direction, increased, jerk_step) {
+                increased
+            } else if self.can_stop_at(target_speed, direction, held, jerk_step) {
+                held
+            } else {
+                (self.current_acceleration - direction * jerk_step)
+                    .clamp(-max_acceleration, max_acceleration)
+            };
+
+        self.current_speed += self.current_acceleration * self.period;
+        self.current_speed
+    }
+
+    /// Returns true when applying the `acceleration` in the next step still allows
+    /// the acceleration to be reduced to zero before the `target_speed` is overshot.
+    fn can_stop_at(
+        &self,
+        target_speed: f32,
+        direction: f32,
+        acceleration: f32,
+        jerk_step: f32,
+    ) -> bool {
+        let speed = self.current_speed + acceleration * self.period;
+        // velocity gained while the acceleration is reduced to zero in steps of `jerk_step`
+        let reduction = acceleration * acceleration.abs() / (2.0 * jerk_step) * self.period
+            - acceleration * self.period / 2.0;
+        (target_speed - speed - reduction) * direction >= 0.0
+    }
+
+    /// Returns the acceleration applied in the last generated step.
+    pub fn current_acceleration(&self) -> f32 {
+        self.current_acceleration
+    }
+
+    /// Restarts the generation from the provided speed with zero acceleration.
+    /// Used when the generator takes over from a different ramp generator.
+    pub fn reset(&mut self, current_speed: f32) {
+        self.current_speed = current_speed;
+        self.current_acceleration = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    #[test]
+    fn trapezoidal_ramp() {
+        let mut generator = TrapRampGen::new(PERIOD);
+        assert!((generator.generate(1.0, 100.0) - 0.1).abs() < 1e-6);
+        for _ in 0..9 {
+            generator.generate(1.0, 100.0);
+        }
+        assert_eq!(generator.generate(1.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn s_curve_respects_limits() {
+        let mut generator = SCurveRampGen::new(PERIOD);
+        let (max_acceleration, jerk) = (50.0, 500.0);
+        let mut previous_acceleration = 0.0;
+        let mut previous_speed = 0.0;
+        let mut reached = None;
+
+        for step in 0..1000 {
+            let speed = generator.generate(10.0, max_acceleration, jerk);
+            let acceleration = generator.current_acceleration();
+
+            assert!(acceleration.abs() <= max_acceleration + 1e-3);
+            assert!((acceleration - previous_acceleration).abs() <= jerk * 0.001 + 1e-3);
+            assert!(speed >= previous_speed);
+            assert!(speed <= 10.0 + 1e-3);
+
+            if reached.is_none() && speed == 10.0 {
+                reached = Some(step);
+            }
+            previous_acceleration = acceleration;
+            previous_speed = speed;
+        }
+
+        // two jerk phases of 0.1 s and a constant acceleration phase of 0.1 s
+        let reached = reached.unwrap();
+        assert!((295..=310).contains(&reached));
+        assert_eq!(generator.current_acceleration(), 0.0);
+    }
+
+    #[test]
+    fn s_curve_reverses_smoothly() {
+        let mut generator = SCurveRampGen::new(PERIOD);
+        for _ in 0..100 {
+            generator.generate(5.0, 50.0, 500.0);
+        }
+
+        let mut previous_acceleration = generator.current_acceleration();
+        for _ in 0..1000 {
+            generator.generate(-5.0, 50.0, 500.0);
+            let acceleration = generator.current_acceleration();
+            assert!((acceleration - previous_acceleration).abs() <= 0.5 + 1e-3);
+            previous_acceleration = acceleration;
+        }
+
+        assert_eq!(generator.generate(-5.0, 50.0, 500.0), -5.0);
+    }
+
+    #[test]
+    fn s_curve_with_excessive_jerk_respects_acceleration() {
+        let max_acceleration = 50.0;
+        for &jerk in &[1e6, 0.0, -1.0] {
+            let mut generator = SCurveRampGen::new(PERIOD);
+            let mut previous_speed = 0.0;
+            for _ in 0..300 {
+                let speed = generator.generate(10.0, max_acceleration, jerk);
+                assert!((speed - previous_speed).abs() <= max_acceleration * 0.001 + 1e-6);
+                previous_speed = speed;
+            }
+            assert_eq!(previous_speed, 10.0);
+        }
+    }
+
+    #[test]
+    fn s_curve_without_jerk_limit() {
+        let mut generator = SCurveRampGen::new(PERIOD);
+        for _ in 0..20 {
+            generator.generate(1.0, 100.0, 0.0);
+        }
+        assert_eq!(generator.generate(1.0, 100.0, 0.0), 1.0);
+    }
 }
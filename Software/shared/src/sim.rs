@@ -0,0 +1,640 @@
+//! Simulated stepper motor with a load, for testing the motion control on the host.
+//!
+//! The rotor is pulled towards the electrical angle commanded by the driver by the torque
+//! `T = -Tpo(ω) · sin(p · (θr - θc))`, where `p` is the number of the pole pairs (a quarter of the full steps
+//! per revolution), `θr` the rotor angle and `θc` the commanded angle. The pull-out torque `Tpo` is proportional
+//! to the current and it is constant up to the corner velocity, above which it falls inversely with the velocity.
+//! The oscillations of the rotor around the commanded angle are damped relative to the rotating field.
+//!
+//! When the load needs more torque than the motor provides, the rotor slips over the maximum of the torque
+//! to the next stable position, which lies four full steps away, and the steps are lost.
+//!
+//! The [SimulatedDriver] and the [SimulatedEncoder] share the same [StepperSimulation], which is advanced
+//! explicitly by the test, so the simulation is deterministic.
+use crate::encoder::{Direction, Encoder};
+use crate::hal::StepperDriver;
+use crate::models::{Position, Velocity};
+use core::f32::consts::PI;
+use embedded_time::duration::Microseconds;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The integration step of the simulation in microseconds.
+const SIMULATION_STEP: u32 = 10;
+
+/// The parameters of the simulated motor.
+#[derive(Copy, Clone, Debug)]
+pub struct MotorParameters {
+    pub full_steps_per_revolution: u32,
+    /// The holding torque at the rated current in N·m.
+    pub holding_torque: f32,
+    /// The rated current in A.
+    pub rated_current: f32,
+    /// The inertia of the rotor in kg·m².
+    pub rotor_inertia: f32,
+    /// The velocity in revolutions per second above which the pull-out torque falls.
+    pub corner_velocity: f32,
+    /// The damping of the rotor relative to the rotating field in N·m·s/rad.
+    pub damping: f32,
+}
+
+/// A NEMA 17 motor with 200 steps per revolution.
+impl Default for MotorParameters {
+    fn default() -> Self {
+        Self {
+            full_steps_per_revolution: 200,
+            holding_torque: 0.4,
+            rated_current: 1.0,
+            rotor_inertia: 5.4e-6,
+            corner_velocity: 2.0,
+            damping: 4.0e-3,
+        }
+    }
+}
+
+/// The parameters of the load driven by the motor.
+#[derive(Copy, Clone, Debug)]
+pub struct LoadParameters {
+    /// The inertia of the load in kg·m².
+    pub inertia: f32,
+    /// The Coulomb friction in N·m.
+    pub friction: f32,
+    /// The viscous friction in N·m·s/rad.
+    pub viscous_friction: f32,
+    /// The external torque acting against the positive direction in N·m.
+    pub torque: f32,
+}
+
+impl Default for LoadParameters {
+    fn default() -> Self {
+        Self {
+            inertia: 2.0e-5,
+            friction: 0.01,
+            viscous_friction: 1.0e-4,
+            torque: 0.0,
+        }
+    }
+}
+
+/// The physical model of the motor and its load.
+struct StepperModel {
+    motor: MotorParameters,
+    load: LoadParameters,
+    /// The commanded frequency in revolutions per second.
+    frequency: f32,
+    microsteps: u16,
+    current: f32,
+    enabled: bool,
+    /// True when the driver has shut down, it recovers when it is disabled.
+    faulted: bool,
+    /// The generated microsteps, the fraction is the part of the microstep that is not generated yet.
+    commanded_microsteps: f64,
+    /// The angle of the rotor in radians.
+    angle: f64,
+    /// The angular velocity of the rotor in radians per second.
+    velocity: f32,
+}
+
+impl StepperModel {
+    fn microsteps_per_revolution(&self) -> u32 {
+        self.motor.full_steps_per_revolution * self.microsteps as u32
+    }
+
+    fn commanded_angle(&self) -> f64 {
+        self.commanded_microsteps.floor() / self.microsteps_per_revolution() as f64
+            * 2.0
+            * core::f64::consts::PI
+    }
+
+    fn pole_pairs(&self) -> f32 {
+        self.motor.full_steps_per_revolution as f32 / 4.0
+    }
+
+    /// Returns true when the windings of the motor are powered by the driver.
+    fn is_powered(&self) -> bool {
+        self.enabled && !self.faulted
+    }
+
+    /// Returns the pull-out torque at the angular `velocity` (radians per second).
+    fn pull_out_torque(&self, velocity: f32) -> f32 {
+        if !self.is_powered() {
+            return 0.0;
+        }
+        let torque = self.motor.holding_torque * self.current / self.motor.rated_current;
+        let rps = velocity.abs() / (2.0 * PI);
+        if rps > self.motor.corner_velocity {
+            torque * self.motor.corner_velocity / rps
+        } else {
+            torque
+        }
+    }
+
+    fn step(&mut self, dt: f32) {
+        self.commanded_microsteps +=
+            (self.frequency * self.microsteps_per_revolution() as f32 * dt) as f64;
+
+        let load_angle = (self.angle - self.commanded_angle()) as f32 * self.pole_pairs();
+        // without the current, there is no field the rotor is damped relative to
+        let field_velocity = if self.is_powered() {
+            self.frequency * 2.0 * PI
+        } else {
+            0.0
+        };
+        let driving = -self.pull_out_torque(self.velocity) * load_angle.sin()
+            - self.motor.damping * (self.velocity - field_velocity)
+            - self.load.viscous_friction * self.velocity
+            - self.load.torque;
+        let inertia = self.motor.rotor_inertia + self.load.inertia;
+
+        let velocity = if self.velocity == 0.0 && driving.abs() <= self.load.friction {
+            // the static friction holds the rotor
+            0.0
+        } else {
+            let friction = if self.velocity != 0.0 {
+                self.load.friction * self.velocity.signum()
+            } else {
+                self.load.friction * driving.signum()
+            };
+            let velocity = self.velocity + (driving - friction) / inertia * dt;
+            // the friction stops the rotor, but does not reverse it
+            if velocity * self.velocity < 0.0 && driving.abs() <= self.load.friction {
+                0.0
+            } else {
+                velocity
+            }
+        };
+        self.angle += ((self.velocity + velocity) / 2.0 * dt) as f64;
+        self.velocity = velocity;
+    }
+}
+
+/// The simulation of a stepper motor with a load.
+#[derive(Clone)]
+pub struct StepperSimulation {
+    model: Rc<RefCell<StepperModel>>,
+}
+
+impl StepperSimulation {
+    pub fn new(motor: MotorParameters, load: LoadParameters) -> Self {
+        Self {
+            model: Rc::new(RefCell::new(StepperModel {
+                motor,
+                load,
+                frequency: 0.0,
+                microsteps: 16,
+                current: motor.rated_current,
+                enabled: true,
+                faulted: false,
+                commanded_microsteps: 0.0,
+                angle: 0.0,
+                velocity: 0.0,
+            })),
+        }
+    }
+
+    /// Returns the driver commanding the simulated motor.
+    pub fn driver(&self) -> SimulatedDriver {
+        SimulatedDriver {
+            model: self.model.clone(),
+        }
+    }
+
+    /// Returns the encoder measuring the rotor, sampled with the `sampling_period`.
+    pub fn encoder<const RESOLUTION: u32>(
+        &self,
+        sampling_period: Microseconds,
+    ) -> SimulatedEncoder<RESOLUTION> {
+        SimulatedEncoder {
+            model: self.model.clone(),
+            zero: 0,
+            past_position: Position::zero(),
+            current_position: Position::zero(),
+            current_velocity: Velocity::zero(),
+            sampling_period,
+        }
+    }
+
+    /// Advances the simulation by the `duration`.
+    pub fn advance(&self, duration: Microseconds) {
+        let mut model = self.model.borrow_mut();
+        let dt = SIMULATION_STEP as f32 / 1_000_000.0;
+        for _ in 0..duration.0 / SIMULATION_STEP {
+            model.step(dt);
+        }
+    }
+
+    /// Returns the position of the rotor in revolutions.
+    pub fn rotor_position(&self) -> f32 {
+        (self.model.borrow().angle / (2.0 * core::f64::consts::PI)) as f32
+    }
+
+    /// Returns the velocity of the rotor in revolutions per second.
+    pub fn rotor_velocity(&self) -> f32 {
+        self.model.borrow().velocity / (2.0 * PI)
+    }
+
+    /// Returns the position commanded by the driver in revolutions.
+    pub fn commanded_position(&self) -> f32 {
+        (self.model.borrow().commanded_angle() / (2.0 * core::f64::consts::PI)) as f32
+    }
+
+    /// Returns the number of the full steps the rotor lags behind the commanded position.
+    pub fn lost_steps(&self) -> i32 {
+        let model = self.model.borrow();
+        let poles = ((model.commanded_angle() - model.angle) as f32 * model.pole_pairs()
+            / (2.0 * PI))
+            .round() as i32;
+        poles * 4
+    }
+
+    /// Sets the external torque acting against the positive direction in N·m.
+    pub fn set_load_torque(&self, torque: f32) {
+        self.model.borrow_mut().load.torque = torque;
+    }
+
+    /// Shuts the driver down as if it was overheated, the driver stays faulted until it is disabled.
+    pub fn inject_driver_fault(&self) {
+        self.model.borrow_mut().faulted = true;
+    }
+
+    pub fn is_driver_enabled(&self) -> bool {
+        self.model.borrow().enabled
+    }
+}
+
+/// The driver of the simulated motor.
+pub struct SimulatedDriver {
+    model: Rc<RefCell<StepperModel>>,
+}
+
+impl StepperDriver for SimulatedDriver {
+    fn set_output_frequency(&mut self, frequency: f32) {
+        self.model.borrow_mut().frequency = frequency;
+    }
+
+    fn output_frequency(&self) -> f32 {
+        self.model.borrow().frequency
+    }
+
+    fn set_microsteps(&mut self, microsteps: u16) {
+        let mut model = self.model.borrow_mut();
+        if model.microsteps != microsteps {
+            // the commanded angle is kept
+            model.commanded_microsteps *= microsteps as f64 / model.microsteps as f64;
+            model.microsteps = microsteps;
+        }
+    }
+
+    fn microsteps_per_revolution(&self) -> u32 {
+        self.model.borrow().microsteps_per_revolution()
+    }
+
+    fn set_current(&mut self, current: f32) {
+        self.model.borrow_mut().current = current.abs();
+    }
+
+    fn enable(&mut self) {
+        self.model.borrow_mut().enabled = true;
+    }
+
+    fn disable(&mut self) {
+        let mut model = self.model.borrow_mut();
+        model.enabled = false;
+        model.faulted = false;
+    }
+
+    fn is_faulted(&mut self) -> bool {
+        self.model.borrow().faulted
+    }
+}
+
+/// The encoder measuring the rotor of the simulated motor with the `RESOLUTION`.
+pub struct SimulatedEncoder<const RESOLUTION: u32> {
+    model: Rc<RefCell<StepperModel>>,
+    /// The increments of the rotor angle corresponding to the zero position.
+    zero: i32,
+    past_position: Position<RESOLUTION>,
+    current_position: Position<RESOLUTION>,
+    current_velocity: Velocity,
+    sampling_period: Microseconds,
+}
+
+impl<const RESOLUTION: u32> SimulatedEncoder<RESOLUTION> {
+    fn increments(&self) -> i32 {
+        let revolutions = self.model.borrow().angle / (2.0 * core::f64::consts::PI);
+        (revolutions * RESOLUTION as f64).floor() as i32
+    }
+}
+
+impl<const RESOLUTION: u32> Encoder<RESOLUTION> for SimulatedEncoder<RESOLUTION> {
+    fn get_velocity(&self) -> Velocity {
+        self.current_velocity
+    }
+
+    fn get_position(&self) -> Position<RESOLUTION> {
+        self.current_position
+    }
+
+    fn reset_position(&mut self) -> Position<RESOLUTION> {
+        let past = self.current_position;
+        self.zero = self.increments();
+        self.current_position = Position::zero();
+        self.past_position = Position::zero();
+        self.current_velocity = Velocity::zero();
+        past
+    }
+
+    fn sample(&mut self) {
+        let mut position = Position::zero();
+        position += self.increments() - self.zero;
+        self.current_position = position;
+        self.current_velocity = Velocity::from_positions(
+            &self.current_position,
+            &self.past_position,
+            self.sampling_period,
+        );
+        self.past_position = self.current_position;
+    }
+
+    /// The encoder measures the rotor directly, the notification is ignored.
+    fn notify_direction_changed(&mut self, _direction: Direction) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canopen::{
+        AxisDictionary, ObjectDictionaryKey, ObjectDictionaryStorage, PersistentStoreAxisDictionary,
+    };
+    use crate::hal::NoHomeSwitch;
+    use crate::models::{Axis, AxisMode, ErrorCode, FaultReaction, RampProfile};
+    use crate::motion_controller::AxisMotionController;
+    use crate::ramp::TrapRampGen;
+    use spin::Mutex;
+    use std::collections::HashMap;
+
+    const RESOLUTION: u32 = 3200;
+    const RAMPING_PERIOD: Microseconds = Microseconds(1000);
+    const CONTROL_PERIOD: Microseconds = Microseconds(10_000);
+
+    #[derive(Default)]
+    struct MockStorage {
+        values: HashMap<u16, [u8; 4]>,
+    }
+
+    impl ObjectDictionaryStorage for MockStorage {
+        fn save_f32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: f32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn save_bool<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: bool) {
+            self.values.insert(key.raw(), [value as u8, 0, 0, 0]);
+        }
+        fn save_u8<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: u8) {
+            self.values.insert(key.raw(), [value, 0, 0, 0]);
+        }
+        fn save_i32<KEY: ObjectDictionaryKey>(&mut self, key: KEY, value: i32) {
+            self.values.insert(key.raw(), value.to_le_bytes());
+        }
+        fn load_f32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<f32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| f32::from_le_bytes(*raw))
+        }
+        fn load_bool<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<bool> {
+            self.values.get(&key.raw()).map(|raw| raw[0] != 0)
+        }
+        fn load_u8<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<u8> {
+            self.values.get(&key.raw()).map(|raw| raw[0])
+        }
+        fn load_i32<KEY: ObjectDictionaryKey>(&self, key: KEY) -> Option<i32> {
+            self.values
+                .get(&key.raw())
+                .map(|raw| i32::from_le_bytes(*raw))
+        }
+    }
+
+    fn dictionary() -> PersistentStoreAxisDictionary<MockStorage, RESOLUTION> {
+        let storage = Box::leak(Box::new(Mutex::new(RefCell::new(MockStorage::default()))));
+        PersistentStoreAxisDictionary::new(Axis::Axis1, storage)
+    }
+
+    /// Runs the `controller` of the `simulation` for the `duration` in seconds.
+    fn run(
+        controller: &mut AxisMotionController<
+            SimulatedDriver,
+            SimulatedEncoder<RESOLUTION>,
+            NoHomeSwitch,
+            RESOLUTION,
+        >,
+        simulation: &StepperSimulation,
+        dictionary: &mut dyn AxisDictionary<RESOLUTION>,
+        duration: f32,
+    ) {
+        let ramps_per_control = CONTROL_PERIOD.0 / RAMPING_PERIOD.0;
+        for _ in 0..(duration * 100.0) as u32 {
+            controller.control(false, dictionary);
+            for _ in 0..ramps_per_control {
+                controller.ramp(false, dictionary);
+                simulation.advance(RAMPING_PERIOD);
+            }
+        }
+    }
+
+    #[test]
+    fn follows_ramp() {
+        let simulation = StepperSimulation::new(Default::default(), Default::default());
+        let mut driver = simulation.driver();
+        let mut ramp = TrapRampGen::new(RAMPING_PERIOD);
+        for _ in 0..2000 {
+            driver.set_output_frequency(ramp.generate(2.0, 20.0));
+            simulation.advance(RAMPING_PERIOD);
+        }
+        assert!((simulation.rotor_velocity() - 2.0).abs() < 0.1);
+        assert!((simulation.rotor_position() - simulation.commanded_position()).abs() < 0.01);
+        assert_eq!(simulation.lost_steps(), 0);
+
+        let mut encoder = simulation.encoder::<RESOLUTION>(RAMPING_PERIOD);
+        encoder.sample();
+        let position = encoder.get_position().get_relative_revolutions();
+        assert!((position - simulation.rotor_position()).abs() < 1.0 / RESOLUTION as f32);
+    }
+
+    #[test]
+    fn overload_loses_steps() {
+        let load = LoadParameters {
+            torque: 0.3,
+            ..Default::default()
+        };
+        let simulation = StepperSimulation::new(Default::default(), load);
+        let mut driver = simulation.driver();
+        driver.set_current(0.5);
+        driver.set_output_frequency(1.0);
+        simulation.advance(Microseconds(1_000_000));
+        assert!(simulation.lost_steps() > 0);
+        assert!(simulation.rotor_position() < 0.5);
+    }
+
+    #[test]
+    fn pull_out() {
+        // the motor cannot be started at a high velocity without a ramp
+        let simulation = StepperSimulation::new(Default::default(), Default::default());
+        let mut driver = simulation.driver();
+        driver.set_output_frequency(10.0);
+        simulation.advance(Microseconds(500_000));
+        assert!(simulation.lost_steps() > 0);
+    }
+
+    #[test]
+    fn closed_loop_position_control() {
+        let simulation = StepperSimulation::new(Default::default(), Default::default());
+        let mut controller = AxisMotionController::new(
+            simulation.driver(),
+            simulation.encoder(CONTROL_PERIOD),
+            NoHomeSwitch,
+            CONTROL_PERIOD,
+            RAMPING_PERIOD,
+        );
+        let mut dictionary = dictionary();
+        dictionary.set_velocity_feedback_control_enabled(true);
+        dictionary.set_mode(AxisMode::Position);
+        dictionary.set_target_position(Position::new(2, RESOLUTION / 2));
+        dictionary.set_enabled(true);
+
+        run(&mut controller, &simulation, &mut dictionary, 3.0);
+        let position = dictionary.actual_position().get_relative_revolutions();
+        assert!((position - 2.5).abs() < 0.01);
+        assert!(dictionary.actual_velocity().get_rps().abs() < 0.1);
+        assert_eq!(simulation.lost_steps(), 0);
+    }
+
+    #[test]
+    fn driver_fault() {
+        let simulation = StepperSimulation::new(Default::default(), Default::default());
+        let mut controller = AxisMotionController::new(
+            simulation.driver(),
+            simulation.encoder(CONTROL_PERIOD),
+            NoHomeSwitch,
+            CONTROL_PERIOD,
+            RAMPING_PERIOD,
+        );
+        let mut dictionary = dictionary();
+        dictionary.set_mode(AxisMode::Velocity);
+        dictionary.set_target_velocity(Velocity::new(1.0));
+        dictionary.set_enabled(true);
+        run(&mut controller, &simulation, &mut dictionary, 0.5);
+        assert!((simulation.rotor_velocity() - 1.0).abs() < 0.1);
+
+        simulation.inject_driver_fault();
+        run(&mut controller, &simulation, &mut dictionary, 0.5);
+        assert_eq!(dictionary.fault(), ErrorCode::DriverError);
+        assert!(!simulation.is_driver_enabled());
+        assert!(simulation.rotor_velocity().abs() < 0.1);
+
+        // the driver recovers once the fault is reset
+        dictionary.reset_fault();
+        run(&mut controller, &simulation, &mut dictionary, 0.5);
+        assert_eq!(dictionary.fault(), ErrorCode::None);
+        assert!(simulation.is_driver_enabled());
+        assert!((simulation.rotor_velocity() - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn velocity_mode_stops_at_limits() {
+        for profile in &[RampProfile::Trapezoidal, RampProfile::SCurve] {
+            for feedback in &[false, true] {
+                let simulation = StepperSimulation::new(Default::default(), Default::default());
+                let mut controller = AxisMotionController::new(
+                    simulation.driver(),
+                    simulation.encoder(CONTROL_PERIOD),
+                    NoHomeSwitch,
+                    CONTROL_PERIOD,
+                    RAMPING_PERIOD,
+                );
+                let mut dictionary = dictionary();
+                dictionary.set_velocity_feedback_control_enabled(*feedback);
+                dictionary.set_ramp_profile(*profile);
+                dictionary.set_min_position(-1.0);
+                dictionary.set_max_position(1.0);
+                dictionary.set_position_limits_enabled(true);
+                dictionary.set_mode(AxisMode::Velocity);
+                dictionary.set_target_velocity(Velocity::new(3.0));
+                dictionary.set_enabled(true);
+
+                let mut max_position = f32::MIN;
+                for _ in 0..200 {
+                    run(&mut controller, &simulation, &mut dictionary, 0.01);
+                    max_position = max_position.max(simulation.rotor_position());
+                }
+                // the motor moves by whole microsteps
+                assert!(max_position <= 1.0 + 1.0 / RESOLUTION as f32);
+                assert!(max_position > 0.99);
+                // stopping at the limit is not a violation of the limit
+                assert_eq!(dictionary.fault(), ErrorCode::None);
+            }
+        }
+    }
+
+    #[test]
+    fn limit_hit_beyond_tolerance() {
+        let simulation = StepperSimulation::new(Default::default(), Default::default());
+        let mut controller = AxisMotionController::new(
+            simulation.driver(),
+            simulation.encoder(CONTROL_PERIOD),
+            NoHomeSwitch,
+            CONTROL_PERIOD,
+            RAMPING_PERIOD,
+        );
+        let mut dictionary = dictionary();
+        dictionary.set_mode(AxisMode::Velocity);
+        dictionary.set_target_velocity(Velocity::new(1.0));
+        dictionary.set_enabled(true);
+        run(&mut controller, &simulation, &mut dictionary, 1.0);
+
+        // the axis is already beyond the limits when they are enabled
+        dictionary.set_max_position(0.5);
+        dictionary.set_limit_tolerance(0.1);
+        dictionary.set_position_limits_enabled(true);
+        run(&mut controller, &simulation, &mut dictionary, 0.1);
+        assert_eq!(dictionary.fault(), ErrorCode::LimitHit);
+
+        // within the tolerance, the limit is not violated
+        dictionary.set_limit_tolerance(1.0);
+        dictionary.reset_fault();
+        run(&mut controller, &simulation, &mut dictionary, 0.1);
+        assert_eq!(dictionary.fault(), ErrorCode::None);
+    }
+
+    #[test]
+    fn following_error_disables_driver() {
+        let load = LoadParameters {
+            torque: 5.0,
+            ..Default::default()
+        };
+        let simulation = StepperSimulation::new(Default::default(), load);
+        let mut controller = AxisMotionController::new(
+            simulation.driver(),
+            simulation.encoder(CONTROL_PERIOD),
+            NoHomeSwitch,
+            CONTROL_PERIOD,
+            RAMPING_PERIOD,
+        );
+        let mut dictionary = dictionary();
+        dictionary.set_velocity_feedback_control_enabled(true);
+        dictionary.set_velocity_error_window(0.2);
+        dictionary.set_velocity_error_time(0.05);
+        dictionary.set_following_error_reaction(FaultReaction::Disable);
+        dictionary.set_mode(AxisMode::Velocity);
+        dictionary.set_target_velocity(Velocity::new(1.0));
+        dictionary.set_enabled(true);
+        run(&mut controller, &simulation, &mut dictionary, 0.5);
+        assert_eq!(dictionary.fault(), ErrorCode::FollowingError);
+        assert!(!dictionary.enabled());
+        assert!(!simulation.is_driver_enabled());
+
+        // the driver recovers once the fault is reset, the axis stays disabled
+        dictionary.reset_fault();
+        run(&mut controller, &simulation, &mut dictionary, 0.1);
+        assert!(simulation.is_driver_enabled());
+        assert!(!dictionary.enabled());
+    }
+}
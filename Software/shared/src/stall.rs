@@ -0,0 +1,128 @@
+//! Detection of the stall of the stepper motor by the StallGuard of the Trinamic drivers.
+//!
+//! The driver measures the back EMF of the motor and reports the StallGuard result, which decreases
+//! with the load of the motor and approaches zero when the motor stalls. The back EMF is too weak at low velocities,
+//! so the result is evaluated only above the minimal velocity. A single low reading may be caused
+//! by a disturbance, so the stall is detected only after several consecutive readings below the threshold.
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// The number of consecutive readings below the threshold that confirm the stall.
+const CONFIRMING_READINGS: u8 = 3;
+
+#[derive(Copy, Clone)]
+pub struct StallSettings {
+    threshold: u16,
+    min_velocity: f32,
+}
+
+impl StallSettings {
+    /// Creates new stall detection settings.
+    ///
+    /// # Arguments
+    /// * `threshold` - the StallGuard result below which the motor is stalled, zero disables the detection
+    /// * `min_velocity` - the minimal velocity of the motor in revolutions per second,
+    ///   below which the StallGuard result is not valid
+    pub fn new(threshold: u16, min_velocity: f32) -> Self {
+        Self {
+            threshold,
+            min_velocity,
+        }
+    }
+
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+    pub fn min_velocity(&self) -> f32 {
+        self.min_velocity
+    }
+    pub fn set_threshold(&mut self, threshold: u16) {
+        self.threshold = threshold;
+    }
+    pub fn set_min_velocity(&mut self, min_velocity: f32) {
+        self.min_velocity = min_velocity;
+    }
+}
+
+impl Default for StallSettings {
+    fn default() -> Self {
+        Self::new(0, 1.0)
+    }
+}
+
+/// Detector of the stall of a single axis, sampled in the control period.
+pub struct StallDetector {
+    /// The number of consecutive readings below the threshold.
+    readings: u8,
+}
+
+impl StallDetector {
+    pub fn new() -> Self {
+        Self { readings: 0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.readings = 0;
+    }
+
+    /// Evaluates the StallGuard `result` read from the driver while the motor turns with the `velocity`
+    /// (in revolutions per second). Returns true while the motor is stalled.
+    pub fn sample(&mut self, result: Option<u16>, velocity: f32, settings: &StallSettings) -> bool {
+        match result {
+            Some(result)
+                if settings.threshold > 0
+                    && velocity.abs() >= settings.min_velocity
+                    && result < settings.threshold =>
+            {
+                self.readings = self.readings.saturating_add(1)
+            }
+            _ => self.readings = 0,
+        }
+        self.readings >= CONFIRMING_READINGS
+    }
+}
+
+impl Default for StallDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stall() {
+        let settings = StallSettings::new(100, 1.0);
+        let mut detector = StallDetector::new();
+        assert!(!detector.sample(Some(300), 2.0, &settings));
+        assert!(!detector.sample(Some(50), 2.0, &settings));
+        assert!(!detector.sample(Some(50), -2.0, &settings));
+        assert!(detector.sample(Some(50), -2.0, &settings));
+        assert!(detector.sample(Some(0), -2.0, &settings));
+
+        // a single reading above the threshold restarts the confirmation
+        assert!(!detector.sample(Some(120), 2.0, &settings));
+        assert!(!detector.sample(Some(50), 2.0, &settings));
+    }
+
+    #[test]
+    fn invalid_readings() {
+        let mut detector = StallDetector::new();
+        // the result is not valid at low velocities
+        let settings = StallSettings::new(100, 1.0);
+        for _ in 0..5 {
+            assert!(!detector.sample(Some(0), 0.5, &settings));
+        }
+        // the driver does not measure the load
+        for _ in 0..5 {
+            assert!(!detector.sample(None, 2.0, &settings));
+        }
+        // the detection is disabled
+        let settings = StallSettings::default();
+        for _ in 0..5 {
+            assert!(!detector.sample(Some(0), 2.0, &settings));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Detection of the steps lost by the stepper motor.
+//!
+//! The position commanded to the motor is accumulated from the frequency actually generated by the driver,
+//! and compared with the position measured by the encoder. When the motor slips, the measured position
+//! lags behind the commanded one. The detection makes sense only with an encoder measuring the motor
+//! independently of the generated steps, e.g. a quadrature or an absolute encoder.
+//!
+//! Only the deviation of the commanded position from the measured position is accumulated,
+//! so the precision does not degrade with the travelled distance.
+use crate::models::{Position, StepLossReaction};
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+#[derive(Copy, Clone)]
+pub struct StepLossSettings {
+    threshold: f32,
+    encoder_ratio: f32,
+    reaction: StepLossReaction,
+}
+
+impl StepLossSettings {
+    /// Creates new step loss settings.
+    ///
+    /// # Arguments
+    /// * `threshold` - the allowed deviation of the motor from the commanded position in revolutions,
+    ///   zero disables the detection. It shall exceed the distance travelled by the motor in a single control period.
+    /// * `encoder_ratio` - the revolutions of the encoder per a single revolution of the motor
+    /// * `reaction` - the reaction to the detected step loss
+    pub fn new(threshold: f32, encoder_ratio: f32, reaction: StepLossReaction) -> Self {
+        Self {
+            threshold,
+            encoder_ratio,
+            reaction,
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+    pub fn encoder_ratio(&self) -> f32 {
+        self.encoder_ratio
+    }
+    pub fn reaction(&self) -> StepLossReaction {
+        self.reaction
+    }
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+    pub fn set_encoder_ratio(&mut self, encoder_ratio: f32) {
+        self.encoder_ratio = encoder_ratio;
+    }
+    pub fn set_reaction(&mut self, reaction: StepLossReaction) {
+        self.reaction = reaction;
+    }
+}
+
+impl Default for StepLossSettings {
+    fn default() -> Self {
+        Self::new(0.0, 1.0, StepLossReaction::default())
+    }
+}
+
+/// Detector of the step loss of a single axis.
+/// The commanded position is accumulated in the ramping period, the measured position is checked in the control period.
+pub struct StepLossMonitor {
+    period: f32,
+    /// The deviation of the commanded position from the measured position of the motor in revolutions.
+    deviation: f32,
+    /// The position of the encoder in the last check in increments, `None` before the first check.
+    past_increments: Option<i32>,
+}
+
+impl StepLossMonitor {
+    pub fn new(ramping_period: Microseconds) -> Self {
+        Self {
+            period: ramping_period.0 as f32 / 1_000_000.0,
+            deviation: 0.0,
+            past_increments: None,
+        }
+    }
+
+    /// Forgets the position of the encoder, which shall be called whenever the position of the encoder is reset.
+    pub fn reset(&mut self) {
+        self.deviation = 0.0;
+        self.past_increments = None;
+    }
+
+    /// Corrects the commanded position to the measured position.
+    pub fn resynchronize(&mut self) {
+        self.deviation = 0.0;
+    }
+
+    /// Returns the deviation of the commanded position from the measured position of the motor in revolutions.
+    pub fn deviation(&self) -> f32 {
+        self.deviation
+    }
+
+    /// Accumulates the commanded position with the `frequency` (in revolutions per second) generated in the last ramping period.
+    pub fn command(&mut self, frequency: f32) {
+        self.deviation += frequency * self.period;
+    }
+
+    /// Compares the commanded position with the `position` measured by the encoder.
+    /// Returns true when the motor deviates from the commanded position by more than the threshold.
+    pub fn check<const RESOLUTION: u32>(
+        &mut self,
+        position: &Position<RESOLUTION>,
+        settings: &StepLossSettings,
+    ) -> bool {
+        let increments = position.get_increments();
+        if let Some(past) = self.past_increments {
+            let measured = (increments - past) as f32 / RESOLUTION as f32;
+            if settings.encoder_ratio != 0.0 {
+                self.deviation -= measured / settings.encoder_ratio;
+            }
+        }
+        self.past_increments = Some(increments);
+
+        settings.threshold > 0.0 && self.deviation.abs() > settings.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOLUTION: u32 = 400;
+
+    fn position(increments: i32) -> Position<RESOLUTION> {
+        let mut position = Position::zero();
+        position += increments;
+        position
+    }
+
+    #[test]
+    fn step_loss() {
+        let settings = StepLossSettings::new(0.1, 2.0, StepLossReaction::Fault);
+        let mut monitor = StepLossMonitor::new(Microseconds(1000));
+        assert!(!monitor.check(&position(1000), &settings));
+
+        // the encoder turns twice as fast as the motor
+        for _ in 0..10 {
+            monitor.command(100.0);
+        }
+        assert!(!monitor.check(&position(1800), &settings));
+        assert!(monitor.deviation().abs() < 1e-4);
+
+        // the motor slips by a quarter of a revolution
+        for _ in 0..10 {
+            monitor.command(-100.0);
+        }
+        assert!(monitor.check(&position(1200), &settings));
+        assert!((monitor.deviation() + 0.25).abs() < 1e-4);
+
+        monitor.resynchronize();
+        assert!(!monitor.check(&position(1200), &settings));
+    }
+
+    #[test]
+    fn disabled() {
+        let settings = StepLossSettings::default();
+        let mut monitor = StepLossMonitor::new(Microseconds(1000));
+        monitor.check(&position(0), &settings);
+        monitor.command(1000.0);
+        assert!(!monitor.check(&position(0), &settings));
+    }
+}
@@ -1,60 +1,107 @@
-use crate::hal::{DACChannel, StepGenerator, StepperDriver};
+use crate::hal::{DACChannel, StepGenerator, StepperDriver, TristatePin};
 use embedded_time::rate::Hertz;
+#[cfg(not(any(test, feature = "std")))]
 use num_traits::Float;
 
 const V_FS: f32 = 0.32; // V
 const R_OFFSET: f32 = 0.02; // Ohm
 const MAX_V_REF: u16 = 2500; // mV
 
-pub struct TMC2100<G, STEP, DIR, DAC> {
+/// The microsteps per a full step the TMC2100 can be switched to by its CFG1 pin, when its CFG2 pin is left open.
+pub const TMC2100_MICROSTEPS: &[u16] = &[4, 16];
+
+/// The TMC2100 driver, the steps are generated by the step and direction pins
+/// and the current is set by the reference voltage of the DAC.
+/// The driver is enabled by the low level of the enable pin (CFG6_ENN) and it reports the overtemperature
+/// and the short circuits by the high level of the error pin.
+/// The microstepping is selected by the CFG1 pin with the CFG2 pin left open, see [TMC2100_MICROSTEPS].
+/// The steps are interpolated to 256 microsteps in stealthChop in both settings.
+pub struct TMC2100<G, STEP, DIR, DAC, EN, ERR, CFG> {
     generator: G,
     _step_pin: STEP,
     dir_pin: DIR,
     current_dac: DAC,
+    enable_pin: EN,
+    error_pin: ERR,
+    cfg1_pin: CFG,
     sense_r: f32,
+    steps_per_revolution: u32,
     microsteps_per_revolution: f32,
+    /// Positive or negative one depending on the direction of the output frequency.
+    direction: f32,
 }
 
-impl<G, STEP, DIR, DAC> TMC2100<G, STEP, DIR, DAC>
+impl<G, STEP, DIR, DAC, EN, ERR, CFG> TMC2100<G, STEP, DIR, DAC, EN, ERR, CFG>
 where
     G: StepGenerator,
     DIR: embedded_hal::digital::v2::OutputPin,
     DAC: DACChannel,
+    EN: embedded_hal::digital::v2::OutputPin,
+    ERR: embedded_hal::digital::v2::InputPin,
+    CFG: TristatePin,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         generator: G,
         step_pin: STEP,
         dir_pin: DIR,
         current_dac: DAC,
+        enable_pin: EN,
+        error_pin: ERR,
+        cfg1_pin: CFG,
         sense_r: f32,
-        microsteps_per_revolution: u32,
+        steps_per_revolution: u32,
+        microsteps: u16,
     ) -> Self {
         let mut s = Self {
             generator,
             _step_pin: step_pin,
             dir_pin,
             current_dac,
+            enable_pin,
+            error_pin,
+            cfg1_pin,
             sense_r,
-            microsteps_per_revolution: microsteps_per_revolution as f32,
+            steps_per_revolution,
+            // the CFG1 pin is open after the reset, which selects 16 microsteps
+            microsteps_per_revolution: (steps_per_revolution * 16) as f32,
+            direction: 1.0,
         };
 
+        s.select_microsteps(microsteps);
         s.set_current(0.2);
+        s.enable();
 
         s
     }
+
+    /// Switches the CFG1 pin to the `microsteps`, unsupported microsteps are ignored.
+    fn select_microsteps(&mut self, microsteps: u16) {
+        match microsteps {
+            4 => self.cfg1_pin.set_high(),
+            16 => self.cfg1_pin.set_open(),
+            _ => return,
+        }
+        self.microsteps_per_revolution = (self.steps_per_revolution * microsteps as u32) as f32;
+    }
 }
 
-impl<G, STEP, DIR, DAC> StepperDriver for TMC2100<G, STEP, DIR, DAC>
+impl<G, STEP, DIR, DAC, EN, ERR, CFG> StepperDriver for TMC2100<G, STEP, DIR, DAC, EN, ERR, CFG>
 where
     G: StepGenerator,
     DIR: embedded_hal::digital::v2::OutputPin,
     DAC: DACChannel,
+    EN: embedded_hal::digital::v2::OutputPin,
+    ERR: embedded_hal::digital::v2::InputPin,
+    CFG: TristatePin,
 {
     fn set_output_frequency(&mut self, frequency: f32) {
         if frequency < 0.0 {
             self.dir_pin.set_high().ok();
+            self.direction = -1.0;
         } else {
             self.dir_pin.set_low().ok();
+            self.direction = 1.0;
         };
 
         self.generator.set_step_frequency(Hertz::new(
@@ -62,9 +109,165 @@ where
         ))
     }
 
+    fn output_frequency(&self) -> f32 {
+        self.direction * self.generator.step_frequency() / self.microsteps_per_revolution
+    }
+
+    /// The microstepping is switched in standstill only, as the CFG pins may pass through another setting
+    /// while being switched. Unsupported microsteps are ignored.
+    fn set_microsteps(&mut self, microsteps: u16) {
+        let microsteps_per_revolution = self.steps_per_revolution * microsteps as u32;
+        if microsteps_per_revolution != self.microsteps_per_revolution as u32
+            && self.generator.step_frequency() == 0.0
+        {
+            self.select_microsteps(microsteps);
+        }
+    }
+
+    fn microsteps_per_revolution(&self) -> u32 {
+        self.microsteps_per_revolution as u32
+    }
+
     fn set_current(&mut self, current: f32) {
         let voltage =
             (current.abs() * MAX_V_REF as f32 / V_FS * (self.sense_r + R_OFFSET) / 0.707) as u16;
         self.current_dac.set_output_voltage(voltage.min(MAX_V_REF));
     }
+
+    fn enable(&mut self) {
+        self.enable_pin.set_low().ok();
+    }
+
+    fn disable(&mut self) {
+        self.enable_pin.set_high().ok();
+    }
+
+    fn is_faulted(&mut self) -> bool {
+        self.error_pin.is_high().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+    #[derive(Default)]
+    struct MockGenerator {
+        frequency: u32,
+    }
+
+    impl StepGenerator for MockGenerator {
+        fn set_step_frequency(&mut self, frequency: Hertz) {
+            self.frequency = frequency.0;
+        }
+
+        fn step_frequency(&self) -> f32 {
+            self.frequency as f32
+        }
+    }
+
+    struct MockDAC;
+
+    impl DACChannel for MockDAC {
+        fn set_output_voltage(&mut self, _voltage: u16) {}
+    }
+
+    struct MockPin;
+
+    impl OutputPin for MockPin {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl InputPin for MockPin {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(false)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Level {
+        Low,
+        High,
+        Open,
+    }
+
+    struct MockCfgPin(Level);
+
+    impl TristatePin for MockCfgPin {
+        fn set_low(&mut self) {
+            self.0 = Level::Low;
+        }
+
+        fn set_high(&mut self) {
+            self.0 = Level::High;
+        }
+
+        fn set_open(&mut self) {
+            self.0 = Level::Open;
+        }
+    }
+
+    fn driver(
+        microsteps: u16,
+    ) -> TMC2100<MockGenerator, MockPin, MockPin, MockDAC, MockPin, MockPin, MockCfgPin> {
+        TMC2100::new(
+            MockGenerator::default(),
+            MockPin,
+            MockPin,
+            MockDAC,
+            MockPin,
+            MockPin,
+            MockCfgPin(Level::Low),
+            0.22,
+            200,
+            microsteps,
+        )
+    }
+
+    #[test]
+    fn microsteps_select_cfg1() {
+        let mut driver = driver(16);
+        assert_eq!(driver.cfg1_pin.0, Level::Open);
+        assert_eq!(driver.microsteps_per_revolution(), 3200);
+
+        driver.set_microsteps(4);
+        assert_eq!(driver.cfg1_pin.0, Level::High);
+        assert_eq!(driver.microsteps_per_revolution(), 800);
+
+        // unsupported microsteps are ignored
+        driver.set_microsteps(8);
+        assert_eq!(driver.cfg1_pin.0, Level::High);
+        assert_eq!(driver.microsteps_per_revolution(), 800);
+    }
+
+    #[test]
+    fn microsteps_switched_in_standstill() {
+        let mut driver = driver(4);
+        driver.set_output_frequency(1.0);
+        driver.set_microsteps(16);
+        assert_eq!(driver.cfg1_pin.0, Level::High);
+        assert_eq!(driver.microsteps_per_revolution(), 800);
+        assert_eq!(driver.output_frequency(), 1.0);
+
+        driver.set_output_frequency(0.0);
+        driver.set_microsteps(16);
+        assert_eq!(driver.cfg1_pin.0, Level::Open);
+        assert_eq!(driver.microsteps_per_revolution(), 3200);
+    }
 }
@@ -0,0 +1,407 @@
+use crate::hal::{StepGenerator, StepperDriver, TrinamicUart};
+use crate::trinamic::*;
+use core::f32::consts::SQRT_2;
+use embedded_time::rate::Hertz;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+const V_FS: f32 = 0.325; // V
+/// The full scale voltage of the sense resistors with the high sensitivity.
+const V_FS_HIGH_SENSITIVITY: f32 = 0.18; // V
+const R_OFFSET: f32 = 0.02; // Ohm
+/// The delay of the power down to the hold current, the hold current equals the run current
+/// as the current is controlled by the motion controller.
+const HOLD_DELAY: u8 = 1;
+/// The StallGuard result occupies the lowest 10 bits of the register.
+const SG_RESULT_MASK: u32 = 0x3ff;
+
+/// The TMC2209 (or TMC2208) driver, the steps are generated by the step and direction pins
+/// and the driver is configured over the single wire UART.
+/// The current is set digitally by the current scale, the full scale current is lowered by the high sensitivity
+/// of the sense resistors for the low currents, so the current is set with a better resolution.
+/// The registers are written only when their value changes, the errors of the writes are available
+/// by [Self::last_error()].
+pub struct TMC2209<G, STEP, DIR, U: TrinamicUart> {
+    generator: G,
+    _step_pin: STEP,
+    dir_pin: DIR,
+    uart: U,
+    address: u8,
+    sense_r: f32,
+    steps_per_revolution: u32,
+    microsteps_per_revolution: f32,
+    /// Positive or negative one depending on the direction of the output frequency.
+    direction: f32,
+    chopper: ChopperConfig,
+    current: HoldRunCurrent,
+    last_error: Option<TrinamicError<U::Error>>,
+}
+
+impl<G, STEP, DIR, U> TMC2209<G, STEP, DIR, U>
+where
+    G: StepGenerator,
+    DIR: embedded_hal::digital::v2::OutputPin,
+    U: TrinamicUart,
+    U::Error: Copy,
+{
+    /// Creates a new driver and configures it.
+    ///
+    /// # Arguments
+    /// * `address` - the slave address of the driver selected by the MS1 and MS2 pins, 0 to 3
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        generator: G,
+        step_pin: STEP,
+        dir_pin: DIR,
+        uart: U,
+        address: u8,
+        sense_r: f32,
+        steps_per_revolution: u32,
+        microsteps: u16,
+    ) -> Self {
+        let mut chopper = ChopperConfig::default();
+        chopper.set_microsteps(microsteps).ok();
+        let mut s = Self {
+            generator,
+            _step_pin: step_pin,
+            dir_pin,
+            uart,
+            address,
+            sense_r,
+            steps_per_revolution,
+            microsteps_per_revolution: (steps_per_revolution * chopper.microsteps() as u32) as f32,
+            direction: 1.0,
+            chopper,
+            current: HoldRunCurrent {
+                hold: 0,
+                run: 0,
+                hold_delay: HOLD_DELAY,
+            },
+            last_error: None,
+        };
+
+        let (high_sensitivity, scale) = s.current_scale(0.2);
+        s.chopper.high_sensitivity = high_sensitivity;
+        s.current.hold = scale;
+        s.current.run = scale;
+        s.last_error = s.configure().err();
+
+        s
+    }
+
+    /// Writes the whole configuration to the driver, e.g. after the driver has been reset.
+    pub fn configure(&mut self) -> Result<(), TrinamicError<U::Error>> {
+        self.write_register(
+            TrinamicRegister::GlobalConfig,
+            GlobalConfig::uart_controlled().into(),
+        )?;
+        self.write_register(TrinamicRegister::ChopperConfig, self.chopper.into())?;
+        self.write_register(TrinamicRegister::HoldRunCurrent, self.current.into())
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the error of the last write of the configuration, `None` when the last write was successful.
+    pub fn last_error(&self) -> Option<TrinamicError<U::Error>> {
+        self.last_error
+    }
+
+    pub fn read_register(
+        &mut self,
+        register: TrinamicRegister,
+    ) -> Result<u32, TrinamicError<U::Error>> {
+        self.uart
+            .write(&read_request(self.address, register))
+            .map_err(TrinamicError::Bus)?;
+        let mut reply = [0u8; DATAGRAM_LENGTH];
+        self.uart.read(&mut reply).map_err(TrinamicError::Bus)?;
+        parse_reply(&reply, register)
+    }
+
+    pub fn write_register(
+        &mut self,
+        register: TrinamicRegister,
+        value: u32,
+    ) -> Result<(), TrinamicError<U::Error>> {
+        self.uart
+            .write(&write_datagram(self.address, register, value))
+            .map_err(TrinamicError::Bus)
+    }
+
+    pub fn driver_status(&mut self) -> Result<DriverStatus, TrinamicError<U::Error>> {
+        self.read_register(TrinamicRegister::DriverStatus)
+            .map(DriverStatus::from)
+    }
+
+    /// Returns the high sensitivity of the sense resistors and the current scale setting the `current`.
+    fn current_scale(&self, current: f32) -> (bool, u8) {
+        let scale = |full_scale: f32| {
+            (current.abs() * 32.0 * SQRT_2 * (self.sense_r + R_OFFSET) / full_scale - 1.0).round()
+        };
+        let high_sensitivity = scale(V_FS) < 16.0;
+        let full_scale = if high_sensitivity {
+            V_FS_HIGH_SENSITIVITY
+        } else {
+            V_FS
+        };
+        (high_sensitivity, scale(full_scale).clamp(0.0, 31.0) as u8)
+    }
+
+    fn update_chopper(&mut self, chopper: ChopperConfig) {
+        if chopper == self.chopper {
+            return;
+        }
+        self.last_error = self
+            .write_register(TrinamicRegister::ChopperConfig, chopper.into())
+            .err();
+        if self.last_error.is_none() {
+            self.chopper = chopper;
+        }
+    }
+
+    fn update_current(&mut self, current: HoldRunCurrent) {
+        if current == self.current {
+            return;
+        }
+        self.last_error = self
+            .write_register(TrinamicRegister::HoldRunCurrent, current.into())
+            .err();
+        if self.last_error.is_none() {
+            self.current = current;
+        }
+    }
+}
+
+impl<G, STEP, DIR, U> StepperDriver for TMC2209<G, STEP, DIR, U>
+where
+    G: StepGenerator,
+    DIR: embedded_hal::digital::v2::OutputPin,
+    U: TrinamicUart,
+    U::Error: Copy,
+{
+    fn set_output_frequency(&mut self, frequency: f32) {
+        if frequency < 0.0 {
+            self.dir_pin.set_high().ok();
+            self.direction = -1.0;
+        } else {
+            self.dir_pin.set_low().ok();
+            self.direction = 1.0;
+        };
+
+        self.generator.set_step_frequency(Hertz::new(
+            (frequency.abs() * self.microsteps_per_revolution) as u32,
+        ))
+    }
+
+    fn output_frequency(&self) -> f32 {
+        self.direction * self.generator.step_frequency() / self.microsteps_per_revolution
+    }
+
+    /// The microstep resolution of the driver is set as well, unsupported resolutions are ignored.
+    fn set_microsteps(&mut self, microsteps: u16) {
+        let mut chopper = self.chopper;
+        if chopper.set_microsteps(microsteps).is_ok() {
+            self.update_chopper(chopper);
+            self.microsteps_per_revolution =
+                (self.steps_per_revolution * self.chopper.microsteps() as u32) as f32;
+        }
+    }
+
+    fn microsteps_per_revolution(&self) -> u32 {
+        self.microsteps_per_revolution as u32
+    }
+
+    fn set_current(&mut self, current: f32) {
+        let (high_sensitivity, scale) = self.current_scale(current);
+        let mut chopper = self.chopper;
+        chopper.high_sensitivity = high_sensitivity;
+        self.update_chopper(chopper);
+        self.update_current(HoldRunCurrent {
+            hold: scale,
+            run: scale,
+            hold_delay: HOLD_DELAY,
+        });
+    }
+
+    /// The result is valid only in the StealthChop mode, which the driver is configured with.
+    fn stall_guard_result(&mut self) -> Option<u16> {
+        self.read_register(TrinamicRegister::StallGuardResult)
+            .ok()
+            .map(|raw| (raw & SG_RESULT_MASK) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MockUart {
+        /// The registers with their values replied to the read requests.
+        registers: HashMap<u8, u32>,
+        written: Vec<Vec<u8>>,
+        pending: Option<u8>,
+        fail: bool,
+    }
+
+    impl MockUart {
+        /// Returns the registers and values written to the driver.
+        fn writes(&self) -> Vec<(u8, u32)> {
+            self.written
+                .iter()
+                .filter(|datagram| datagram.len() == DATAGRAM_LENGTH)
+                .map(|datagram| {
+                    (
+                        datagram[2] & 0x7f,
+                        u32::from_be_bytes([datagram[3], datagram[4], datagram[5], datagram[6]]),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    impl TrinamicUart for MockUart {
+        type Error = ();
+
+        fn write(&mut self, datagram: &[u8]) -> Result<(), Self::Error> {
+            if self.fail {
+                return Err(());
+            }
+            if datagram.len() == READ_REQUEST_LENGTH {
+                self.pending = Some(datagram[2]);
+            }
+            self.written.push(datagram.to_vec());
+            Ok(())
+        }
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            let register = self.pending.take().ok_or(())?;
+            buffer[..3].copy_from_slice(&[0x05, 0xff, register]);
+            let value = self.registers.get(&register).ok_or(())?;
+            buffer[3..7].copy_from_slice(&value.to_be_bytes());
+            buffer[7] = crc8(&buffer[..7]);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockGenerator {
+        frequency: u32,
+    }
+
+    impl StepGenerator for MockGenerator {
+        fn set_step_frequency(&mut self, frequency: Hertz) {
+            self.frequency = frequency.0;
+        }
+
+        fn step_frequency(&self) -> f32 {
+            self.frequency as f32
+        }
+    }
+
+    #[derive(Default)]
+    struct MockPin {
+        high: bool,
+    }
+
+    impl embedded_hal::digital::v2::OutputPin for MockPin {
+        type Error = ();
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    fn driver() -> TMC2209<MockGenerator, (), MockPin, MockUart> {
+        TMC2209::new(
+            MockGenerator::default(),
+            (),
+            MockPin::default(),
+            MockUart::default(),
+            0,
+            0.11,
+            200,
+            16,
+        )
+    }
+
+    #[test]
+    fn configuration() {
+        let driver = driver();
+        assert!(driver.last_error().is_none());
+        assert_eq!(
+            driver.uart.writes(),
+            [
+                (0x00, 0x0000_01c0),
+                (0x6c, 0x1402_0053),
+                (0x10, 0x0001_0606)
+            ]
+        );
+    }
+
+    #[test]
+    fn current() {
+        let mut driver = driver();
+        driver.uart.written.clear();
+        driver.set_current(1.0);
+        driver.set_current(1.0);
+        assert_eq!(
+            driver.uart.writes(),
+            [(0x6c, 0x1400_0053), (0x10, 0x0001_1111)]
+        );
+
+        driver.uart.fail = true;
+        driver.set_current(2.0);
+        assert_eq!(driver.last_error(), Some(TrinamicError::Bus(())));
+        // the failed write is repeated
+        driver.uart.fail = false;
+        driver.set_current(2.0);
+        assert!(driver.last_error().is_none());
+        assert_eq!(driver.current.run, 31);
+    }
+
+    #[test]
+    fn steps() {
+        let mut driver = driver();
+        driver.set_output_frequency(-1.0);
+        assert!(driver.dir_pin.high);
+        assert_eq!(driver.generator.frequency, 3200);
+        assert_eq!(driver.output_frequency(), -1.0);
+
+        driver.uart.written.clear();
+        driver.set_microsteps(8);
+        driver.set_microsteps(12);
+        assert_eq!(driver.microsteps_per_revolution(), 1600);
+        assert_eq!(driver.uart.writes(), [(0x6c, 0x1502_0053)]);
+    }
+
+    #[test]
+    fn status() {
+        let mut driver = driver();
+        driver.uart.registers.insert(0x6f, 0x0000_0002);
+        let status = driver.driver_status().unwrap();
+        assert!(status.overtemperature);
+        assert!(status.is_fault());
+        assert_eq!(
+            driver.read_register(TrinamicRegister::InterfaceCounter),
+            Err(TrinamicError::Bus(()))
+        );
+    }
+
+    #[test]
+    fn stall_guard() {
+        let mut driver = driver();
+        assert_eq!(driver.stall_guard_result(), None);
+        driver.uart.registers.insert(0x41, 0xfc00_0123);
+        assert_eq!(driver.stall_guard_result(), Some(0x123));
+    }
+}
@@ -0,0 +1,491 @@
+//! The single wire UART interface of the Trinamic smart drivers (TMC2208, TMC2209).
+//!
+//! The datagrams start with the sync byte followed by the slave address and the register address,
+//! whose most significant bit distinguishes a write from a read. A write datagram carries 32 bits of data,
+//! a read request carries no data and the driver replies with the data of the register addressed to the master.
+//! The data are transmitted with the most significant byte first and every datagram ends with the CRC8
+//! of the preceding bytes, calculated with the polynomial `x^8 + x^2 + x + 1` over the bits in the order
+//! they are transmitted, that is the least significant bit of each byte first.
+//!
+//! The registers with their own struct are typed, the others are accessed as raw values.
+
+/// The sync byte starting every datagram, the upper four bits are reserved and zero.
+const SYNC: u8 = 0x05;
+/// The address the replies of the driver are sent to.
+const MASTER_ADDRESS: u8 = 0xff;
+const WRITE: u8 = 0x80;
+
+/// The length of the write datagram and of the reply to the read request.
+pub const DATAGRAM_LENGTH: usize = 8;
+/// The length of the read request.
+pub const READ_REQUEST_LENGTH: usize = 4;
+
+/// The registers of the drivers. The StallGuard and CoolStep registers are available in the TMC2209 only.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TrinamicRegister {
+    GlobalConfig,
+    GlobalStatus,
+    /// The counter of the successful write accesses.
+    InterfaceCounter,
+    SlaveConfig,
+    Inputs,
+    HoldRunCurrent,
+    PowerDownDelay,
+    /// The measured time between two microsteps.
+    StepTime,
+    StealthChopThreshold,
+    CoolStepThreshold,
+    /// The velocity generated by the internal pulse generator.
+    Velocity,
+    StallGuardThreshold,
+    StallGuardResult,
+    CoolStepConfig,
+    MicrostepCounter,
+    ChopperConfig,
+    DriverStatus,
+    StealthChopConfig,
+}
+
+impl TrinamicRegister {
+    pub fn readable(&self) -> bool {
+        !matches!(
+            self,
+            Self::SlaveConfig
+                | Self::HoldRunCurrent
+                | Self::PowerDownDelay
+                | Self::StealthChopThreshold
+                | Self::CoolStepThreshold
+                | Self::Velocity
+                | Self::StallGuardThreshold
+                | Self::CoolStepConfig
+        )
+    }
+
+    pub fn writeable(&self) -> bool {
+        !matches!(
+            self,
+            Self::InterfaceCounter
+                | Self::Inputs
+                | Self::StepTime
+                | Self::StallGuardResult
+                | Self::MicrostepCounter
+                | Self::DriverStatus
+        )
+    }
+}
+
+impl From<TrinamicRegister> for u8 {
+    fn from(register: TrinamicRegister) -> Self {
+        match register {
+            TrinamicRegister::GlobalConfig => 0x00,
+            TrinamicRegister::GlobalStatus => 0x01,
+            TrinamicRegister::InterfaceCounter => 0x02,
+            TrinamicRegister::SlaveConfig => 0x03,
+            TrinamicRegister::Inputs => 0x06,
+            TrinamicRegister::HoldRunCurrent => 0x10,
+            TrinamicRegister::PowerDownDelay => 0x11,
+            TrinamicRegister::StepTime => 0x12,
+            TrinamicRegister::StealthChopThreshold => 0x13,
+            TrinamicRegister::CoolStepThreshold => 0x14,
+            TrinamicRegister::Velocity => 0x22,
+            TrinamicRegister::StallGuardThreshold => 0x40,
+            TrinamicRegister::StallGuardResult => 0x41,
+            TrinamicRegister::CoolStepConfig => 0x42,
+            TrinamicRegister::MicrostepCounter => 0x6a,
+            TrinamicRegister::ChopperConfig => 0x6c,
+            TrinamicRegister::DriverStatus => 0x6f,
+            TrinamicRegister::StealthChopConfig => 0x70,
+        }
+    }
+}
+
+/// The errors of the communication with the driver.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TrinamicError<E> {
+    /// The transfer of the datagram failed.
+    Bus(E),
+    /// The CRC of the received datagram does not match.
+    Crc,
+    /// The received datagram is not the reply to the read request.
+    Frame,
+}
+
+/// The number of microsteps is not supported by the driver, it is not a power of two up to 256.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct InvalidMicrosteps;
+
+/// Returns the CRC8 of the `data` as specified by Trinamic.
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        let mut byte = *byte;
+        for _ in 0..8 {
+            if (crc >> 7) ^ (byte & 0x01) != 0 {
+                crc = (crc << 1) ^ 0x07;
+            } else {
+                crc <<= 1;
+            }
+            byte >>= 1;
+        }
+    }
+    crc
+}
+
+/// Returns the datagram writing the `value` to the `register` of the driver with the `address`.
+pub fn write_datagram(
+    address: u8,
+    register: TrinamicRegister,
+    value: u32,
+) -> [u8; DATAGRAM_LENGTH] {
+    let mut datagram = [0u8; DATAGRAM_LENGTH];
+    datagram[0] = SYNC;
+    datagram[1] = address;
+    datagram[2] = u8::from(register) | WRITE;
+    datagram[3..7].copy_from_slice(&value.to_be_bytes());
+    datagram[7] = crc8(&datagram[..7]);
+    datagram
+}
+
+/// Returns the request reading the `register` of the driver with the `address`.
+pub fn read_request(address: u8, register: TrinamicRegister) -> [u8; READ_REQUEST_LENGTH] {
+    let mut request = [SYNC, address, u8::from(register), 0];
+    request[3] = crc8(&request[..3]);
+    request
+}
+
+/// Returns the value of the `register` carried by the `reply` to the read request.
+pub fn parse_reply<E>(
+    reply: &[u8; DATAGRAM_LENGTH],
+    register: TrinamicRegister,
+) -> Result<u32, TrinamicError<E>> {
+    if crc8(&reply[..7]) != reply[7] {
+        Err(TrinamicError::Crc)
+    } else if reply[0] & 0x0f != SYNC
+        || reply[1] != MASTER_ADDRESS
+        || reply[2] != u8::from(register)
+    {
+        Err(TrinamicError::Frame)
+    } else {
+        Ok(u32::from_be_bytes([reply[3], reply[4], reply[5], reply[6]]))
+    }
+}
+
+/// Returns `width` bits of the `value` starting at the bit `offset`.
+fn bits(value: u32, offset: u8, width: u8) -> u8 {
+    ((value >> offset) & ((1 << width) - 1)) as u8
+}
+
+fn bit(value: u32, offset: u8) -> bool {
+    value & (1 << offset) != 0
+}
+
+/// The global configuration of the driver (GCONF).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GlobalConfig {
+    /// The current is scaled by the voltage on the VREF pin instead of the internal reference.
+    pub analog_current_scale: bool,
+    pub internal_sense_resistors: bool,
+    /// The SpreadCycle chopper is used instead of StealthChop.
+    pub spread_cycle: bool,
+    /// Inverts the direction of the motor.
+    pub shaft: bool,
+    pub index_overtemperature_warning: bool,
+    pub index_step: bool,
+    /// The PDN_UART pin is used for the UART only, the power down function is disabled.
+    pub power_down_disabled: bool,
+    /// The microstep resolution is selected by the [ChopperConfig] instead of the MS1 and MS2 pins.
+    pub microsteps_by_register: bool,
+    pub step_pulse_filter: bool,
+}
+
+impl GlobalConfig {
+    /// Returns the configuration of a driver controlled over the UART,
+    /// the current and the microstep resolution are set by the registers.
+    pub fn uart_controlled() -> Self {
+        Self {
+            analog_current_scale: false,
+            power_down_disabled: true,
+            microsteps_by_register: true,
+            ..Self::from(0x0000_0101)
+        }
+    }
+}
+
+impl From<u32> for GlobalConfig {
+    fn from(raw: u32) -> Self {
+        Self {
+            analog_current_scale: bit(raw, 0),
+            internal_sense_resistors: bit(raw, 1),
+            spread_cycle: bit(raw, 2),
+            shaft: bit(raw, 3),
+            index_overtemperature_warning: bit(raw, 4),
+            index_step: bit(raw, 5),
+            power_down_disabled: bit(raw, 6),
+            microsteps_by_register: bit(raw, 7),
+            step_pulse_filter: bit(raw, 8),
+        }
+    }
+}
+
+impl From<GlobalConfig> for u32 {
+    fn from(config: GlobalConfig) -> Self {
+        config.analog_current_scale as u32
+            | (config.internal_sense_resistors as u32) << 1
+            | (config.spread_cycle as u32) << 2
+            | (config.shaft as u32) << 3
+            | (config.index_overtemperature_warning as u32) << 4
+            | (config.index_step as u32) << 5
+            | (config.power_down_disabled as u32) << 6
+            | (config.microsteps_by_register as u32) << 7
+            | (config.step_pulse_filter as u32) << 8
+    }
+}
+
+/// The global status flags of the driver (GSTAT), the flags are cleared by writing them back.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GlobalStatus {
+    /// The driver has been reset since the flag was cleared.
+    pub reset: bool,
+    /// The driver has been shut down due to the overtemperature or a short circuit.
+    pub driver_error: bool,
+    pub charge_pump_undervoltage: bool,
+}
+
+impl From<u32> for GlobalStatus {
+    fn from(raw: u32) -> Self {
+        Self {
+            reset: bit(raw, 0),
+            driver_error: bit(raw, 1),
+            charge_pump_undervoltage: bit(raw, 2),
+        }
+    }
+}
+
+impl From<GlobalStatus> for u32 {
+    fn from(status: GlobalStatus) -> Self {
+        status.reset as u32
+            | (status.driver_error as u32) << 1
+            | (status.charge_pump_undervoltage as u32) << 2
+    }
+}
+
+/// The motor current settings (IHOLD_IRUN), the currents are scaled in 32 steps of the full scale current.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HoldRunCurrent {
+    /// The standstill current scale, 0 to 31.
+    pub hold: u8,
+    /// The motor run current scale, 0 to 31.
+    pub run: u8,
+    /// The number of clock cycles times 2^18 of the power down ramp to the hold current, 0 to 15.
+    pub hold_delay: u8,
+}
+
+impl From<u32> for HoldRunCurrent {
+    fn from(raw: u32) -> Self {
+        Self {
+            hold: bits(raw, 0, 5),
+            run: bits(raw, 8, 5),
+            hold_delay: bits(raw, 16, 4),
+        }
+    }
+}
+
+impl From<HoldRunCurrent> for u32 {
+    fn from(current: HoldRunCurrent) -> Self {
+        (current.hold & 0x1f) as u32
+            | ((current.run & 0x1f) as u32) << 8
+            | ((current.hold_delay & 0x0f) as u32) << 16
+    }
+}
+
+/// The chopper and the microstep resolution configuration (CHOPCONF).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ChopperConfig {
+    /// The off time of the chopper, zero disables the driver.
+    pub off_time: u8,
+    pub hysteresis_start: u8,
+    pub hysteresis_end: u8,
+    pub blank_time: u8,
+    /// The high sensitivity of the sense resistor voltage, which lowers the full scale current.
+    pub high_sensitivity: bool,
+    /// The microstep resolution, 0 for 256 microsteps to 8 for full steps.
+    pub microstep_resolution: u8,
+    /// The microsteps are interpolated to 256 microsteps.
+    pub interpolation: bool,
+    pub double_edge: bool,
+    pub short_to_ground_protection_disabled: bool,
+    pub short_to_supply_protection_disabled: bool,
+}
+
+impl ChopperConfig {
+    /// Returns the number of microsteps per a full step.
+    pub fn microsteps(&self) -> u16 {
+        256 >> self.microstep_resolution.min(8)
+    }
+
+    /// Sets the number of microsteps per a full step, it shall be a power of two up to 256.
+    pub fn set_microsteps(&mut self, microsteps: u16) -> Result<(), InvalidMicrosteps> {
+        if !microsteps.is_power_of_two() || microsteps > 256 {
+            return Err(InvalidMicrosteps);
+        }
+        self.microstep_resolution = 8 - microsteps.trailing_zeros() as u8;
+        Ok(())
+    }
+}
+
+/// The configuration after the reset of the driver.
+impl Default for ChopperConfig {
+    fn default() -> Self {
+        Self::from(0x1000_0053)
+    }
+}
+
+impl From<u32> for ChopperConfig {
+    fn from(raw: u32) -> Self {
+        Self {
+            off_time: bits(raw, 0, 4),
+            hysteresis_start: bits(raw, 4, 3),
+            hysteresis_end: bits(raw, 7, 4),
+            blank_time: bits(raw, 15, 2),
+            high_sensitivity: bit(raw, 17),
+            microstep_resolution: bits(raw, 24, 4),
+            interpolation: bit(raw, 28),
+            double_edge: bit(raw, 29),
+            short_to_ground_protection_disabled: bit(raw, 30),
+            short_to_supply_protection_disabled: bit(raw, 31),
+        }
+    }
+}
+
+impl From<ChopperConfig> for u32 {
+    fn from(config: ChopperConfig) -> Self {
+        (config.off_time & 0x0f) as u32
+            | ((config.hysteresis_start & 0x07) as u32) << 4
+            | ((config.hysteresis_end & 0x0f) as u32) << 7
+            | ((config.blank_time & 0x03) as u32) << 15
+            | (config.high_sensitivity as u32) << 17
+            | ((config.microstep_resolution & 0x0f) as u32) << 24
+            | (config.interpolation as u32) << 28
+            | (config.double_edge as u32) << 29
+            | (config.short_to_ground_protection_disabled as u32) << 30
+            | (config.short_to_supply_protection_disabled as u32) << 31
+    }
+}
+
+/// The status of the driver (DRV_STATUS).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DriverStatus {
+    pub overtemperature_warning: bool,
+    pub overtemperature: bool,
+    pub short_to_ground_a: bool,
+    pub short_to_ground_b: bool,
+    pub short_to_supply_a: bool,
+    pub short_to_supply_b: bool,
+    pub open_load_a: bool,
+    pub open_load_b: bool,
+    /// The temperature thresholds of 120, 143, 150 and 157 °C are exceeded.
+    pub temperature_thresholds: [bool; 4],
+    /// The actual current scale, 0 to 31.
+    pub actual_current: u8,
+    /// The driver operates in StealthChop.
+    pub stealth_chop: bool,
+    pub standstill: bool,
+}
+
+impl DriverStatus {
+    /// Returns true when the driver has been shut down by the overtemperature or a short circuit.
+    pub fn is_fault(&self) -> bool {
+        self.overtemperature
+            || self.short_to_ground_a
+            || self.short_to_ground_b
+            || self.short_to_supply_a
+            || self.short_to_supply_b
+    }
+}
+
+impl From<u32> for DriverStatus {
+    fn from(raw: u32) -> Self {
+        Self {
+            overtemperature_warning: bit(raw, 0),
+            overtemperature: bit(raw, 1),
+            short_to_ground_a: bit(raw, 2),
+            short_to_ground_b: bit(raw, 3),
+            short_to_supply_a: bit(raw, 4),
+            short_to_supply_b: bit(raw, 5),
+            open_load_a: bit(raw, 6),
+            open_load_b: bit(raw, 7),
+            temperature_thresholds: [bit(raw, 8), bit(raw, 9), bit(raw, 10), bit(raw, 11)],
+            actual_current: bits(raw, 16, 5),
+            stealth_chop: bit(raw, 30),
+            standstill: bit(raw, 31),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datagrams() {
+        assert_eq!(crc8(&[0x05, 0x00, 0x02]), 0x8f);
+        assert_eq!(
+            read_request(0, TrinamicRegister::InterfaceCounter),
+            [0x05, 0x00, 0x02, 0x8f]
+        );
+        assert_eq!(
+            write_datagram(0, TrinamicRegister::GlobalConfig, 0xc0),
+            [0x05, 0x00, 0x80, 0x00, 0x00, 0x00, 0xc0, 0x40]
+        );
+    }
+
+    #[test]
+    fn replies() {
+        let reply = [0x05, 0xff, 0x02, 0x00, 0x00, 0x00, 0x01, 0xc5];
+        assert_eq!(
+            parse_reply::<()>(&reply, TrinamicRegister::InterfaceCounter),
+            Ok(1)
+        );
+        assert_eq!(
+            parse_reply::<()>(&reply, TrinamicRegister::GlobalConfig),
+            Err(TrinamicError::Frame)
+        );
+        let mut corrupted = reply;
+        corrupted[6] = 0x03;
+        assert_eq!(
+            parse_reply::<()>(&corrupted, TrinamicRegister::InterfaceCounter),
+            Err(TrinamicError::Crc)
+        );
+    }
+
+    #[test]
+    fn registers() {
+        let config = GlobalConfig::uart_controlled();
+        assert_eq!(u32::from(config), 0x0000_01c0);
+        assert_eq!(GlobalConfig::from(u32::from(config)), config);
+
+        let current = HoldRunCurrent {
+            hold: 8,
+            run: 31,
+            hold_delay: 1,
+        };
+        assert_eq!(u32::from(current), 0x0001_1f08);
+        assert_eq!(HoldRunCurrent::from(0x0001_1f08), current);
+
+        let mut chopper = ChopperConfig::default();
+        assert_eq!(chopper.microsteps(), 256);
+        assert_eq!(chopper.off_time, 3);
+        chopper.set_microsteps(16).unwrap();
+        assert_eq!(chopper.microstep_resolution, 4);
+        assert_eq!(u32::from(chopper), 0x1400_0053);
+        assert_eq!(chopper.set_microsteps(12), Err(InvalidMicrosteps));
+        assert_eq!(chopper.set_microsteps(512), Err(InvalidMicrosteps));
+
+        let status = DriverStatus::from(0x8010_0002);
+        assert!(status.overtemperature);
+        assert!(status.standstill);
+        assert_eq!(status.actual_current, 16);
+        assert!(status.is_fault());
+        assert!(!DriverStatus::from(0x0000_0001).is_fault());
+    }
+}
@@ -0,0 +1,173 @@
+//! Conversion between the revolutions of the motor and the user units of an axis (e.g. millimeters or degrees).
+//!
+//! The user position is `polarity * units_per_revolution * numerator / denominator * motor revolutions`,
+//! where the numerator and the denominator describe the gearbox between the motor and the output shaft
+//! and the units per revolution describe the mechanics driven by the output shaft, e.g. the pitch of a lead screw.
+use crate::models::{Position, Velocity};
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// The number of fixed-point increments in a single user unit.
+pub const FIXED_POINT_SCALE: f32 = 1000.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct UnitScaling {
+    units_per_revolution: f32,
+    numerator: i32,
+    denominator: i32,
+    inverted: bool,
+}
+
+impl UnitScaling {
+    /// Creates new unit scaling.
+    ///
+    /// # Arguments
+    /// * `units_per_revolution` - the user units travelled by a single revolution of the output shaft
+    /// * `numerator` - the revolutions of the output shaft per `denominator` revolutions of the motor
+    /// * `denominator` - the revolutions of the motor per `numerator` revolutions of the output shaft, shall not be zero
+    /// * `inverted` - the user units grow when the motor turns in the negative direction
+    ///
+    /// # Example
+    /// ```
+    /// use sm4_shared::prelude::UnitScaling;
+    ///
+    /// // a lead screw with the pitch of 8 mm behind a 1:2 reduction
+    /// let scaling = UnitScaling::new(8.0, 1, 2, false);
+    /// assert_eq!(scaling.to_units(3.0), 12.0);
+    /// assert_eq!(scaling.from_units(12.0), 3.0);
+    /// ```
+    pub const fn new(
+        units_per_revolution: f32,
+        numerator: i32,
+        denominator: i32,
+        inverted: bool,
+    ) -> Self {
+        Self {
+            units_per_revolution,
+            numerator,
+            denominator,
+            inverted,
+        }
+    }
+
+    pub fn units_per_revolution(&self) -> f32 {
+        self.units_per_revolution
+    }
+    pub fn numerator(&self) -> i32 {
+        self.numerator
+    }
+    pub fn denominator(&self) -> i32 {
+        self.denominator
+    }
+    pub fn inverted(&self) -> bool {
+        self.inverted
+    }
+    pub fn set_units_per_revolution(&mut self, units_per_revolution: f32) {
+        self.units_per_revolution = units_per_revolution;
+    }
+    pub fn set_numerator(&mut self, numerator: i32) {
+        self.numerator = numerator;
+    }
+    pub fn set_denominator(&mut self, denominator: i32) {
+        self.denominator = denominator;
+    }
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    /// Returns the user units per a single revolution of the motor, zero when the scaling is not valid.
+    pub fn factor(&self) -> f32 {
+        if self.denominator == 0 {
+            return 0.0;
+        }
+        let factor = self.units_per_revolution * self.numerator as f32 / self.denominator as f32;
+        if self.inverted {
+            -factor
+        } else {
+            factor
+        }
+    }
+
+    /// Converts the motor `revolutions` to user units.
+    pub fn to_units(&self, revolutions: f32) -> f32 {
+        revolutions * self.factor()
+    }
+
+    /// Converts the user `units` to motor revolutions, returns zero when the scaling is not valid.
+    pub fn from_units(&self, units: f32) -> f32 {
+        let factor = self.factor();
+        if factor == 0.0 {
+            0.0
+        } else {
+            units / factor
+        }
+    }
+
+    pub fn position_to_units<const RESOLUTION: u32>(&self, position: &Position<RESOLUTION>) -> f32 {
+        self.to_units(position.get_relative_revolutions())
+    }
+
+    pub fn position_from_units<const RESOLUTION: u32>(&self, units: f32) -> Position<RESOLUTION> {
+        let revolutions = self.from_units(units);
+        let whole = revolutions.floor();
+        Position::new(
+            whole as i32,
+            ((revolutions - whole) * RESOLUTION as f32).round() as u32,
+        )
+    }
+
+    pub fn velocity_to_units(&self, velocity: &Velocity) -> f32 {
+        self.to_units(velocity.get_rps())
+    }
+
+    pub fn velocity_from_units(&self, units: f32) -> Velocity {
+        Velocity::new(self.from_units(units))
+    }
+}
+
+impl Default for UnitScaling {
+    fn default() -> Self {
+        Self::new(1.0, 1, 1, false)
+    }
+}
+
+/// Converts the user `units` to the fixed-point representation.
+pub fn to_fixed_point(units: f32) -> i32 {
+    (units * FIXED_POINT_SCALE).round() as i32
+}
+
+/// Converts the fixed-point representation to user units.
+pub fn from_fixed_point(raw: i32) -> f32 {
+    raw as f32 / FIXED_POINT_SCALE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn scaling() {
+        let scaling = UnitScaling::new(360.0, 1, 4, true);
+        assert!((scaling.to_units(2.0) + 180.0).abs() < EPSILON);
+        assert!((scaling.from_units(-180.0) - 2.0).abs() < EPSILON);
+        assert!((scaling.velocity_to_units(&Velocity::new(1.0)) + 90.0).abs() < EPSILON);
+
+        let invalid = UnitScaling::new(360.0, 1, 0, false);
+        assert_eq!(invalid.to_units(1.0), 0.0);
+        assert_eq!(invalid.from_units(1.0), 0.0);
+    }
+
+    #[test]
+    fn position() {
+        let scaling = UnitScaling::new(8.0, 1, 1, false);
+        let position = scaling.position_from_units::<4>(-10.0);
+        assert_eq!(position.get_revolutions(), -2);
+        assert_eq!(position.get_angle(), 3);
+        assert!((scaling.position_to_units(&position) + 10.0).abs() < EPSILON);
+
+        assert_eq!(to_fixed_point(-1.2345), -1235);
+        assert!((from_fixed_point(1500) - 1.5).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,318 @@
+//! Estimation of the velocity from the positions sampled by an encoder.
+//!
+//! The velocity calculated as the difference of two consecutive positions is heavily quantised at low speeds,
+//! because only a few increments pass between the samples. The estimator smooths the difference
+//! with one of the filters selected by [VelocityFilter]:
+//! * the moving average of the differences over a window of samples,
+//! * the first-order low-pass (IIR) filter of the differences,
+//! * the tracking observer (alpha-beta filter, equivalent to a PLL), which estimates both the position
+//!   and the velocity and corrects them by the error of the estimated position.
+//!
+//! The moving average and the low-pass filter delay the estimate, the observer follows a constant acceleration
+//! without a steady-state error. The cutoff frequency shall be well below the sampling frequency.
+use crate::encoder::{Direction, Encoder};
+use crate::models::{Position, Velocity, VelocityFilter};
+use core::f32::consts::PI;
+use embedded_time::duration::Microseconds;
+#[cfg(not(any(test, feature = "std")))]
+use num_traits::Float;
+
+/// The maximum number of samples averaged by the moving average.
+pub const MAX_VELOCITY_WINDOW: u8 = 16;
+
+#[derive(Copy, Clone)]
+pub struct VelocityEstimatorSettings {
+    filter: VelocityFilter,
+    window: u8,
+    cutoff: f32,
+}
+
+impl VelocityEstimatorSettings {
+    /// Creates new velocity estimator settings.
+    ///
+    /// # Arguments
+    /// * `filter` - the filter applied to the velocity
+    /// * `window` - the number of samples averaged by the moving average, up to [MAX_VELOCITY_WINDOW]
+    /// * `cutoff` - the cutoff frequency of the low-pass filter and the bandwidth of the observer in Hz
+    pub fn new(filter: VelocityFilter, window: u8, cutoff: f32) -> Self {
+        Self {
+            filter,
+            window,
+            cutoff,
+        }
+    }
+
+    pub fn filter(&self) -> VelocityFilter {
+        self.filter
+    }
+    pub fn window(&self) -> u8 {
+        self.window
+    }
+    pub fn cutoff(&self) -> f32 {
+        self.cutoff
+    }
+    pub fn set_filter(&mut self, filter: VelocityFilter) {
+        self.filter = filter;
+    }
+    pub fn set_window(&mut self, window: u8) {
+        self.window = window;
+    }
+    pub fn set_cutoff(&mut self, cutoff: f32) {
+        self.cutoff = cutoff;
+    }
+}
+
+impl Default for VelocityEstimatorSettings {
+    fn default() -> Self {
+        Self::new(VelocityFilter::Difference, 4, 20.0)
+    }
+}
+
+/// Estimator of the velocity from the positions sampled with a fixed period.
+pub struct VelocityEstimator {
+    period: f32,
+    /// The last sampled position in increments, `None` before the first sample.
+    past_increments: Option<i32>,
+    /// The differences of the last samples in revolutions per second.
+    differences: [f32; MAX_VELOCITY_WINDOW as usize],
+    /// The index where the next difference is stored.
+    next: usize,
+    /// The deviation of the position estimated by the observer from the sampled position in revolutions.
+    deviation: f32,
+    velocity: f32,
+}
+
+impl VelocityEstimator {
+    pub fn new(sampling_period: Microseconds) -> Self {
+        Self {
+            period: sampling_period.0 as f32 / 1_000_000.0,
+            past_increments: None,
+            differences: [0.0; MAX_VELOCITY_WINDOW as usize],
+            next: 0,
+            deviation: 0.0,
+            velocity: 0.0,
+        }
+    }
+
+    /// Forgets the sampled positions, the estimated velocity is zero.
+    pub fn reset(&mut self) {
+        self.past_increments = None;
+        self.differences = [0.0; MAX_VELOCITY_WINDOW as usize];
+        self.next = 0;
+        self.deviation = 0.0;
+        self.velocity = 0.0;
+    }
+
+    /// Returns the last estimated velocity.
+    pub fn velocity(&self) -> Velocity {
+        Velocity::new(self.velocity)
+    }
+
+    /// Updates the estimate with the `position` sampled in the current period and returns the estimated velocity.
+    pub fn estimate<const RESOLUTION: u32>(
+        &mut self,
+        position: &Position<RESOLUTION>,
+        settings: &VelocityEstimatorSettings,
+    ) -> Velocity {
+        let increments = position.get_increments();
+        let step = match self.past_increments {
+            Some(past) => (increments - past) as f32 / RESOLUTION as f32,
+            None => 0.0,
+        };
+        self.past_increments = Some(increments);
+
+        let difference = step / self.period;
+        self.differences[self.next] = difference;
+        self.next = (self.next + 1) % self.differences.len();
+
+        self.velocity = match settings.filter {
+            VelocityFilter::Difference => difference,
+            VelocityFilter::MovingAverage => {
+                let window = settings.window.clamp(1, MAX_VELOCITY_WINDOW) as usize;
+                let len = self.differences.len();
+                (1..=window)
+                    .map(|age| self.differences[(self.next + len - age) % len])
+                    .sum::<f32>()
+                    / window as f32
+            }
+            VelocityFilter::LowPass => {
+                let alpha = 1.0 - (-2.0 * PI * settings.cutoff * self.period).exp();
+                self.velocity + alpha * (difference - self.velocity)
+            }
+            VelocityFilter::Observer => {
+                // critically damped second-order loop with the natural frequency of the bandwidth
+                let omega = 2.0 * PI * settings.cutoff;
+                self.deviation += self.velocity * self.period - step;
+                let error = -self.deviation;
+                self.deviation += 2.0 * omega * self.period * error;
+                self.velocity + omega * omega * self.period * error
+            }
+        };
+        // the observer starts from the sampled position whenever it is selected
+        if settings.filter != VelocityFilter::Observer {
+            self.deviation = 0.0;
+        }
+
+        self.velocity()
+    }
+}
+
+/// Encoder adapter providing the velocity estimated from the positions of the wrapped encoder.
+/// The position is provided by the wrapped encoder without any change.
+pub struct FilteredEncoder<E: Encoder<RESOLUTION>, const RESOLUTION: u32> {
+    encoder: E,
+    estimator: VelocityEstimator,
+    settings: VelocityEstimatorSettings,
+}
+
+impl<E: Encoder<RESOLUTION>, const RESOLUTION: u32> FilteredEncoder<E, RESOLUTION> {
+    /// Wraps the `encoder` sampled with the `sampling_period`.
+    pub fn new(encoder: E, sampling_period: Microseconds) -> Self {
+        Self {
+            encoder,
+            estimator: VelocityEstimator::new(sampling_period),
+            settings: Default::default(),
+        }
+    }
+
+    pub fn settings(&self) -> VelocityEstimatorSettings {
+        self.settings
+    }
+
+    /// Returns the wrapped encoder.
+    pub fn inner(&self) -> &E {
+        &self.encoder
+    }
+}
+
+impl<E: Encoder<RESOLUTION>, const RESOLUTION: u32> Encoder<RESOLUTION>
+    for FilteredEncoder<E, RESOLUTION>
+{
+    fn get_velocity(&self) -> Velocity {
+        self.estimator.velocity()
+    }
+
+    fn get_position(&self) -> Position<RESOLUTION> {
+        self.encoder.get_position()
+    }
+
+    fn reset_position(&mut self) -> Position<RESOLUTION> {
+        self.estimator.reset();
+        self.encoder.reset_position()
+    }
+
+    fn sample(&mut self) {
+        self.encoder.sample();
+        self.estimator
+            .estimate(&self.encoder.get_position(), &self.settings);
+    }
+
+    fn notify_direction_changed(&mut self, direction: Direction) {
+        self.encoder.notify_direction_changed(direction);
+    }
+
+    fn set_velocity_estimator(&mut self, settings: &VelocityEstimatorSettings) {
+        self.settings = *settings;
+    }
+
+    fn set_step_resolution(&mut self, steps_per_revolution: u32) {
+        self.encoder.set_step_resolution(steps_per_revolution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::mock::{MockEncoder, ENCODER_RESOLUTION};
+
+    const RESOLUTION: u32 = 3200;
+    const PERIOD: Microseconds = Microseconds(1000);
+
+    /// Samples the positions of an axis moving with the `velocity` (revolutions per second)
+    /// and the `acceleration` (revolutions per second squared) and returns the last estimate.
+    fn track(
+        estimator: &mut VelocityEstimator,
+        settings: &VelocityEstimatorSettings,
+        velocity: f32,
+        acceleration: f32,
+        samples: u32,
+    ) -> f32 {
+        let mut estimate = 0.0;
+        for sample in 0..samples {
+            let time = sample as f32 * 0.001;
+            let revolutions = velocity * time + acceleration * time * time / 2.0;
+            let mut position = Position::<RESOLUTION>::zero();
+            position += (revolutions * RESOLUTION as f32).floor() as i32;
+            estimate = estimator.estimate(&position, settings).get_rps();
+        }
+        estimate
+    }
+
+    #[test]
+    fn quantised_difference() {
+        // 0.1 increment per sample, the difference is either zero or ten times the velocity
+        let velocity = 0.1 / RESOLUTION as f32 * 1000.0;
+        let settings = VelocityEstimatorSettings::default();
+        let mut estimator = VelocityEstimator::new(PERIOD);
+        let estimate = track(&mut estimator, &settings, velocity, 0.0, 1001);
+        assert!(estimate == 0.0 || (estimate - 10.0 * velocity).abs() < 1e-3);
+    }
+
+    #[test]
+    fn moving_average() {
+        // a quarter of an increment per sample is averaged exactly over four samples
+        let velocity = 0.25 / RESOLUTION as f32 * 1000.0;
+        let settings = VelocityEstimatorSettings::new(VelocityFilter::MovingAverage, 4, 0.0);
+        let mut estimator = VelocityEstimator::new(PERIOD);
+        for samples in 100..110 {
+            estimator.reset();
+            let estimate = track(&mut estimator, &settings, velocity, 0.0, samples);
+            assert!((estimate - velocity).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn low_pass() {
+        let velocity = 0.1 / RESOLUTION as f32 * 1000.0;
+        let settings = VelocityEstimatorSettings::new(VelocityFilter::LowPass, 0, 5.0);
+        let mut estimator = VelocityEstimator::new(PERIOD);
+        let estimate = track(&mut estimator, &settings, velocity, 0.0, 2000);
+        assert!((estimate - velocity).abs() < 0.5 * velocity);
+    }
+
+    #[test]
+    fn observer() {
+        let settings = VelocityEstimatorSettings::new(VelocityFilter::Observer, 0, 20.0);
+        let mut estimator = VelocityEstimator::new(PERIOD);
+        let velocity = 0.1 / RESOLUTION as f32 * 1000.0;
+        let estimate = track(&mut estimator, &settings, velocity, 0.0, 2000);
+        assert!((estimate - velocity).abs() < 0.5 * velocity);
+
+        // the observer follows the accelerating axis
+        let mut estimator = VelocityEstimator::new(PERIOD);
+        let estimate = track(&mut estimator, &settings, 0.0, 2.0, 1001);
+        assert!((estimate - 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn filtered_encoder() {
+        let mut encoder = FilteredEncoder::new(MockEncoder::new(), PERIOD);
+        encoder.set_velocity_estimator(&VelocityEstimatorSettings::new(
+            VelocityFilter::MovingAverage,
+            2,
+            0.0,
+        ));
+        encoder.sample();
+        encoder.sample();
+        // the first sample has no past position to be compared with
+        let velocity = 1000.0 / ENCODER_RESOLUTION as f32;
+        assert!((encoder.get_velocity().get_rps() - velocity / 2.0).abs() < 1e-3);
+        encoder.sample();
+        assert!((encoder.get_velocity().get_rps() - velocity).abs() < 1e-3);
+        assert_eq!(encoder.get_position().get_increments(), 3);
+
+        encoder.reset_position();
+        assert_eq!(encoder.get_velocity().get_rps(), 0.0);
+        assert_eq!(encoder.get_position().get_increments(), 0);
+    }
+}